@@ -0,0 +1,14 @@
+use ai_functions::ai_function;
+
+#[ai_function]
+pub fn print_protected_routes(_project_description: &str) {
+    /// INPUT: Takes in a PROJECT_DESCRIPTION for a website backend where users sign up, log in and log out
+    /// FUNCTION: Lists the paths of the backend that only a logged in user may call
+    /// IMPORTANT: Sign up, login and logout are served at "/signup", "/login" and "/logout". Never list them.
+    /// IMPORTANT: Paths start with "/" and use actix-web placeholders such as "/todos/{id}". Every method on a listed path is protected.
+    /// IMPORTANT: If API_CONTRACT_PATHS are given, only list paths from them, written exactly as given
+    /// IMPORTANT: If PROTECTED_ROUTE_PROBLEMS are given, PREVIOUS_PROTECTED_ROUTES had those problems. Fix every one of them.
+    /// OUTPUT: Prints ONLY the JSON array of paths, nothing else. For example:
+    ///   ["/todos", "/todos/{id}", "/profile"]
+    println!(OUTPUT)
+}
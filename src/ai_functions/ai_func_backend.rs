@@ -9,6 +9,15 @@ pub fn print_backend_webserver_code(_project_description_and_template: &str) {
     /// IMPORTANT: The following libraries are already installed
     ///   reqwest, serde, serde_json, tokio, actix-web, async-trait, actix_cors
     /// No other external libraries should be used. Write functions that fit with the description from the PROJECT_DESCRIPTION
+    /// IMPORTANT: The server binds to the HOST and PORT environment variables, falling back to "127.0.0.1" and 8080 when they are not set
+    /// IMPORTANT: If EXTERNAL_APIS are given, call those urls and parse their responses using the listed content_type, sample_payload and payload_shape
+    /// IMPORTANT: If EXTERNAL_API_TYPES are given, copy those modules into the code unchanged and deserialize each external response into its generated type
+    /// IMPORTANT: If an API_CONTRACT is given, serve every operation it declares at exactly its path and method, accept its request bodies and answer with its success status codes and response schemas
+    /// IMPORTANT: If a DATA_ACCESS_LAYER is given, it already exists next to main.rs and rusqlite is installed for it. Declare it with mod <module>; instead of copying it
+    /// IMPORTANT: With a DATA_ACCESS_LAYER, open the database once with its open function at the DATABASE_PATH environment variable, falling back to "app.db", share it as web::Data<Mutex<rusqlite::Connection>> and store every entity through its functions instead of in memory or a JSON file
+    /// IMPORTANT: If an AUTH_MODULE is given, it already exists next to main.rs and its libraries are installed. Declare it with mod <module>; and do not write any other password, session or token code
+    /// IMPORTANT: With an AUTH_MODULE, create its AuthState once, register it with .app_data(..), wrap the App with .wrap(<module>::RequireLogin) and register .route("/signup", web::post().to(<module>::signup)), .route("/login", web::post().to(<module>::login)) and .route("/logout", web::post().to(<module>::logout))
+    /// IMPORTANT: With an AUTH_MODULE, serve every path in PROTECTED_ROUTES at exactly that path. Handlers that need the logged in user take a <module>::AuthenticatedUser argument
     /// OUTPUT: Print ONLY the code, nothing else. This function ONLY prints code.
     println!(OUTPUT)
 }
@@ -22,17 +31,34 @@ pub fn print_improved_webserver_code(_project_description_and_template: &str) {
     ///   3. ONLY writes the code. No commentary.
     /// IMPORTANT: The following libraries are already installed. Does not use ANY libraries other than what was provided in the template
     ///   reqwest, serde, serde_json, tokio, actix-web, async-trait
+    /// IMPORTANT: The server binds to the HOST and PORT environment variables, falling back to "127.0.0.1" and 8080 when they are not set
+    /// IMPORTANT: Responses from external urls are parsed using the sample_payload and payload_shape in external_api_profiles
+    /// IMPORTANT: If EXTERNAL_API_TYPES are given, keep those modules unchanged and deserialize each external response into its generated type
+    /// IMPORTANT: If an API_CONTRACT is given, keep serving every operation it declares exactly as declared
+    /// IMPORTANT: If a DATA_ACCESS_LAYER is given, rusqlite is installed for it. Keep the mod <module>; declaration and store every entity through its functions. Do not copy the module into the code
+    /// IMPORTANT: If an AUTH_MODULE is given, keep the mod <module>; declaration, its AuthState, the RequireLogin wrap and the /signup, /login and /logout routes, and keep serving every path in PROTECTED_ROUTES
     println!(OUTPUT)
 }
 
 #[ai_function]
 pub fn print_fixed_code(_broken_code_with_bugs: &str) {
     /// INPUT: Takes in Rust BROKEN_CODE and the ERROR_BUGS found
+    /// IMPORTANT: ERROR_BUGS are either compiler errors or RUNTIME ERRORS, which list the failing route, its status and the logs the server printed
     /// FUNCTION: Removes bugs from code
     /// IMPORTANT: Only prints out the new and improved code. No commentary or anything else
     println!(OUTPUT)
 }
 
+#[ai_function]
+pub fn print_lint_fixed_code(_code_with_lint_warnings: &str) {
+    /// INPUT: Takes in Rust CODE_INPUT that compiles and the LINT_WARNINGS reported by cargo clippy
+    /// FUNCTION: Rewrites the code so that every lint warning is resolved without changing behaviour
+    /// IMPORTANT: Routes, handlers, structs and their fields must stay exactly the same
+    /// IMPORTANT: Do not silence lints with #[allow(...)] attributes. Fix the underlying code instead.
+    /// IMPORTANT: Only prints out the new and improved code. No commentary or anything else
+    println!(OUTPUT)
+}
+
 #[ai_function]
 pub fn print_rest_api_endpoints(_code_input: &str) {
     /// INPUT: Takes in Rust webserver CODE_INPUT based on actix-web
@@ -0,0 +1,35 @@
+use ai_functions::ai_function;
+
+#[ai_function]
+pub fn print_data_model(_project_description: &str) {
+    /// INPUT: Takes in a PROJECT_DESCRIPTION for a website backend that stores data
+    /// FUNCTION: Designs the entity/relationship model of the data the website stores
+    /// IMPORTANT: Every entity gets an integer "id" primary key automatically. Do not list it as a field.
+    /// IMPORTANT: Do not list fields that reference other entities. Declare a relationship instead.
+    /// IMPORTANT: Field types are one of "integer", "real", "text", "boolean" or "timestamp"
+    /// IMPORTANT: Relationship kinds are one of "one_to_one", "one_to_many" or "many_to_many". In "one_to_many", one "from" has many "to".
+    /// IMPORTANT: If DATA_MODEL_PROBLEMS are given, PREVIOUS_DATA_MODEL had those problems. Fix every one of them.
+    /// OUTPUT: Prints ONLY the JSON object of the model, nothing else. For example:
+    ///   {
+    ///     "entities": [
+    ///       {
+    ///         "name": "User",
+    ///         "fields": [
+    ///           { "name": "email", "type": "text", "is_optional": false, "is_unique": true },
+    ///           { "name": "joined_at", "type": "timestamp", "is_optional": false, "is_unique": false }
+    ///         ]
+    ///       },
+    ///       {
+    ///         "name": "Workout",
+    ///         "fields": [
+    ///           { "name": "duration_minutes", "type": "integer", "is_optional": false, "is_unique": false },
+    ///           { "name": "notes", "type": "text", "is_optional": true, "is_unique": false }
+    ///         ]
+    ///       }
+    ///     ],
+    ///     "relationships": [
+    ///       { "from": "User", "to": "Workout", "kind": "one_to_many" }
+    ///     ]
+    ///   }
+    println!(OUTPUT)
+}
@@ -0,0 +1,34 @@
+use ai_functions::ai_function;
+
+#[ai_function]
+pub fn print_frontend_code(_project_description_and_endpoints: &str) {
+    /// INPUT: Takes in a PROJECT_DESCRIPTION, the API_ENDPOINTS served by the backend and the FRONTEND_ROUTE the pages are served under
+    /// FUNCTION: Writes a static frontend in plain HTML, JavaScript and CSS that lets a user do everything the PROJECT_DESCRIPTION asks through the API_ENDPOINTS
+    /// IMPORTANT: No frameworks, build steps or CDN libraries. Only plain HTML, JavaScript using fetch, and CSS.
+    /// IMPORTANT: Only call routes listed in API_ENDPOINTS, with their exact method and path, sending and reading the listed request_body and response shapes
+    /// IMPORTANT: Call the API with paths starting with "/", such as fetch("/items"), because the pages are served by the same server
+    /// IMPORTANT: Reference other files by their path under FRONTEND_ROUTE, such as FRONTEND_ROUTE/app.js
+    /// IMPORTANT: There must be an "index.html". File names are plain names ending in .html, .js or .css, without directories.
+    /// IMPORTANT: If FRONTEND_BUGS are given, PREVIOUS_FRONTEND had those bugs. Fix every one of them.
+    /// OUTPUT: Prints ONLY a JSON object from file name to file contents, nothing else. For example:
+    ///   {
+    ///     "index.html": "<!DOCTYPE html>...",
+    ///     "app.js": "async function loadItems() { ... }",
+    ///     "styles.css": "body { ... }"
+    ///   }
+    println!(OUTPUT)
+}
+
+#[ai_function]
+pub fn print_frontend_served_code(_backend_code_and_frontend_files: &str) {
+    /// INPUT: Takes in Rust BACKEND_CODE based on actix-web, the FRONTEND_FILES of a static frontend, the FRONTEND_DIR they are written to and the FRONTEND_ROUTE they are served under
+    /// FUNCTION: Adds routes to the BACKEND_CODE that serve the frontend, keeping everything else unchanged
+    /// IMPORTANT: Serve every file in FRONTEND_FILES with a GET route at FRONTEND_ROUTE/{file name}, and index.html also at FRONTEND_ROUTE itself
+    /// IMPORTANT: Read the files at request time with std::fs from the directory in the FRONTEND_DIR environment variable, falling back to the given FRONTEND_DIR when it is not set
+    /// IMPORTANT: Answer with content type text/html for .html, application/javascript for .js and text/css for .css files, and 404 for unknown file names
+    /// IMPORTANT: Existing routes, handlers, structs and their fields must stay exactly the same
+    /// IMPORTANT: The following libraries are already installed. Does not use ANY libraries other than what was provided in the code
+    ///   reqwest, serde, serde_json, tokio, actix-web, async-trait, actix_cors
+    /// OUTPUT: Print ONLY the code, nothing else. This function ONLY prints code.
+    println!(OUTPUT)
+}
@@ -1,3 +1,6 @@
 pub mod ai_func_architect;
+pub mod ai_func_auth;
 pub mod ai_func_backend;
+pub mod ai_func_database;
+pub mod ai_func_frontend;
 pub mod ai_func_managing;
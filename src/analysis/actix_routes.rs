@@ -0,0 +1,614 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+use syn::{
+    punctuated::Punctuated,
+    visit::{self, Visit},
+    Expr, ExprCall, ExprMethodCall, Fields, FnArg, GenericArgument, ItemFn, ItemStruct, Lit,
+    PathArguments, ReturnType, Token, Type,
+};
+
+use crate::models::{FieldSchema, HttpMethod, RouteObject};
+
+const HTTP_METHODS: &[&str] = &["get", "post", "put", "patch", "delete", "head"];
+
+// Handler signature and the routes declared on it with attribute macros
+#[derive(Clone, Default)]
+struct Handler {
+    request_type: Option<Type>,
+    response_type: Option<Type>,
+    attribute_routes: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct CodeIndex {
+    handlers: HashMap<String, Handler>,
+    structs: HashMap<String, Vec<(String, Type)>>,
+}
+
+fn last_segment_name(path: &syn::Path) -> String {
+    path.segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default()
+}
+
+// Inner type of a wrapper such as web::Json<T>
+fn generic_argument(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let PathArguments::AngleBracketed(arguments) = &type_path.path.segments.last()?.arguments
+    else {
+        return None;
+    };
+    arguments.args.iter().find_map(|argument| match argument {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(type_path) => last_segment_name(&type_path.path),
+        Type::Reference(reference) => type_name(&reference.elem),
+        _ => String::new(),
+    }
+}
+
+// Body type carried by a Json<T> or Result<Json<T>, E>
+fn json_payload(ty: &Type) -> Option<Type> {
+    match type_name(ty).as_str() {
+        "Json" | "Form" => generic_argument(ty).cloned(),
+        "Result" | "Option" => json_payload(generic_argument(ty)?),
+        _ => None,
+    }
+}
+
+fn attribute_routes(item_fn: &ItemFn) -> Vec<(String, String)> {
+    item_fn
+        .attrs
+        .iter()
+        .filter_map(|attr| {
+            let method = last_segment_name(attr.path());
+            if !HTTP_METHODS.contains(&method.as_str()) {
+                return None;
+            }
+            let args = attr
+                .parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+                .ok()?;
+            match args.first()? {
+                Expr::Lit(lit) => match &lit.lit {
+                    Lit::Str(path) => Some((method, path.value())),
+                    _ => None,
+                },
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+// Struct named in a `.json(Item { .. })` or `Json(Item { .. })` call of the handler body
+#[derive(Default)]
+struct JsonBodyFinder {
+    struct_name: Option<String>,
+}
+
+impl<'ast> Visit<'ast> for JsonBodyFinder {
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        if call.method == "json" && self.struct_name.is_none() {
+            if let Some(Expr::Struct(literal)) = call.args.first() {
+                self.struct_name = Some(last_segment_name(&literal.path));
+            }
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let (Expr::Path(func), Some(Expr::Struct(literal))) = (&*call.func, call.args.first()) {
+            if last_segment_name(&func.path) == "Json" && self.struct_name.is_none() {
+                self.struct_name = Some(last_segment_name(&literal.path));
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+}
+
+impl<'ast> Visit<'ast> for CodeIndex {
+    fn visit_item_struct(&mut self, item: &'ast ItemStruct) {
+        if let Fields::Named(fields) = &item.fields {
+            let fields = fields
+                .named
+                .iter()
+                .filter_map(|field| Some((serde_name(field)?, field.ty.clone())))
+                .collect();
+            self.structs.insert(item.ident.to_string(), fields);
+        }
+        visit::visit_item_struct(self, item);
+    }
+
+    fn visit_item_fn(&mut self, item_fn: &'ast ItemFn) {
+        let request_type = item_fn.sig.inputs.iter().find_map(|input| match input {
+            FnArg::Typed(arg) => json_payload(&arg.ty),
+            FnArg::Receiver(_) => None,
+        });
+        let response_type = match &item_fn.sig.output {
+            ReturnType::Type(_, ty) => json_payload(ty).or_else(|| {
+                let mut finder = JsonBodyFinder::default();
+                finder.visit_block(&item_fn.block);
+                let name = finder.struct_name?;
+                syn::parse_str::<Type>(&name).ok()
+            }),
+            ReturnType::Default => None,
+        };
+        self.handlers.insert(
+            item_fn.sig.ident.to_string(),
+            Handler {
+                request_type,
+                response_type,
+                attribute_routes: attribute_routes(item_fn),
+            },
+        );
+        visit::visit_item_fn(self, item_fn);
+    }
+}
+
+// Field name as serialized, honouring #[serde(rename = "...")] and skipping #[serde(skip)]
+fn serde_name(field: &syn::Field) -> Option<String> {
+    let mut name = field.ident.as_ref()?.to_string();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+    {
+        let mut skipped = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                name = value.value();
+            } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                skipped = true;
+            } else if meta.input.peek(Token![=]) {
+                let _: Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        });
+        if skipped {
+            return None;
+        }
+    }
+    Some(name.trim_start_matches("r#").to_string())
+}
+
+// Type sketch in the format the extraction prompt uses, such as {"id": "number"}
+fn type_sketch(ty: &Type, structs: &HashMap<String, Vec<(String, Type)>>, depth: usize) -> Value {
+    let name = type_name(ty);
+    match name.as_str() {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+        | "isize" | "f32" | "f64" => Value::from("number"),
+        "String" | "str" | "char" => Value::from("string"),
+        "bool" => Value::from("bool"),
+        "HashMap" | "BTreeMap" | "Value" => Value::from("object"),
+        "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => match generic_argument(ty) {
+            Some(item) => Value::Array(vec![type_sketch(item, structs, depth)]),
+            None => Value::from("array"),
+        },
+        "Option" => match generic_argument(ty).map(|inner| type_sketch(inner, structs, depth)) {
+            Some(Value::String(kind)) => Value::String(format!("Option<{}>", kind)),
+            Some(sketch) => sketch,
+            None => Value::from("not_provided"),
+        },
+        "Json" | "Box" | "Arc" => match generic_argument(ty) {
+            Some(inner) => type_sketch(inner, structs, depth),
+            None => Value::from("not_provided"),
+        },
+        _ => match structs.get(&name) {
+            Some(fields) if depth < 5 => Value::Object(
+                fields
+                    .iter()
+                    .map(|(field, ty)| (field.clone(), type_sketch(ty, structs, depth + 1)))
+                    .collect::<Map<String, Value>>(),
+            ),
+            _ if matches!(ty, Type::Tuple(tuple) if tuple.elems.is_empty()) => Value::from("None"),
+            _ if name.is_empty() => Value::from("not_provided"),
+            _ => Value::String(name),
+        },
+    }
+}
+
+fn join_path(prefix: &str, path: &str) -> String {
+    let joined = format!(
+        "{}/{}",
+        prefix.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    );
+    if joined.len() > 1 {
+        joined.trim_end_matches('/').to_string()
+    } else {
+        joined
+    }
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(value) => Some(value.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Innermost expression of a method chain, e.g. App::new() in App::new().route(..)
+fn chain_root(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::MethodCall(call) => chain_root(&call.receiver),
+        _ => expr,
+    }
+}
+
+// Name and first argument of a call such as web::scope("/api")
+fn root_call(expr: &Expr) -> Option<(String, Option<&Expr>)> {
+    match chain_root(expr) {
+        Expr::Call(call) => match &*call.func {
+            Expr::Path(func) => Some((last_segment_name(&func.path), call.args.first())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Method and handler of `web::get().to(handler)`
+fn route_target(expr: &Expr) -> Option<(String, String)> {
+    let (method, _) = root_call(expr)?;
+    if !HTTP_METHODS.contains(&method.as_str()) {
+        return None;
+    }
+    let mut current = expr;
+    while let Expr::MethodCall(call) = current {
+        if call.method == "to" {
+            if let Some(Expr::Path(handler)) = call.args.first() {
+                return Some((method, last_segment_name(&handler.path)));
+            }
+        }
+        current = &call.receiver;
+    }
+    None
+}
+
+fn is_app_new(expr: &Expr) -> bool {
+    let Expr::Call(call) = expr else {
+        return false;
+    };
+    let Expr::Path(func) = &*call.func else {
+        return false;
+    };
+    let names: Vec<String> = func
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    names.ends_with(&[String::from("App"), String::from("new")])
+}
+
+struct RouteCollector<'a> {
+    index: &'a CodeIndex,
+    routes: Vec<(String, String, String)>,
+    in_service_config: bool,
+}
+
+impl RouteCollector<'_> {
+    // Walk App, scope or ServiceConfig method chains, registering routes and services
+    fn walk_chain(&mut self, expr: &Expr, prefix: &str) {
+        let Expr::MethodCall(call) = expr else {
+            return;
+        };
+        self.walk_chain(&call.receiver, prefix);
+        let args: Vec<&Expr> = call.args.iter().collect();
+        match (call.method.to_string().as_str(), args.as_slice()) {
+            ("route", [path, target]) => {
+                if let (Some(path), Some((method, handler))) =
+                    (string_literal(path), route_target(target))
+                {
+                    self.routes
+                        .push((join_path(prefix, &path), method, handler));
+                }
+            }
+            ("service", [service]) => self.walk_service(service, prefix),
+            _ => {}
+        }
+    }
+
+    fn walk_service(&mut self, service: &Expr, prefix: &str) {
+        match service {
+            Expr::Path(handler) => {
+                let name = last_segment_name(&handler.path);
+                if let Some(found) = self.index.handlers.get(&name) {
+                    for (method, path) in &found.attribute_routes {
+                        self.routes
+                            .push((join_path(prefix, path), method.clone(), name.clone()));
+                    }
+                }
+            }
+            Expr::Tuple(services) => {
+                for service in &services.elems {
+                    self.walk_service(service, prefix);
+                }
+            }
+            Expr::Paren(inner) => self.walk_service(&inner.expr, prefix),
+            _ => match root_call(service) {
+                Some((kind, Some(path))) if kind == "scope" => {
+                    if let Some(path) = string_literal(path) {
+                        self.walk_chain(service, &join_path(prefix, &path));
+                    }
+                }
+                Some((kind, Some(path))) if kind == "resource" => {
+                    if let Some(path) = string_literal(path) {
+                        self.walk_resource(service, &join_path(prefix, &path));
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    fn walk_resource(&mut self, expr: &Expr, path: &str) {
+        let Expr::MethodCall(call) = expr else {
+            return;
+        };
+        self.walk_resource(&call.receiver, path);
+        if call.method == "route" {
+            if let Some((method, handler)) = call.args.first().and_then(route_target) {
+                self.routes.push((path.to_string(), method, handler));
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for RouteCollector<'_> {
+    fn visit_item_fn(&mut self, item_fn: &'ast ItemFn) {
+        let was_service_config = self.in_service_config;
+        self.in_service_config = item_fn.sig.inputs.iter().any(|input| match input {
+            FnArg::Typed(arg) => type_name(&arg.ty) == "ServiceConfig",
+            FnArg::Receiver(_) => false,
+        });
+        visit::visit_item_fn(self, item_fn);
+        self.in_service_config = was_service_config;
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        let root = chain_root(&call.receiver);
+        let is_app = is_app_new(root);
+        let is_service_config = self.in_service_config && matches!(root, Expr::Path(_));
+        if is_app || is_service_config {
+            self.walk_chain(&Expr::MethodCall(call.clone()), "");
+            return;
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+}
+
+// Routes registered in actix-web code, read without running or compiling it
+pub fn parse_actix_routes(code: &str) -> Result<Vec<RouteObject>, syn::Error> {
+    let file = syn::parse_file(code)?;
+    let mut index = CodeIndex::default();
+    index.visit_file(&file);
+
+    let mut collector = RouteCollector {
+        index: &index,
+        routes: vec![],
+        in_service_config: false,
+    };
+    collector.visit_file(&file);
+
+    let mut routes: Vec<RouteObject> = vec![];
+    for (path, method, handler) in collector.routes {
+        let Ok(method) = method.parse::<HttpMethod>() else {
+            continue;
+        };
+        let duplicate = routes
+            .iter()
+            .any(|route| route.route == path && route.method == method);
+        if duplicate {
+            continue;
+        }
+        let handler = index.handlers.get(&handler).cloned().unwrap_or_default();
+        let sketch = |ty: &Option<Type>, missing: &str| match ty {
+            Some(ty) => type_sketch(ty, &index.structs, 0),
+            None => Value::from(missing),
+        };
+        routes.push(RouteObject::new(
+            method,
+            &path,
+            FieldSchema::from(sketch(&handler.request_type, "None")),
+            FieldSchema::from(sketch(&handler.response_type, "not_provided")),
+        ));
+    }
+    Ok(routes)
+}
+
+// Placeholder names do not matter when comparing, only their position
+pub fn path_key(path: &str) -> String {
+    let segments: Vec<&str> = path
+        .split('/')
+        .map(|segment| {
+            if segment.starts_with('{') {
+                "{}"
+            } else {
+                segment
+            }
+        })
+        .collect();
+    segments.join("/")
+}
+
+pub fn route_key(route: &RouteObject) -> String {
+    format!("{} {}", route.method, path_key(&route.route))
+}
+
+// Routes that only one of the two extraction methods found
+pub fn compare_extracted_routes(parsed: &[RouteObject], from_model: &[RouteObject]) -> Vec<String> {
+    let parsed_keys: Vec<String> = parsed.iter().map(route_key).collect();
+    let model_keys: Vec<String> = from_model.iter().map(route_key).collect();
+    let mut differences: Vec<String> = vec![];
+    for key in parsed_keys.iter().filter(|key| !model_keys.contains(key)) {
+        differences.push(format!("{} found by the parser only", key));
+    }
+    for key in model_keys.iter().filter(|key| !parsed_keys.contains(key)) {
+        differences.push(format!("{} found by the model only", key));
+    }
+    differences
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    const CODE: &str = r#"
+use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Item {
+    id: u64,
+    name: String,
+    #[serde(rename = "isDone")]
+    done: bool,
+    tags: Vec<String>,
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NewUser {
+    username: String,
+}
+
+async fn create_item(db: web::Data<AppState>, item: web::Json<Item>) -> impl Responder {
+    HttpResponse::Ok().json(Item { id: 1, name: item.name.clone(), done: false, tags: vec![], note: None })
+}
+
+async fn read_item(path: web::Path<u64>) -> Result<web::Json<Item>, actix_web::Error> {
+    todo!()
+}
+
+async fn list_items() -> web::Json<Vec<Item>> {
+    todo!()
+}
+
+async fn delete_item(path: web::Path<u64>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+async fn signup(user: web::Json<NewUser>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+#[get("/health")]
+async fn health() -> impl Responder {
+    "ok"
+}
+
+fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/signup", web::post().to(signup));
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    HttpServer::new(move || {
+        App::new()
+            .app_data(data.clone())
+            .service(health)
+            .configure(config)
+            .route("/item", web::post().to(create_item))
+            .service(
+                web::scope("/api")
+                    .route("/items", web::get().to(list_items))
+                    .service(
+                        web::resource("/item/{item_id}")
+                            .route(web::get().to(read_item))
+                            .route(web::delete().to(delete_item)),
+                    ),
+            )
+    })
+    .bind(("127.0.0.1", 8080))?
+    .run()
+    .await
+}
+"#;
+
+    #[test]
+    fn test_parse_actix_routes() {
+        let routes = parse_actix_routes(CODE).unwrap();
+        let keys: Vec<String> = routes.iter().map(route_key).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "POST /signup",
+                "GET /health",
+                "POST /item",
+                "GET /api/items",
+                "GET /api/item/{}",
+                "DELETE /api/item/{}",
+            ]
+        );
+
+        let item_sketch = json!({
+            "id": "number",
+            "name": "string",
+            "isDone": "bool",
+            "tags": ["string"],
+            "note": "Option<string>"
+        });
+        let create = &routes[2];
+        assert!(!create.is_route_dynamic);
+        assert_eq!(create.request_body.to_sketch(), item_sketch);
+        assert_eq!(create.response.to_sketch(), item_sketch);
+
+        let list = &routes[3];
+        assert_eq!(list.request_body, FieldSchema::Empty);
+        assert_eq!(list.response.to_sketch(), json!([item_sketch]));
+
+        let read = &routes[4];
+        assert_eq!(read.route, "/api/item/{item_id}");
+        assert!(read.is_route_dynamic);
+        assert_eq!(read.path_params, vec!["item_id"]);
+        assert_eq!(read.response.to_sketch(), item_sketch);
+
+        assert_eq!(routes[5].response, FieldSchema::Unknown);
+        assert_eq!(
+            routes[0].request_body.to_sketch(),
+            json!({"username": "string"})
+        );
+    }
+
+    #[test]
+    fn test_parse_actix_routes_invalid_code() {
+        assert!(parse_actix_routes("```rust\nfn main() {}\n```").is_err());
+        assert!(parse_actix_routes("fn main() {}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compare_extracted_routes() {
+        let route = |method: &str, path: &str| {
+            RouteObject::new(
+                method.parse().unwrap(),
+                path,
+                FieldSchema::Empty,
+                FieldSchema::Empty,
+            )
+        };
+        let parsed = vec![route("get", "/item/{id}"), route("post", "/item")];
+        let from_model = vec![route("GET", "/item/{item_id}"), route("get", "/items")];
+
+        assert_eq!(
+            compare_extracted_routes(&parsed, &from_model),
+            vec![
+                "POST /item found by the parser only",
+                "GET /items found by the model only",
+            ]
+        );
+    }
+}
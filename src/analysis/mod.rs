@@ -0,0 +1,2 @@
+pub mod actix_routes;
+pub mod openapi_contract;
@@ -0,0 +1,401 @@
+use std::{collections::BTreeMap, error::Error, fs, path::Path};
+
+use serde_json::Value;
+
+use crate::models::{ApiContract, ContractOperation, FieldSchema, HttpMethod, RouteObject};
+
+// Deepest $ref chain or schema nesting followed, so recursive schemas stay finite
+const MAX_SCHEMA_DEPTH: usize = 12;
+
+// Object a local reference such as "#/components/schemas/Item" points to
+fn resolve_ref<'a>(document: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    document.pointer(pointer)
+}
+
+// Follow $ref until a real object is reached
+fn resolve<'a>(document: &'a Value, mut value: &'a Value) -> &'a Value {
+    for _ in 0..MAX_SCHEMA_DEPTH {
+        let Some(target) = value["$ref"]
+            .as_str()
+            .and_then(|reference| resolve_ref(document, reference))
+        else {
+            break;
+        };
+        value = target;
+    }
+    value
+}
+
+fn optional(schema: FieldSchema) -> FieldSchema {
+    match schema {
+        FieldSchema::Optional(_) | FieldSchema::Empty | FieldSchema::Unknown => schema,
+        schema => FieldSchema::Optional(Box::new(schema)),
+    }
+}
+
+fn type_schema(document: &Value, schema: &Value, kind: &str, depth: usize) -> FieldSchema {
+    match kind {
+        "integer" | "number" => FieldSchema::Number,
+        "string" => FieldSchema::String,
+        "boolean" => FieldSchema::Bool,
+        "null" => FieldSchema::Empty,
+        "array" => FieldSchema::Array(Box::new(match schema.get("items") {
+            Some(items) => field_schema(document, items, depth + 1),
+            None => FieldSchema::Unknown,
+        })),
+        "object" => {
+            let required: Vec<&str> = schema["required"]
+                .as_array()
+                .map(|keys| keys.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let fields: BTreeMap<String, FieldSchema> = schema["properties"]
+                .as_object()
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .map(|(key, property)| {
+                            let field = field_schema(document, property, depth + 1);
+                            if required.contains(&key.as_str()) {
+                                (key.clone(), field)
+                            } else {
+                                (key.clone(), optional(field))
+                            }
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            FieldSchema::Object(fields)
+        }
+        _ => FieldSchema::Unknown,
+    }
+}
+
+// FieldSchema for a JSON Schema object of the contract
+pub fn field_schema(document: &Value, schema: &Value, depth: usize) -> FieldSchema {
+    if depth > MAX_SCHEMA_DEPTH {
+        return FieldSchema::Unknown;
+    }
+    if let Some(reference) = schema["$ref"].as_str() {
+        return match resolve_ref(document, reference) {
+            Some(target) => field_schema(document, target, depth + 1),
+            None => FieldSchema::Named(reference.rsplit('/').next().unwrap_or_default().into()),
+        };
+    }
+
+    // anyOf and oneOf are only understood as "this type or null"
+    for key in ["anyOf", "oneOf"] {
+        if let Some(variants) = schema[key].as_array() {
+            let non_null: Vec<&Value> = variants
+                .iter()
+                .filter(|variant| variant["type"] != "null")
+                .collect();
+            return match non_null.as_slice() {
+                [only] if non_null.len() < variants.len() => {
+                    optional(field_schema(document, only, depth + 1))
+                }
+                [only] => field_schema(document, only, depth + 1),
+                _ => FieldSchema::Unknown,
+            };
+        }
+    }
+    if let Some(parts) = schema["allOf"].as_array() {
+        let mut fields: BTreeMap<String, FieldSchema> = BTreeMap::new();
+        for part in parts {
+            match field_schema(document, part, depth + 1) {
+                FieldSchema::Object(part_fields) => fields.extend(part_fields),
+                _ => return FieldSchema::Unknown,
+            }
+        }
+        return FieldSchema::Object(fields);
+    }
+
+    let field = match &schema["type"] {
+        Value::String(kind) => type_schema(document, schema, kind, depth),
+        // OpenAPI 3.1 writes nullable fields as a list such as ["string", "null"]
+        Value::Array(kinds) => {
+            let non_null: Vec<&str> = kinds
+                .iter()
+                .filter_map(Value::as_str)
+                .filter(|kind| *kind != "null")
+                .collect();
+            match non_null.as_slice() {
+                [kind] => {
+                    let field = type_schema(document, schema, kind, depth);
+                    if non_null.len() < kinds.len() {
+                        optional(field)
+                    } else {
+                        field
+                    }
+                }
+                _ => FieldSchema::Unknown,
+            }
+        }
+        _ if schema.get("properties").is_some() => type_schema(document, schema, "object", depth),
+        _ => FieldSchema::Unknown,
+    };
+    // OpenAPI 3.0 marks nullable fields with a flag instead
+    match schema["nullable"].as_bool() {
+        Some(true) => optional(field),
+        _ => field,
+    }
+}
+
+// Schema of the JSON content of a request body or response
+fn content_schema(document: &Value, body: &Value) -> FieldSchema {
+    let content = &resolve(document, body)["content"];
+    let schema = content
+        .get("application/json")
+        .or_else(|| content.as_object()?.values().next())
+        .and_then(|media| media.get("schema"));
+    match schema {
+        Some(schema) => field_schema(document, schema, 0),
+        None => FieldSchema::Empty,
+    }
+}
+
+fn contract_operation(
+    document: &Value,
+    path: &str,
+    method: HttpMethod,
+    operation: &Value,
+) -> ContractOperation {
+    let request_body = match operation.get("requestBody") {
+        Some(body) => content_schema(document, body),
+        None => FieldSchema::Empty,
+    };
+
+    let mut success_statuses: Vec<u16> = vec![];
+    let mut response = FieldSchema::Empty;
+    if let Some(responses) = operation["responses"].as_object() {
+        for (status, declared) in responses {
+            let Ok(status) = status.parse::<u16>() else {
+                continue;
+            };
+            if !(200..300).contains(&status) {
+                continue;
+            }
+            if success_statuses.is_empty() {
+                response = content_schema(document, declared);
+            }
+            success_statuses.push(status);
+        }
+    }
+
+    ContractOperation {
+        route: RouteObject::new(method, path, request_body, response),
+        success_statuses,
+    }
+}
+
+// Every operation of an OpenAPI 3.0 or 3.1 document
+pub fn parse_api_contract(document: Value) -> Result<ApiContract, String> {
+    let version = document["openapi"].as_str().unwrap_or_default();
+    if !version.starts_with('3') {
+        return Err(String::from(
+            "only OpenAPI 3.0 and 3.1 documents are supported",
+        ));
+    }
+    let Some(paths) = document["paths"].as_object() else {
+        return Err(String::from("the document has no paths"));
+    };
+
+    let mut operations: Vec<ContractOperation> = vec![];
+    for (path, path_item) in paths {
+        let path_item = resolve(&document, path_item);
+        let Some(path_item) = path_item.as_object() else {
+            continue;
+        };
+        for (method, operation) in path_item {
+            // Skip shared fields such as "parameters" and methods actix-web tests do not send
+            let Ok(method) = method.parse::<HttpMethod>() else {
+                continue;
+            };
+            operations.push(contract_operation(&document, path, method, operation));
+        }
+    }
+    if operations.is_empty() {
+        return Err(String::from("the document declares no operations"));
+    }
+
+    Ok(ApiContract {
+        title: document["info"]["title"]
+            .as_str()
+            .unwrap_or_default()
+            .into(),
+        description: document["info"]["description"]
+            .as_str()
+            .unwrap_or_default()
+            .into(),
+        document,
+        operations,
+    })
+}
+
+// Read a contract in YAML or JSON; YAML parsing accepts both
+pub fn load_api_contract(path: &Path) -> Result<ApiContract, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    let document: Value = serde_yaml::from_str(&contents)?;
+    Ok(parse_api_contract(document)?)
+}
+
+// Operation list used to describe the contract in the goal request
+pub fn describe_contract(contract: &ApiContract) -> String {
+    let operations: Vec<String> = contract
+        .operations
+        .iter()
+        .map(|operation| format!("{} {}", operation.route.method, operation.route.route))
+        .collect();
+    format!(
+        "{} {} \n OPERATIONS: {}",
+        contract.title,
+        contract.description,
+        operations.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    const CONTRACT: &str = r##"
+openapi: 3.0.3
+info:
+  title: Todo API
+  description: Tracks todo items
+  version: 1.0.0
+paths:
+  /items:
+    get:
+      responses:
+        "200":
+          description: All items
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: "#/components/schemas/Item"
+    post:
+      requestBody:
+        $ref: "#/components/requestBodies/NewItem"
+      responses:
+        "201":
+          description: Created
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Item"
+        400:
+          description: Invalid item
+  /items/{itemId}:
+    parameters:
+      - name: itemId
+        in: path
+        required: true
+        schema:
+          type: integer
+    delete:
+      responses:
+        204:
+          description: Deleted
+components:
+  requestBodies:
+    NewItem:
+      content:
+        application/json:
+          schema:
+            type: object
+            required: [title]
+            properties:
+              title:
+                type: string
+  schemas:
+    Item:
+      allOf:
+        - type: object
+          required: [id, title]
+          properties:
+            id:
+              type: integer
+            title:
+              type: string
+        - type: object
+          properties:
+            note:
+              type: string
+              nullable: true
+            done:
+              type: boolean
+"##;
+
+    #[test]
+    fn test_parse_api_contract() {
+        let document: Value = serde_yaml::from_str(CONTRACT).unwrap();
+        let contract = parse_api_contract(document).unwrap();
+        assert_eq!(contract.title, "Todo API");
+
+        let routes: Vec<String> = contract
+            .operations
+            .iter()
+            .map(|operation| format!("{} {}", operation.route.method, operation.route.route))
+            .collect();
+        assert_eq!(
+            routes,
+            vec!["GET /items", "POST /items", "DELETE /items/{itemId}"]
+        );
+
+        let item = json!({
+            "id": "number",
+            "title": "string",
+            "note": "Option<string>",
+            "done": "Option<bool>"
+        });
+        let list = &contract.operations[0];
+        assert_eq!(list.success_statuses, vec![200]);
+        assert_eq!(list.route.response.to_sketch(), json!([item]));
+
+        let create = &contract.operations[1];
+        assert_eq!(create.success_statuses, vec![201]);
+        assert_eq!(
+            create.route.request_body.to_sketch(),
+            json!({"title": "string"})
+        );
+        assert_eq!(create.route.response.to_sketch(), item);
+
+        let delete = &contract.operations[2];
+        assert_eq!(delete.success_statuses, vec![204]);
+        assert_eq!(delete.route.path_params, vec!["itemId"]);
+        assert_eq!(delete.route.response, FieldSchema::Empty);
+
+        assert!(describe_contract(&contract).contains("OPERATIONS: GET /items, POST /items"));
+    }
+
+    #[test]
+    fn test_field_schema_nullable_forms() {
+        let document = json!({});
+        let schema = |value: Value| field_schema(&document, &value, 0).to_sketch();
+
+        assert_eq!(
+            schema(json!({"type": ["integer", "null"]})),
+            "Option<number>"
+        );
+        assert_eq!(
+            schema(json!({"anyOf": [{"type": "string"}, {"type": "null"}]})),
+            "Option<string>"
+        );
+        assert_eq!(
+            schema(json!({"$ref": "#/components/schemas/Missing"})),
+            "Missing"
+        );
+        assert_eq!(schema(json!({})), "not_provided");
+    }
+
+    #[test]
+    fn test_parse_api_contract_errors() {
+        assert!(parse_api_contract(json!({"swagger": "2.0", "paths": {}})).is_err());
+        assert!(parse_api_contract(json!({"openapi": "3.1.0", "paths": {}})).is_err());
+    }
+}
@@ -58,9 +58,10 @@ pub async fn call_gpt(messages: Vec<Message>) -> Result<String, Box<dyn std::err
         .await
         .map_err(|err| -> Box<dyn std::error::Error + Send> { Box::new(err) })?;
 
-    let res = res.json::<APIResponse>()
-    .await
-    .map_err(|err| -> Box<dyn std::error::Error + Send> { Box::new(err) })?;
+    let res = res
+        .json::<APIResponse>()
+        .await
+        .map_err(|err| -> Box<dyn std::error::Error + Send> { Box::new(err) })?;
 
     Ok(res.choices[0].message.content.clone())
 }
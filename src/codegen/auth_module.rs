@@ -0,0 +1,211 @@
+use std::{fmt, str::FromStr};
+
+// Reviewed modules the auth agent copies into the web server, never written by the model
+const SESSION_TEMPLATE: &str = include_str!("templates/auth_session.rs");
+const JWT_TEMPLATE: &str = include_str!("templates/auth_jwt.rs");
+const PROTECTED_ROUTES_PLACEHOLDER: &str = "/* PROTECTED_ROUTES */";
+
+// Routes the auth module serves itself
+pub const AUTH_ROUTES: [&str; 3] = ["/signup", "/login", "/logout"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuthStrategy {
+    Session,
+    Jwt,
+}
+
+impl AuthStrategy {
+    fn template(self) -> &'static str {
+        match self {
+            AuthStrategy::Session => SESSION_TEMPLATE,
+            AuthStrategy::Jwt => JWT_TEMPLATE,
+        }
+    }
+
+    // Crates the module needs in the web server, as Cargo.toml name and spec
+    pub fn dependencies(self) -> Vec<(&'static str, &'static str)> {
+        let mut dependencies = vec![("argon2", "\"0.5\""), ("getrandom", "\"0.2\"")];
+        if self == AuthStrategy::Jwt {
+            dependencies.push(("jsonwebtoken", "\"9\""));
+        }
+        dependencies
+    }
+
+    fn credentials_description(self) -> &'static str {
+        match self {
+            AuthStrategy::Session => "login answers {\"token\"} and sets an HttpOnly session cookie; later requests send the cookie or an Authorization: Bearer <token> header",
+            AuthStrategy::Jwt => "login answers {\"token\"} with a signed JWT; later requests send an Authorization: Bearer <token> header",
+        }
+    }
+}
+
+impl FromStr for AuthStrategy {
+    type Err = String;
+
+    fn from_str(strategy: &str) -> Result<Self, Self::Err> {
+        match strategy.trim().to_lowercase().as_str() {
+            "session" | "sessions" | "cookie" => Ok(AuthStrategy::Session),
+            "jwt" | "token" | "bearer" => Ok(AuthStrategy::Jwt),
+            _ => Err(format!("unknown auth strategy {:?}", strategy)),
+        }
+    }
+}
+
+impl fmt::Display for AuthStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthStrategy::Session => write!(f, "session"),
+            AuthStrategy::Jwt => write!(f, "jwt"),
+        }
+    }
+}
+
+// Problems with the protected paths the model listed
+pub fn protected_route_problems(protected_routes: &[String]) -> Vec<String> {
+    let mut problems: Vec<String> = vec![];
+    for (index, path) in protected_routes.iter().enumerate() {
+        if !path.starts_with('/') {
+            problems.push(format!("protected route {:?} does not start with /", path));
+        } else if path.contains(|c: char| c.is_whitespace() || c == '?' || c == '#') {
+            problems.push(format!("protected route {:?} is not a plain path", path));
+        } else if path.matches('{').count() != path.matches('}').count() {
+            problems.push(format!("protected route {:?} has unbalanced braces", path));
+        } else if AUTH_ROUTES.contains(&path.as_str()) {
+            problems.push(format!(
+                "protected route {} is served by the auth module and must stay public",
+                path
+            ));
+        } else if protected_routes[..index].contains(path) {
+            problems.push(format!("protected route {} is listed twice", path));
+        }
+    }
+    problems
+}
+
+// The reviewed module with the protected paths filled in
+pub fn auth_module(strategy: AuthStrategy, protected_routes: &[String]) -> String {
+    let paths: Vec<String> = protected_routes
+        .iter()
+        .map(|path| format!("{:?}", path))
+        .collect();
+    strategy
+        .template()
+        .replace(PROTECTED_ROUTES_PLACEHOLDER, &paths.join(", "))
+}
+
+// What the backend prompts need to know to use the module without copying it
+pub fn auth_prompt_context(
+    module: &str,
+    strategy: AuthStrategy,
+    protected_routes: &[String],
+) -> String {
+    format!(
+        "AUTH_MODULE (module {}, {} strategy): declares AuthState, whose AuthState::new() returns web::Data<AuthState>, the RequireLogin middleware, the AuthenticatedUser {{ username: String }} extractor, and the handlers signup, login and logout, which take JSON {{\"username\", \"password\"}}. The {} \n PROTECTED_ROUTES: {:?}",
+        module,
+        strategy,
+        strategy.credentials_description(),
+        protected_routes
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_auth_module() {
+        let protected_routes = vec![String::from("/todos"), String::from("/todos/{id}")];
+        for strategy in [AuthStrategy::Session, AuthStrategy::Jwt] {
+            let module = auth_module(strategy, &protected_routes);
+            assert!(module
+                .contains("pub const PROTECTED_ROUTES: &[&str] = &[\"/todos\", \"/todos/{id}\"];"));
+            assert!(!module.contains(PROTECTED_ROUTES_PLACEHOLDER));
+            for handler in ["signup", "login", "logout"] {
+                assert!(module.contains(&format!("pub async fn {}(", handler)));
+            }
+            syn::parse_file(&module).expect("Auth module should parse");
+        }
+
+        let empty = auth_module(AuthStrategy::Session, &[]);
+        assert!(empty.contains("pub const PROTECTED_ROUTES: &[&str] = &[];"));
+        assert_eq!(
+            AuthStrategy::Jwt.dependencies().last(),
+            Some(&("jsonwebtoken", "\"9\""))
+        );
+        assert_eq!("JWT".parse::<AuthStrategy>(), Ok(AuthStrategy::Jwt));
+        assert!("oauth".parse::<AuthStrategy>().is_err());
+    }
+
+    // Builds both modules in a fixture crate with the crates dependencies() adds,
+    // plus the ones the web server template already has
+    #[test]
+    fn test_auth_modules_compile() {
+        let fixture_dir = std::env::temp_dir().join("benjamin-auth-module-fixture");
+        let _ = std::fs::remove_dir_all(fixture_dir.join("src"));
+        std::fs::create_dir_all(fixture_dir.join("src")).unwrap();
+
+        let mut dependencies = vec![
+            String::from("actix-web = \"4\""),
+            String::from("serde = { version = \"1\", features = [\"derive\"] }"),
+            String::from("serde_json = \"1\""),
+        ];
+        let protected_routes = vec![String::from("/todos/{id}")];
+        let mut modules: Vec<String> = vec![];
+        for (strategy, module) in [
+            (AuthStrategy::Session, "auth_session"),
+            (AuthStrategy::Jwt, "auth_jwt"),
+        ] {
+            for (name, spec) in strategy.dependencies() {
+                let dependency = format!("{} = {}", name, spec);
+                if !dependencies.contains(&dependency) {
+                    dependencies.push(dependency);
+                }
+            }
+            std::fs::write(
+                fixture_dir.join("src").join(format!("{}.rs", module)),
+                auth_module(strategy, &protected_routes),
+            )
+            .unwrap();
+            modules.push(format!("pub mod {};", module));
+        }
+        std::fs::write(
+            fixture_dir.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"auth-module-fixture\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n\n[dependencies]\n{}\n",
+                dependencies.join("\n")
+            ),
+        )
+        .unwrap();
+        std::fs::write(fixture_dir.join("src").join("lib.rs"), modules.join("\n")).unwrap();
+
+        let output = std::process::Command::new("cargo")
+            .args(["check", "--quiet"])
+            .current_dir(&fixture_dir)
+            .output()
+            .expect("Failed to run cargo check");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn test_protected_route_problems() {
+        let routes: Vec<String> = ["/todos", "todos", "/todos/{id", "/login", "/todos", "/a b"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            protected_route_problems(&routes),
+            vec![
+                "protected route \"todos\" does not start with /",
+                "protected route \"/todos/{id\" has unbalanced braces",
+                "protected route /login is served by the auth module and must stay public",
+                "protected route /todos is listed twice",
+                "protected route \"/a b\" is not a plain path",
+            ]
+        );
+        assert!(protected_route_problems(&[String::from("/todos/{id}")]).is_empty());
+    }
+}
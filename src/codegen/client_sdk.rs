@@ -0,0 +1,466 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    codegen::{
+        openapi::operation_id,
+        serde_structs::{pascal_case, snake_case, struct_name},
+    },
+    models::{FieldSchema, HttpMethod, RouteObject},
+    testing::payload::build_request_body,
+};
+
+const RUST_CLIENT_MANIFEST: &str = r#"[package]
+name = "benjamin-api-client"
+version = "0.1.0"
+edition = "2021"
+
+# Standalone, even when generated inside another cargo project
+[workspace]
+
+[dependencies]
+reqwest = { version = "0.11", features = ["json"] }
+serde = { version = "1", features = ["derive"] }
+serde_json = "1"
+
+[dev-dependencies]
+tokio = { version = "1", features = ["macros", "rt-multi-thread"] }
+"#;
+
+const RUST_CLIENT_CORE: &str = r#"pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+// An empty body decodes as null, so routes without a response still succeed
+async fn decode<T: serde::de::DeserializeOwned>(response: reqwest::Response) -> Result<T, Error> {
+    let response = response.error_for_status()?;
+    let text = response.text().await?;
+    let text = if text.trim().is_empty() { "null" } else { text.as_str() };
+    Ok(serde_json::from_str(text)?)
+}
+"#;
+
+const TYPESCRIPT_CLIENT_CORE: &str = r#"export class ApiError extends Error {
+  constructor(public status: number, public body: string) {
+    super(`Request failed with status ${status}: ${body}`);
+  }
+}
+
+export class ApiClient {
+  constructor(private baseUrl: string, private fetchImpl: typeof fetch = fetch) {}
+
+  private async request<T>(method: string, path: string, body?: unknown): Promise<T> {
+    const response = await this.fetchImpl(`${this.baseUrl}${path}`, {
+      method,
+      headers: body === undefined ? {} : { "Content-Type": "application/json" },
+      body: body === undefined ? undefined : JSON.stringify(body),
+    });
+    const text = await response.text();
+    if (!response.ok) {
+      throw new ApiError(response.status, text);
+    }
+    return (text.trim() === "" ? null : JSON.parse(text)) as T;
+  }
+"#;
+
+fn has_request_body(route: &RouteObject) -> bool {
+    !matches!(
+        route.request_body,
+        FieldSchema::Empty | FieldSchema::Unknown
+    )
+}
+
+// Type name prefix of a route, such as PostItem for POST /item
+fn type_prefix(route: &RouteObject) -> String {
+    pascal_case(&operation_id(route))
+}
+
+// Rust serde types, nested structs first, keeping every struct name unique
+struct RustTypes {
+    structs: Vec<String>,
+    used_names: HashSet<String>,
+}
+
+impl RustTypes {
+    fn unique_name(&mut self, name: String) -> String {
+        let mut candidate = name.clone();
+        let mut suffix = 2;
+        while !self.used_names.insert(candidate.clone()) {
+            candidate = format!("{}{}", name, suffix);
+            suffix += 1;
+        }
+        candidate
+    }
+
+    fn rust_type(&mut self, schema: &FieldSchema, name_hint: &str) -> String {
+        match schema {
+            FieldSchema::Empty | FieldSchema::Unknown | FieldSchema::Named(_) => {
+                String::from("serde_json::Value")
+            }
+            FieldSchema::Number => String::from("f64"),
+            FieldSchema::String => String::from("String"),
+            FieldSchema::Bool => String::from("bool"),
+            FieldSchema::Optional(inner) => format!("Option<{}>", self.rust_type(inner, name_hint)),
+            FieldSchema::Array(item) => {
+                format!(
+                    "Vec<{}>",
+                    self.rust_type(item, &format!("{}Item", name_hint))
+                )
+            }
+            FieldSchema::Object(fields) if fields.is_empty() => {
+                String::from("serde_json::Map<String, serde_json::Value>")
+            }
+            FieldSchema::Object(fields) => {
+                let name = self.unique_name(struct_name(name_hint));
+                let mut used_fields: HashSet<String> = HashSet::new();
+                let mut lines: Vec<String> = vec![];
+                for (key, field) in fields {
+                    let mut field_name = snake_case(key);
+                    while !used_fields.insert(field_name.clone()) {
+                        field_name = format!("{}_", field_name);
+                    }
+                    let field_type = self.rust_type(field, &format!("{}_{}", name, key));
+                    if field_name.trim_start_matches("r#") != key {
+                        lines.push(format!("    #[serde(rename = {:?})]", key));
+                    }
+                    if matches!(field, FieldSchema::Optional(_)) {
+                        lines.push(String::from(
+                            "    #[serde(default, skip_serializing_if = \"Option::is_none\")]",
+                        ));
+                    }
+                    lines.push(format!("    pub {}: {},", field_name, field_type));
+                }
+                self.structs.push(format!(
+                    "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct {} {{\n{}\n}}",
+                    name,
+                    lines.join("\n")
+                ));
+                name
+            }
+        }
+    }
+
+    // Named type for a whole request or response, aliasing anything that is not a struct
+    fn root_type(&mut self, schema: &FieldSchema, name: &str) -> String {
+        let rust_type = self.rust_type(schema, name);
+        if rust_type != name {
+            let name = self.unique_name(name.to_string());
+            self.structs
+                .push(format!("pub type {} = {};", name, rust_type));
+            return name;
+        }
+        rust_type
+    }
+}
+
+// Route path as a format! string with its arguments, e.g. ("/item/{}", ["id"])
+fn path_format(route: &RouteObject, argument: impl Fn(&str) -> String) -> (String, Vec<String>) {
+    let mut arguments: Vec<String> = vec![];
+    let segments: Vec<String> = route
+        .route
+        .split('/')
+        .map(
+            |segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(name) => {
+                    arguments.push(argument(name));
+                    String::from("{}")
+                }
+                None => segment.to_string(),
+            },
+        )
+        .collect();
+    (segments.join("/"), arguments)
+}
+
+fn rust_method(method: HttpMethod) -> String {
+    format!("reqwest::Method::{}", method)
+}
+
+fn path_argument(name: &str) -> String {
+    snake_case(name)
+}
+
+// lib.rs of a reqwest client with one method per route
+pub fn rust_client(routes: &[RouteObject]) -> String {
+    let mut types = RustTypes {
+        structs: vec![],
+        used_names: HashSet::new(),
+    };
+    let mut methods: Vec<String> = vec![];
+    for route in routes {
+        let prefix = type_prefix(route);
+        let response_type = types.root_type(&route.response, &format!("{}Response", prefix));
+
+        let mut parameters: Vec<String> = vec![String::from("&self")];
+        for name in &route.path_params {
+            parameters.push(format!("{}: &str", path_argument(name)));
+        }
+        let mut send = String::from(".send()");
+        if has_request_body(route) {
+            let request_type = types.root_type(&route.request_body, &format!("{}Request", prefix));
+            parameters.push(format!("body: &{}", request_type));
+            send = String::from(".json(body).send()");
+        }
+
+        let (path, arguments) = path_format(route, path_argument);
+        let mut format_arguments = vec![String::from("self.base_url")];
+        format_arguments.extend(arguments);
+        methods.push(format!(
+            "    // {} {}\n    pub async fn {}({}) -> Result<{}, Error> {{\n        let url = format!(\"{{}}{}\", {});\n        let response = self.http.request({}, url){}.await?;\n        decode(response).await\n    }}",
+            route.method,
+            route.route,
+            snake_case(&operation_id(route)).trim_start_matches("r#"),
+            parameters.join(", "),
+            response_type,
+            path,
+            format_arguments.join(", "),
+            rust_method(route.method),
+            send
+        ));
+    }
+
+    format!(
+        "// Generated by Benjamin from the extracted API schema\nuse serde::{{Deserialize, Serialize}};\n\n{}\n{}\n\nimpl ApiClient {{\n    pub fn new(base_url: impl Into<String>) -> Self {{\n        Self {{\n            base_url: base_url.into().trim_end_matches('/').to_string(),\n            http: reqwest::Client::new(),\n        }}\n    }}\n\n{}\n}}\n",
+        RUST_CLIENT_CORE,
+        types.structs.join("\n\n"),
+        methods.join("\n\n")
+    )
+}
+
+// Integration test calling the running server through the client
+pub fn rust_client_test(routes: &[RouteObject], base_url: &str) -> String {
+    let mut calls: Vec<String> = vec![];
+    // Ids in paths may not exist on the running server, so only fixed paths are called
+    for route in routes.iter().filter(|route| route.path_params.is_empty()) {
+        let method_name = snake_case(&operation_id(route));
+        let method_name = method_name.trim_start_matches("r#");
+        let call = match build_request_body(&route.request_body) {
+            Some(body) => format!(
+                "    let body = serde_json::from_str(r#\"{}\"#).unwrap();\n    client.{}(&body).await.expect(\"{} {} failed\");",
+                body, method_name, route.method, route.route
+            ),
+            None => format!(
+                "    client.{}().await.expect(\"{} {} failed\");",
+                method_name, route.method, route.route
+            ),
+        };
+        calls.push(call);
+    }
+    format!(
+        "use benjamin_api_client::ApiClient;\n\n// Routes that passed the endpoint tests, called against the running server\n#[tokio::test]\nasync fn test_running_server() {{\n    let base_url = std::env::var(\"API_BASE_URL\").unwrap_or_else(|_| String::from({:?}));\n    let client = ApiClient::new(base_url);\n{}\n}}\n",
+        base_url,
+        calls.join("\n")
+    )
+}
+
+fn typescript_type(schema: &FieldSchema) -> String {
+    match schema {
+        FieldSchema::Empty | FieldSchema::Unknown | FieldSchema::Named(_) => {
+            String::from("unknown")
+        }
+        FieldSchema::Number => String::from("number"),
+        FieldSchema::String => String::from("string"),
+        FieldSchema::Bool => String::from("boolean"),
+        FieldSchema::Optional(inner) => format!("{} | null", typescript_type(inner)),
+        FieldSchema::Array(item) => match item.as_ref() {
+            FieldSchema::Optional(_) => format!("({})[]", typescript_type(item)),
+            item => format!("{}[]", typescript_type(item)),
+        },
+        FieldSchema::Object(fields) if fields.is_empty() => String::from("Record<string, unknown>"),
+        FieldSchema::Object(fields) => {
+            let properties: Vec<String> = fields
+                .iter()
+                .map(|(key, field)| {
+                    let optional = if matches!(field, FieldSchema::Optional(_)) {
+                        "?"
+                    } else {
+                        ""
+                    };
+                    format!("{:?}{}: {}", key, optional, typescript_type(field))
+                })
+                .collect();
+            format!("{{ {} }}", properties.join("; "))
+        }
+    }
+}
+
+// Lower camel case, as TypeScript methods are named
+fn camel_case(text: &str) -> String {
+    let pascal = pascal_case(text);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => pascal,
+    }
+}
+
+// client.ts with a fetch based client and one type per request and response
+pub fn typescript_client(routes: &[RouteObject]) -> String {
+    let mut types: Vec<String> = vec![];
+    let mut methods: Vec<String> = vec![];
+    for route in routes {
+        let prefix = type_prefix(route);
+        let response_type = format!("{}Response", prefix);
+        types.push(format!(
+            "export type {} = {};",
+            response_type,
+            typescript_type(&route.response)
+        ));
+
+        let mut parameters: Vec<String> = route
+            .path_params
+            .iter()
+            .map(|name| format!("{}: string", camel_case(name)))
+            .collect();
+        let mut body_argument = String::new();
+        if has_request_body(route) {
+            let request_type = format!("{}Request", prefix);
+            types.push(format!(
+                "export type {} = {};",
+                request_type,
+                typescript_type(&route.request_body)
+            ));
+            parameters.push(format!("body: {}", request_type));
+            body_argument = String::from(", body");
+        }
+
+        let (path, arguments) = path_format(route, camel_case);
+        let mut arguments = arguments.into_iter();
+        let path = path
+            .split("{}")
+            .enumerate()
+            .map(|(index, part)| match index {
+                0 => part.to_string(),
+                _ => format!(
+                    "${{encodeURIComponent({})}}{}",
+                    arguments.next().unwrap_or_default(),
+                    part
+                ),
+            })
+            .collect::<String>();
+        methods.push(format!(
+            "  // {} {}\n  {}({}): Promise<{}> {{\n    return this.request(\"{}\", `{}`{});\n  }}",
+            route.method,
+            route.route,
+            camel_case(&operation_id(route)),
+            parameters.join(", "),
+            response_type,
+            route.method,
+            path,
+            body_argument
+        ));
+    }
+
+    format!(
+        "// Generated by Benjamin from the extracted API schema\n{}\n\n{}\n\n{}\n}}\n",
+        types.join("\n"),
+        TYPESCRIPT_CLIENT_CORE,
+        methods.join("\n\n")
+    )
+}
+
+// Rust crate under dir/rust and TypeScript client under dir/typescript; returns the crate path
+pub fn write_client_sdks(
+    dir: &Path,
+    routes: &[RouteObject],
+    smoke_routes: &[RouteObject],
+    base_url: &str,
+) -> io::Result<PathBuf> {
+    let rust_dir = dir.join("rust");
+    fs::create_dir_all(rust_dir.join("src"))?;
+    fs::create_dir_all(rust_dir.join("tests"))?;
+    fs::write(rust_dir.join("Cargo.toml"), RUST_CLIENT_MANIFEST)?;
+    fs::write(rust_dir.join("src").join("lib.rs"), rust_client(routes))?;
+    fs::write(
+        rust_dir.join("tests").join("running_server.rs"),
+        rust_client_test(smoke_routes, base_url),
+    )?;
+
+    let typescript_dir = dir.join("typescript");
+    fs::create_dir_all(&typescript_dir)?;
+    fs::write(typescript_dir.join("client.ts"), typescript_client(routes))?;
+    Ok(rust_dir)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::test_support::test_route;
+
+    fn routes() -> Vec<RouteObject> {
+        let mut list = test_route("get", "/items", json!("None"));
+        list.response = FieldSchema::from(json!([{"id": "number", "title": "string"}]));
+        let mut create = test_route(
+            "post",
+            "/items",
+            json!({"title": "string", "dueDate": "Option<string>"}),
+        );
+        create.response = FieldSchema::from(json!({"id": "number", "title": "string"}));
+        vec![
+            list,
+            create,
+            test_route("delete", "/items/{itemId}", json!("None")),
+        ]
+    }
+
+    #[test]
+    fn test_rust_client() {
+        let client = rust_client(&routes());
+
+        assert!(client.contains(
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct GetItemsResponseItem {\n    pub id: f64,\n    pub title: String,\n}"
+        ));
+        assert!(client.contains("pub type GetItemsResponse = Vec<GetItemsResponseItem>;"));
+        assert!(client.contains(
+            "    #[serde(rename = \"dueDate\")]\n    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub due_date: Option<String>,"
+        ));
+        assert!(client.contains(
+            "    pub async fn post_items(&self, body: &PostItemsRequest) -> Result<PostItemsResponse, Error> {\n        let url = format!(\"{}/items\", self.base_url);\n        let response = self.http.request(reqwest::Method::POST, url).json(body).send().await?;"
+        ));
+        assert!(client.contains(
+            "pub async fn delete_items_itemid(&self, item_id: &str) -> Result<DeleteItemsItemidResponse, Error> {\n        let url = format!(\"{}/items/{}\", self.base_url, item_id);"
+        ));
+        assert!(client.contains("pub type DeleteItemsItemidResponse = serde_json::Value;"));
+
+        // The generated code must at least be valid Rust
+        syn::parse_file(&client).expect("Generated client is not valid Rust");
+    }
+
+    #[test]
+    fn test_rust_client_test() {
+        let routes = routes();
+        let harness = rust_client_test(&routes[..2], "http://127.0.0.1:8080");
+
+        assert!(harness.contains("String::from(\"http://127.0.0.1:8080\")"));
+        assert!(harness.contains("client.get_items().await.expect(\"GET /items failed\");"));
+        assert!(harness.contains("let body = serde_json::from_str(r#\"{\"dueDate\":\"sample\",\"title\":\"sample\"}\"#).unwrap();"));
+        syn::parse_file(&harness).expect("Generated test is not valid Rust");
+    }
+
+    #[test]
+    fn test_typescript_client() {
+        let client = typescript_client(&routes());
+
+        assert!(client
+            .contains("export type GetItemsResponse = { \"id\": number; \"title\": string }[];"));
+        assert!(client.contains(
+            "export type PostItemsRequest = { \"dueDate\"?: string | null; \"title\": string };"
+        ));
+        assert!(client.contains(
+            "  deleteItemsItemid(itemId: string): Promise<DeleteItemsItemidResponse> {\n    return this.request(\"DELETE\", `/items/${encodeURIComponent(itemId)}`);\n  }"
+        ));
+        assert!(client.contains(
+            "  postItems(body: PostItemsRequest): Promise<PostItemsResponse> {\n    return this.request(\"POST\", `/items`, body);"
+        ));
+        assert!(client.trim_end().ends_with('}'));
+    }
+}
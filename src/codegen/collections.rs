@@ -0,0 +1,317 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    codegen::serde_structs::snake_case,
+    models::RouteObject,
+    testing::payload::{build_request_body, default_placeholder_value, method_rank},
+};
+
+// Routes in the order the endpoint tests send them, so data exists before it is read or deleted
+fn ordered_routes(routes: &[RouteObject]) -> Vec<&RouteObject> {
+    let mut ordered: Vec<&RouteObject> = routes.iter().collect();
+    ordered.sort_by_key(|route| method_rank(&route.method.to_method()));
+    ordered
+}
+
+// Every placeholder name across the routes, once, with its example value
+fn placeholder_examples(routes: &[RouteObject]) -> Vec<(String, String)> {
+    let mut examples: Vec<(String, String)> = vec![];
+    for name in routes.iter().flat_map(|route| &route.path_params) {
+        if !examples.iter().any(|(known, _)| known == name) {
+            examples.push((name.clone(), default_placeholder_value(name)));
+        }
+    }
+    examples
+}
+
+// Route with each {placeholder} replaced by the variable syntax of a tool
+fn templated_path(route: &str, variable: impl Fn(&str) -> String) -> String {
+    route
+        .split('/')
+        .map(
+            |segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(name) => variable(name),
+                None => segment.to_string(),
+            },
+        )
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+fn pretty_body(route: &RouteObject) -> Option<String> {
+    build_request_body(&route.request_body)
+        .map(|body| serde_json::to_string_pretty(&body).expect("Failed to encode request body"))
+}
+
+// Requests in the REST Client .http format
+pub fn http_collection(routes: &[RouteObject], base_url: &str) -> String {
+    let mut lines: Vec<String> = vec![format!("@baseUrl = {}", base_url)];
+    for (name, value) in placeholder_examples(routes) {
+        lines.push(format!("@{} = {}", name, value));
+    }
+
+    for route in ordered_routes(routes) {
+        lines.push(String::new());
+        lines.push(format!("### {} {}", route.method, route.route));
+        let path = templated_path(&route.route, |name| format!("{{{{{}}}}}", name));
+        lines.push(format!("{} {{{{baseUrl}}}}{}", route.method, path));
+        if let Some(body) = pretty_body(route) {
+            lines.push(String::from("Content-Type: application/json"));
+            lines.push(String::new());
+            lines.push(body);
+        }
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+// Collection in the Postman v2.1 format
+pub fn postman_collection(routes: &[RouteObject], base_url: &str, name: &str) -> Value {
+    let mut variables: Vec<Value> = vec![json!({"key": "baseUrl", "value": base_url})];
+    for (placeholder, value) in placeholder_examples(routes) {
+        variables.push(json!({"key": placeholder, "value": value}));
+    }
+
+    let items: Vec<Value> = ordered_routes(routes)
+        .into_iter()
+        .map(|route| {
+            let path = templated_path(&route.route, |name| format!("{{{{{}}}}}", name));
+            let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+            let mut request = json!({
+                "method": route.method.to_string(),
+                "header": [],
+                "url": {
+                    "raw": format!("{{{{baseUrl}}}}{}", path),
+                    "host": ["{{baseUrl}}"],
+                    "path": segments
+                }
+            });
+            if let Some(body) = pretty_body(route) {
+                request["header"] = json!([{"key": "Content-Type", "value": "application/json"}]);
+                request["body"] = json!({
+                    "mode": "raw",
+                    "raw": body,
+                    "options": {"raw": {"language": "json"}}
+                });
+            }
+            json!({"name": format!("{} {}", route.method, route.route), "request": request})
+        })
+        .collect();
+
+    json!({
+        "info": {
+            "name": name,
+            "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+        },
+        "item": items,
+        "variable": variables
+    })
+}
+
+// Shell variable for every placeholder, prefixed so {path} cannot clobber PATH,
+// and numbered when names such as {userId} and {user_id} meet
+fn shell_variables(routes: &[RouteObject]) -> Vec<(String, String, String)> {
+    let mut variables: Vec<(String, String, String)> = vec![];
+    for (name, value) in placeholder_examples(routes) {
+        let base = format!(
+            "PARAM_{}",
+            snake_case(&name).trim_start_matches("r#").to_uppercase()
+        );
+        let mut variable = base.clone();
+        let mut suffix = 2;
+        while variables.iter().any(|(_, known, _)| *known == variable) {
+            variable = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        variables.push((name, variable, value));
+    }
+    variables
+}
+
+// Quote a value for sh, so bodies can hold any character
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+// Shell script sending every request with curl
+pub fn curl_script(routes: &[RouteObject], base_url: &str) -> String {
+    let mut lines: Vec<String> = vec![
+        String::from("#!/bin/sh"),
+        String::from(
+            "# Override any variable from the environment, e.g. BASE_URL=http://localhost:3000",
+        ),
+        format!("BASE_URL=\"${{BASE_URL:-{}}}\"", base_url),
+    ];
+    let variables = shell_variables(routes);
+    for (_, variable, value) in &variables {
+        lines.push(format!("{}=\"${{{}:-{}}}\"", variable, variable, value));
+    }
+
+    for route in ordered_routes(routes) {
+        lines.push(String::new());
+        lines.push(format!(
+            "echo {}",
+            shell_quote(&format!("{} {}", route.method, route.route))
+        ));
+        let path = templated_path(&route.route, |name| {
+            let variable = variables
+                .iter()
+                .find(|(known, _, _)| known == name)
+                .map(|(_, variable, _)| variable.as_str())
+                .unwrap_or_default();
+            format!("${{{}}}", variable)
+        });
+        let mut command = format!("curl -sS -X {} \"$BASE_URL{}\"", route.method, path);
+        if let Some(body) = build_request_body(&route.request_body) {
+            command = format!(
+                "{} -H 'Content-Type: application/json' -d {}",
+                command,
+                shell_quote(&body.to_string())
+            );
+        }
+        lines.push(command);
+        lines.push(String::from("echo"));
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+// Write the .http file, Postman collection and curl script into dir
+pub fn write_request_collections(
+    dir: &Path,
+    routes: &[RouteObject],
+    base_url: &str,
+    name: &str,
+) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let postman = serde_json::to_string_pretty(&postman_collection(routes, base_url, name))
+        .expect("Failed to encode Postman collection");
+    let files = [
+        ("api.http", http_collection(routes, base_url)),
+        ("api.postman_collection.json", postman),
+        ("api_requests.sh", curl_script(routes, base_url)),
+    ];
+
+    let mut paths: Vec<PathBuf> = vec![];
+    for (file_name, contents) in files {
+        let path = dir.join(file_name);
+        fs::write(&path, contents)?;
+        paths.push(path);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&paths[2], fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{models::FieldSchema, testing::test_support::test_route};
+
+    fn routes() -> Vec<RouteObject> {
+        vec![
+            test_route("get", "/item/{id}", json!("None")),
+            test_route("post", "/item", json!({"name": "string", "done": "bool"})),
+        ]
+    }
+
+    #[test]
+    fn test_http_collection() {
+        let collection = http_collection(&routes(), "http://127.0.0.1:8080");
+        assert_eq!(
+            collection,
+            r#"@baseUrl = http://127.0.0.1:8080
+@id = 1
+
+### POST /item
+POST {{baseUrl}}/item
+Content-Type: application/json
+
+{
+  "done": true,
+  "name": "sample"
+}
+
+### GET /item/{id}
+GET {{baseUrl}}/item/{{id}}
+"#
+        );
+    }
+
+    #[test]
+    fn test_postman_collection() {
+        let collection = postman_collection(&routes(), "http://127.0.0.1:8080", "Todo API");
+        assert_eq!(
+            collection["info"]["schema"],
+            "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+        );
+        assert_eq!(
+            collection["variable"],
+            json!([
+                {"key": "baseUrl", "value": "http://127.0.0.1:8080"},
+                {"key": "id", "value": "1"}
+            ])
+        );
+
+        let create = &collection["item"][0]["request"];
+        assert_eq!(create["method"], "POST");
+        assert_eq!(
+            serde_json::from_str::<Value>(create["body"]["raw"].as_str().unwrap()).unwrap(),
+            json!({"name": "sample", "done": true})
+        );
+        let read = &collection["item"][1]["request"];
+        assert_eq!(read["url"]["raw"], "{{baseUrl}}/item/{{id}}");
+        assert_eq!(read["url"]["path"], json!(["item", "{{id}}"]));
+        assert!(read.get("body").is_none());
+    }
+
+    #[test]
+    fn test_curl_script() {
+        let mut routes = routes();
+        routes.push(test_route(
+            "put",
+            "/note/{noteId}",
+            json!({"text": "string"}),
+        ));
+        routes.push(test_route("get", "/files/{path}", json!("None")));
+        routes.push(test_route("get", "/users/{userId}", json!("None")));
+        routes.push(test_route("get", "/users/{user_id}/posts", json!("None")));
+        routes[2].request_body = FieldSchema::from(json!({"it's": "string"}));
+
+        let script = curl_script(&routes, "http://127.0.0.1:8080");
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("BASE_URL=\"${BASE_URL:-http://127.0.0.1:8080}\"\n"));
+        assert!(script.contains("PARAM_NOTE_ID=\"${PARAM_NOTE_ID:-1}\"\n"));
+        assert!(script.contains(r#"curl -sS -X GET "$BASE_URL/files/${PARAM_PATH}""#));
+        assert!(script.contains(r#"curl -sS -X GET "$BASE_URL/users/${PARAM_USER_ID}""#));
+        assert!(script.contains(r#"curl -sS -X GET "$BASE_URL/users/${PARAM_USER_ID_2}/posts""#));
+        assert!(!script.contains("\nPATH="));
+        assert!(script.contains(
+            r#"curl -sS -X POST "$BASE_URL/item" -H 'Content-Type: application/json' -d '{"done":true,"name":"sample"}'"#
+        ));
+        assert!(script.contains(r#"curl -sS -X GET "$BASE_URL/item/${PARAM_ID}""#));
+        assert!(script.contains(r#"-d '{"it'\''s":"sample"}'"#));
+    }
+
+    #[test]
+    fn test_write_request_collections() {
+        let dir =
+            std::env::temp_dir().join(format!("benjamin-collections-test-{}", std::process::id()));
+        let paths = write_request_collections(&dir, &routes(), "http://127.0.0.1:8080", "Todo API")
+            .unwrap();
+
+        assert_eq!(paths.len(), 3);
+        assert!(paths.iter().all(|path| path.exists()));
+        let postman: Value = serde_json::from_str(&fs::read_to_string(&paths[1]).unwrap()).unwrap();
+        assert_eq!(postman["info"]["name"], "Todo API");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
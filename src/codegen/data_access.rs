@@ -0,0 +1,352 @@
+use crate::{
+    codegen::{
+        migrations::{migration, quoted, tables, Column, SqlDialect, Table},
+        serde_structs::{pascal_case, snake_case},
+    },
+    models::{ColumnType, DataModel},
+};
+
+fn rust_type(column: &Column) -> String {
+    let rust_type = match column.column_type {
+        ColumnType::Integer => "i64",
+        ColumnType::Real => "f64",
+        ColumnType::Boolean => "bool",
+        ColumnType::Text | ColumnType::Timestamp => "String",
+    };
+    if column.is_optional {
+        format!("Option<{}>", rust_type)
+    } else {
+        rust_type.to_string()
+    }
+}
+
+fn plural(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix('y') {
+        if !stem.ends_with(['a', 'e', 'i', 'o', 'u']) {
+            return format!("{}ies", stem);
+        }
+    }
+    if ["s", "x", "ch", "sh"].iter().any(|end| name.ends_with(end)) {
+        format!("{}es", name)
+    } else {
+        format!("{}s", name)
+    }
+}
+
+fn selected_columns(table: &Table, alias: &str) -> String {
+    let mut columns: Vec<String> = vec![format!("{}\"id\"", alias)];
+    columns.extend(
+        table
+            .column_names()
+            .into_iter()
+            .map(|name| format!("{}{}", alias, quoted(name))),
+    );
+    columns.join(", ")
+}
+
+fn insert_sql(table: &Table) -> String {
+    if table.columns.is_empty() {
+        return format!("INSERT INTO {} DEFAULT VALUES", quoted(&table.name));
+    }
+    let names: Vec<String> = table.column_names().into_iter().map(quoted).collect();
+    let values: Vec<String> = (1..=names.len()).map(|n| format!("?{}", n)).collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quoted(&table.name),
+        names.join(", "),
+        values.join(", ")
+    )
+}
+
+fn select_sql(table: &Table) -> String {
+    format!(
+        "SELECT {} FROM {} WHERE \"id\" = ?1",
+        selected_columns(table, ""),
+        quoted(&table.name)
+    )
+}
+
+fn list_sql(table: &Table) -> String {
+    format!(
+        "SELECT {} FROM {} ORDER BY \"id\"",
+        selected_columns(table, ""),
+        quoted(&table.name)
+    )
+}
+
+fn list_by_sql(table: &Table, column: &str) -> String {
+    format!(
+        "SELECT {} FROM {} WHERE {} = ?1 ORDER BY \"id\"",
+        selected_columns(table, ""),
+        quoted(&table.name),
+        quoted(column)
+    )
+}
+
+fn update_sql(table: &Table) -> Option<String> {
+    if table.columns.is_empty() {
+        return None;
+    }
+    let assignments: Vec<String> = table
+        .column_names()
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| format!("{} = ?{}", quoted(name), index + 1))
+        .collect();
+    Some(format!(
+        "UPDATE {} SET {} WHERE \"id\" = ?{}",
+        quoted(&table.name),
+        assignments.join(", "),
+        table.columns.len() + 1
+    ))
+}
+
+fn delete_sql(table: &Table) -> String {
+    format!("DELETE FROM {} WHERE \"id\" = ?1", quoted(&table.name))
+}
+
+fn link_sql(join: &Table) -> String {
+    format!(
+        "INSERT OR IGNORE INTO {} ({}, {}) VALUES (?1, ?2)",
+        quoted(&join.name),
+        quoted(&join.columns[0].name),
+        quoted(&join.columns[1].name)
+    )
+}
+
+fn unlink_sql(join: &Table) -> String {
+    format!(
+        "DELETE FROM {} WHERE {} = ?1 AND {} = ?2",
+        quoted(&join.name),
+        quoted(&join.columns[0].name),
+        quoted(&join.columns[1].name)
+    )
+}
+
+// Rows of target linked through the join table to the row given as ?1
+fn linked_sql(join: &Table, target: &Table, own_column: &str, target_column: &str) -> String {
+    format!(
+        "SELECT {} FROM {} AS t JOIN {} AS j ON j.{} = t.\"id\" WHERE j.{} = ?1 ORDER BY t.\"id\"",
+        selected_columns(target, "t."),
+        quoted(&target.name),
+        quoted(&join.name),
+        quoted(target_column),
+        quoted(own_column)
+    )
+}
+
+// Join table with the two entity tables it links
+fn join_tables(tables: &[Table]) -> Vec<(&Table, &Table, &Table)> {
+    let find = |name: &str| tables.iter().find(|table| table.name == name);
+    tables
+        .iter()
+        .filter(|table| table.entity.is_none())
+        .filter_map(|join| {
+            let from = find(join.columns[0].references.as_deref()?)?;
+            let to = find(join.columns[1].references.as_deref()?)?;
+            Some((join, from, to))
+        })
+        .collect()
+}
+
+// Every SQL statement the data-access layer runs, to check against the migrated schema
+pub fn data_access_statements(model: &DataModel) -> Vec<String> {
+    let tables = tables(model);
+    let mut statements: Vec<String> = vec![];
+    for table in tables.iter().filter(|table| table.entity.is_some()) {
+        statements.push(insert_sql(table));
+        statements.push(select_sql(table));
+        statements.push(list_sql(table));
+        statements.extend(update_sql(table));
+        statements.push(delete_sql(table));
+        for column in table.columns.iter().filter(|c| c.references.is_some()) {
+            statements.push(list_by_sql(table, &column.name));
+        }
+    }
+    for (join, from, to) in join_tables(&tables) {
+        let (from_column, to_column) = (&join.columns[0].name, &join.columns[1].name);
+        statements.push(link_sql(join));
+        statements.push(unlink_sql(join));
+        statements.push(linked_sql(join, to, from_column, to_column));
+        statements.push(linked_sql(join, from, to_column, from_column));
+    }
+    statements
+}
+
+fn struct_name(table: &Table) -> String {
+    pascal_case(table.entity.as_deref().unwrap_or(&table.name))
+}
+
+fn query_list(function: &str, argument: &str, row_type: &str, sql: &str, params: &str) -> String {
+    format!(
+        "pub fn {function}(connection: &Connection{argument}) -> rusqlite::Result<Vec<{row_type}>> {{\n    let mut statement = connection.prepare({sql:?})?;\n    let rows = statement.query_map({params}, {row_type}::from_row)?;\n    rows.collect()\n}}\n"
+    )
+}
+
+fn entity_code(table: &Table) -> String {
+    let name = struct_name(table);
+    let new_name = format!("New{}", name);
+    let fields: Vec<(String, String)> = table
+        .columns
+        .iter()
+        .map(|column| (snake_case(&column.name), rust_type(column)))
+        .collect();
+    let struct_fields: String = fields
+        .iter()
+        .map(|(field, rust_type)| format!("    pub {}: {},\n", field, rust_type))
+        .collect();
+    let row_fields: String = fields
+        .iter()
+        .enumerate()
+        .map(|(index, (field, _))| format!("            {}: row.get({})?,\n", field, index + 1))
+        .collect();
+    let params: Vec<String> = fields
+        .iter()
+        .map(|(field, _)| format!("new.{}", field))
+        .collect();
+    let singular = &table.name;
+    let plural = plural(singular);
+
+    let mut code = format!(
+        "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct {name} {{\n    pub id: i64,\n{struct_fields}}}\n\n\
+         #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct {new_name} {{\n{struct_fields}}}\n\n\
+         impl {name} {{\n    fn from_row(row: &Row) -> rusqlite::Result<Self> {{\n        Ok(Self {{\n            id: row.get(0)?,\n{row_fields}        }})\n    }}\n}}\n\n"
+    );
+
+    code.push_str(&format!(
+        "pub fn create_{singular}(connection: &Connection, new: &{new_name}) -> rusqlite::Result<{name}> {{\n    connection.execute({:?}, params![{}])?;\n    let id = connection.last_insert_rowid();\n    connection.query_row({:?}, [id], {name}::from_row)\n}}\n\n",
+        insert_sql(table),
+        params.join(", "),
+        select_sql(table)
+    ));
+    code.push_str(&format!(
+        "pub fn get_{singular}(connection: &Connection, id: i64) -> rusqlite::Result<Option<{name}>> {{\n    connection\n        .query_row({:?}, [id], {name}::from_row)\n        .optional()\n}}\n\n",
+        select_sql(table)
+    ));
+    code.push_str(&query_list(
+        &format!("list_{}", plural),
+        "",
+        &name,
+        &list_sql(table),
+        "[]",
+    ));
+    code.push('\n');
+    if let Some(update) = update_sql(table) {
+        let mut update_params = params.clone();
+        update_params.push(String::from("id"));
+        code.push_str(&format!(
+            "pub fn update_{singular}(connection: &Connection, id: i64, new: &{new_name}) -> rusqlite::Result<bool> {{\n    Ok(connection.execute({:?}, params![{}])? > 0)\n}}\n\n",
+            update,
+            update_params.join(", ")
+        ));
+    }
+    code.push_str(&format!(
+        "pub fn delete_{singular}(connection: &Connection, id: i64) -> rusqlite::Result<bool> {{\n    Ok(connection.execute({:?}, [id])? > 0)\n}}\n",
+        delete_sql(table)
+    ));
+    for column in table.columns.iter().filter(|c| c.references.is_some()) {
+        let parent = column.name.trim_end_matches("_id");
+        code.push('\n');
+        code.push_str(&query_list(
+            &format!("list_{}_by_{}", plural, parent),
+            &format!(", {}: i64", column.name),
+            &name,
+            &list_by_sql(table, &column.name),
+            &format!("[{}]", column.name),
+        ));
+    }
+    code
+}
+
+fn join_code(join: &Table, from: &Table, to: &Table) -> String {
+    let (from_column, to_column) = (&join.columns[0].name, &join.columns[1].name);
+    let arguments = format!(", {}: i64, {}: i64", from_column, to_column);
+    let mut code = format!(
+        "pub fn link_{join_name}(connection: &Connection{arguments}) -> rusqlite::Result<()> {{\n    connection.execute({:?}, [{from_column}, {to_column}])?;\n    Ok(())\n}}\n\n\
+         pub fn unlink_{join_name}(connection: &Connection{arguments}) -> rusqlite::Result<bool> {{\n    Ok(connection.execute({:?}, [{from_column}, {to_column}])? > 0)\n}}\n\n",
+        link_sql(join),
+        unlink_sql(join),
+        join_name = join.name,
+    );
+    code.push_str(&query_list(
+        &format!("list_{}_for_{}", plural(&to.name), from.name),
+        &format!(", {}: i64", from_column),
+        &struct_name(to),
+        &linked_sql(join, to, from_column, to_column),
+        &format!("[{}]", from_column),
+    ));
+    code.push('\n');
+    code.push_str(&query_list(
+        &format!("list_{}_for_{}", plural(&from.name), to.name),
+        &format!(", {}: i64", to_column),
+        &struct_name(from),
+        &linked_sql(join, from, to_column, from_column),
+        &format!("[{}]", to_column),
+    ));
+    code
+}
+
+// Rust module over rusqlite with typed create, read, update and delete functions
+pub fn data_access_layer(model: &DataModel) -> String {
+    let tables = tables(model);
+    let mut sections: Vec<String> = vec![format!(
+        "// Generated by Benjamin from the data model\n// Not every function is used by the server\n#![allow(dead_code)]\n\nuse rusqlite::{{params, Connection, OptionalExtension, Row}};\nuse serde::{{Deserialize, Serialize}};\n\npub const MIGRATION: &str = r#\"{}\"#;\n\n\
+         // Open the database, creating its tables if they do not exist yet\npub fn open(path: &str) -> rusqlite::Result<Connection> {{\n    let connection = Connection::open(path)?;\n    connection.execute_batch(\"PRAGMA foreign_keys = ON;\")?;\n    connection.execute_batch(MIGRATION)?;\n    Ok(connection)\n}}\n",
+        migration(model, SqlDialect::Sqlite)
+    )];
+    for table in tables.iter().filter(|table| table.entity.is_some()) {
+        sections.push(entity_code(table));
+    }
+    for (join, from, to) in join_tables(&tables) {
+        sections.push(join_code(join, from, to));
+    }
+    sections.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::test_support::blog_model;
+
+    #[test]
+    fn test_data_access_layer() {
+        let code = data_access_layer(&blog_model());
+        syn::parse_file(&code).expect("data-access layer is not valid Rust");
+
+        assert!(code.contains("pub const MIGRATION: &str = r#\"-- Generated by Benjamin"));
+        assert!(code.contains(
+            "pub struct Post {\n    pub id: i64,\n    pub title: String,\n    pub published: bool,\n    pub published_at: Option<String>,\n    pub user_id: i64,\n}"
+        ));
+        assert!(code.contains("pub struct NewPost {\n    pub title: String,"));
+        assert!(code.contains(
+            "pub fn create_post(connection: &Connection, new: &NewPost) -> rusqlite::Result<Post>"
+        ));
+        assert!(code.contains("params![new.title, new.published, new.published_at, new.user_id]"));
+        assert!(code.contains(
+            "pub fn list_posts_by_user(connection: &Connection, user_id: i64) -> rusqlite::Result<Vec<Post>>"
+        ));
+        assert!(
+            code.contains("pub fn update_post(connection: &Connection, id: i64, new: &NewPost)")
+        );
+        assert!(code.contains(
+            "pub fn link_post_tag(connection: &Connection, post_id: i64, tag_id: i64) -> rusqlite::Result<()>"
+        ));
+        assert!(code.contains("pub fn list_tags_for_post(connection: &Connection, post_id: i64)"));
+        assert!(code.contains("pub fn list_posts_for_tag(connection: &Connection, tag_id: i64)"));
+    }
+
+    #[test]
+    fn test_data_access_statements() {
+        let statements = data_access_statements(&blog_model());
+        assert_eq!(statements.len(), 4 * 5 + 2 + 4);
+        assert!(statements.contains(&String::from(
+            "UPDATE \"post\" SET \"title\" = ?1, \"published\" = ?2, \"published_at\" = ?3, \"user_id\" = ?4 WHERE \"id\" = ?5"
+        )));
+        assert!(statements.contains(&String::from(
+            "SELECT t.\"id\", t.\"label\" FROM \"tag\" AS t JOIN \"post_tag\" AS j ON j.\"tag_id\" = t.\"id\" WHERE j.\"post_id\" = ?1 ORDER BY t.\"id\""
+        )));
+        assert_eq!(plural("category"), "categories");
+        assert_eq!(plural("day"), "days");
+        assert_eq!(plural("box"), "boxes");
+    }
+}
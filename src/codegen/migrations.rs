@@ -0,0 +1,381 @@
+use std::collections::HashSet;
+
+use crate::{
+    codegen::serde_structs::{pascal_case, snake_case},
+    models::{ColumnType, DataModel, RelationshipKind},
+};
+
+// Type names the data access layer imports or relies on, which an entity struct would shadow
+const RESERVED_ENTITY_NAMES: &[&str] = &[
+    "Connection",
+    "Row",
+    "OptionalExtension",
+    "Deserialize",
+    "Serialize",
+    "Self",
+    "Option",
+    "Result",
+    "Some",
+    "None",
+    "Ok",
+    "Err",
+    "Vec",
+    "String",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SqlDialect {
+    Sqlite,
+    Postgres,
+}
+
+// Column of a generated table, other than the id primary key
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub is_optional: bool,
+    pub is_unique: bool,
+    pub references: Option<String>,
+}
+
+// Table of an entity, or a join table when entity is None
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub name: String,
+    pub entity: Option<String>,
+    pub columns: Vec<Column>,
+}
+
+impl Table {
+    pub fn column_names(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect()
+    }
+}
+
+// SQL identifier for a model name, without the r# Rust adds to keywords
+pub fn sql_name(name: &str) -> String {
+    snake_case(name).trim_start_matches("r#").to_string()
+}
+
+pub fn quoted(name: &str) -> String {
+    format!("\"{}\"", name)
+}
+
+fn reference_column(entity: &str, references: &str) -> Column {
+    Column {
+        name: format!("{}_id", sql_name(entity)),
+        column_type: ColumnType::Integer,
+        is_optional: false,
+        is_unique: false,
+        references: Some(sql_name(references)),
+    }
+}
+
+// Entity tables in the order they can be created, followed by join tables
+pub fn tables(model: &DataModel) -> Vec<Table> {
+    let mut entity_tables: Vec<Table> = model
+        .entities
+        .iter()
+        .map(|entity| {
+            let mut columns: Vec<Column> = entity
+                .fields
+                .iter()
+                .filter(|field| sql_name(&field.name) != "id")
+                .map(|field| Column {
+                    name: sql_name(&field.name),
+                    column_type: field.column_type,
+                    is_optional: field.is_optional,
+                    is_unique: field.is_unique,
+                    references: None,
+                })
+                .collect();
+            for relationship in &model.relationships {
+                if relationship.kind != RelationshipKind::ManyToMany
+                    && sql_name(&relationship.to) == sql_name(&entity.name)
+                {
+                    let mut column = reference_column(&relationship.from, &relationship.from);
+                    column.is_unique = relationship.kind == RelationshipKind::OneToOne;
+                    columns.push(column);
+                }
+            }
+            Table {
+                name: sql_name(&entity.name),
+                entity: Some(entity.name.clone()),
+                columns,
+            }
+        })
+        .collect();
+
+    // Postgres needs referenced tables first; a cycle keeps the declared order
+    let mut ordered: Vec<Table> = vec![];
+    while !entity_tables.is_empty() {
+        let created: HashSet<String> = ordered.iter().map(|table| table.name.clone()).collect();
+        let ready = entity_tables.iter().position(|table| {
+            table.columns.iter().all(|column| match &column.references {
+                Some(referenced) => referenced == &table.name || created.contains(referenced),
+                None => true,
+            })
+        });
+        ordered.push(entity_tables.remove(ready.unwrap_or(0)));
+    }
+
+    for relationship in &model.relationships {
+        if relationship.kind == RelationshipKind::ManyToMany {
+            ordered.push(Table {
+                name: format!(
+                    "{}_{}",
+                    sql_name(&relationship.from),
+                    sql_name(&relationship.to)
+                ),
+                entity: None,
+                columns: vec![
+                    reference_column(&relationship.from, &relationship.from),
+                    reference_column(&relationship.to, &relationship.to),
+                ],
+            });
+        }
+    }
+    ordered
+}
+
+// Problems the model has to fix before its tables can be generated
+pub fn data_model_problems(model: &DataModel) -> Vec<String> {
+    let mut problems: Vec<String> = vec![];
+    if model.entities.is_empty() {
+        problems.push(String::from("the data model has no entities"));
+    }
+
+    for entity in &model.entities {
+        let type_name = pascal_case(&entity.name);
+        if RESERVED_ENTITY_NAMES.contains(&entity.name.as_str())
+            || RESERVED_ENTITY_NAMES.contains(&type_name.as_str())
+        {
+            problems.push(format!(
+                "entity {} clashes with a name the data access layer uses, rename it",
+                entity.name
+            ));
+        }
+    }
+
+    let entity_names: Vec<String> = model
+        .entities
+        .iter()
+        .map(|entity| sql_name(&entity.name))
+        .collect();
+    for relationship in &model.relationships {
+        for name in [&relationship.from, &relationship.to] {
+            if !entity_names.contains(&sql_name(name)) {
+                problems.push(format!("relationship refers to unknown entity {}", name));
+            }
+        }
+        if relationship.kind == RelationshipKind::ManyToMany
+            && sql_name(&relationship.from) == sql_name(&relationship.to)
+        {
+            problems.push(format!(
+                "many to many relationship of {} with itself needs an entity of its own",
+                relationship.from
+            ));
+        }
+    }
+
+    let mut table_names: HashSet<String> = HashSet::new();
+    for table in tables(model) {
+        if !table_names.insert(table.name.clone()) {
+            problems.push(format!("more than one table is named {}", table.name));
+        }
+        let mut column_names: HashSet<&str> = HashSet::new();
+        for column in &table.columns {
+            if !column_names.insert(&column.name) {
+                problems.push(format!(
+                    "table {} has more than one column named {}",
+                    table.name, column.name
+                ));
+            }
+        }
+    }
+    problems
+}
+
+fn sql_type(column_type: ColumnType, dialect: SqlDialect) -> &'static str {
+    match (dialect, column_type) {
+        (SqlDialect::Sqlite, ColumnType::Integer | ColumnType::Boolean) => "INTEGER",
+        (SqlDialect::Sqlite, ColumnType::Real) => "REAL",
+        // SQLite has no date type, timestamps are kept as ISO 8601 text
+        (SqlDialect::Sqlite, ColumnType::Text | ColumnType::Timestamp) => "TEXT",
+        (SqlDialect::Postgres, ColumnType::Integer) => "BIGINT",
+        (SqlDialect::Postgres, ColumnType::Real) => "DOUBLE PRECISION",
+        (SqlDialect::Postgres, ColumnType::Text) => "TEXT",
+        (SqlDialect::Postgres, ColumnType::Boolean) => "BOOLEAN",
+        (SqlDialect::Postgres, ColumnType::Timestamp) => "TIMESTAMPTZ",
+    }
+}
+
+fn column_definition(column: &Column, dialect: SqlDialect) -> String {
+    let mut definition = format!(
+        "{} {}",
+        quoted(&column.name),
+        sql_type(column.column_type, dialect)
+    );
+    if !column.is_optional {
+        definition.push_str(" NOT NULL");
+    }
+    if column.is_unique {
+        definition.push_str(" UNIQUE");
+    }
+    if let Some(referenced) = &column.references {
+        definition = format!(
+            "{} REFERENCES {} (\"id\") ON DELETE CASCADE",
+            definition,
+            quoted(referenced)
+        );
+    }
+    definition
+}
+
+// Migration creating every table; it can run again on an existing database
+pub fn migration(model: &DataModel, dialect: SqlDialect) -> String {
+    let mut statements: Vec<String> = vec![];
+    for table in tables(model) {
+        let mut definitions: Vec<String> = vec![];
+        if table.entity.is_some() {
+            definitions.push(match dialect {
+                SqlDialect::Sqlite => String::from("\"id\" INTEGER PRIMARY KEY AUTOINCREMENT"),
+                SqlDialect::Postgres => String::from("\"id\" BIGSERIAL PRIMARY KEY"),
+            });
+        }
+        definitions.extend(
+            table
+                .columns
+                .iter()
+                .map(|column| column_definition(column, dialect)),
+        );
+        if table.entity.is_none() {
+            let key: Vec<String> = table.column_names().into_iter().map(quoted).collect();
+            definitions.push(format!("PRIMARY KEY ({})", key.join(", ")));
+        }
+        statements.push(format!(
+            "CREATE TABLE IF NOT EXISTS {} (\n    {}\n);",
+            quoted(&table.name),
+            definitions.join(",\n    ")
+        ));
+
+        for column in table.columns.iter().filter(|column| {
+            column.references.is_some() && !column.is_unique && table.entity.is_some()
+        }) {
+            statements.push(format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} ({});",
+                quoted(&format!("{}_{}_index", table.name, column.name)),
+                quoted(&table.name),
+                quoted(&column.name)
+            ));
+        }
+    }
+    format!(
+        "-- Generated by Benjamin from the data model\n{}\n",
+        statements.join("\n\n")
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::test_support::blog_model;
+
+    #[test]
+    fn test_tables() {
+        let tables = tables(&blog_model());
+        let names: Vec<&str> = tables.iter().map(|table| table.name.as_str()).collect();
+        assert_eq!(names, vec!["user", "post", "tag", "profile", "post_tag"]);
+
+        assert_eq!(tables[0].column_names(), vec!["email"]);
+        assert_eq!(
+            tables[1].column_names(),
+            vec!["title", "published", "published_at", "user_id"]
+        );
+        assert!(tables[3].columns[1].is_unique);
+        assert_eq!(tables[4].column_names(), vec!["post_id", "tag_id"]);
+        assert!(data_model_problems(&blog_model()).is_empty());
+    }
+
+    #[test]
+    fn test_data_model_problems() {
+        let model: DataModel = serde_json::from_value(json!({
+            "entities": [
+                {"name": "Team", "fields": []},
+                {"name": "Member", "fields": [{"name": "team_id", "type": "integer"}]},
+                {"name": "team", "fields": []}
+            ],
+            "relationships": [
+                {"from": "Team", "to": "Member", "kind": "one_to_many"},
+                {"from": "Member", "to": "Squad", "kind": "one_to_many"},
+                {"from": "Member", "to": "Member", "kind": "many_to_many"}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            data_model_problems(&model),
+            vec![
+                "relationship refers to unknown entity Squad",
+                "many to many relationship of Member with itself needs an entity of its own",
+                "table member has more than one column named team_id",
+                "more than one table is named team",
+                "table member_member has more than one column named member_id",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reserved_entity_names() {
+        let model: DataModel = serde_json::from_value(json!({
+            "entities": [
+                {"name": "Connection", "fields": []},
+                {"name": "row", "fields": []},
+                {"name": "Self", "fields": []},
+                {"name": "option", "fields": []},
+                {"name": "Result", "fields": []},
+                {"name": "Rowing", "fields": []}
+            ],
+            "relationships": []
+        }))
+        .unwrap();
+
+        assert_eq!(
+            data_model_problems(&model),
+            vec![
+                "entity Connection clashes with a name the data access layer uses, rename it",
+                "entity row clashes with a name the data access layer uses, rename it",
+                "entity Self clashes with a name the data access layer uses, rename it",
+                "entity option clashes with a name the data access layer uses, rename it",
+                "entity Result clashes with a name the data access layer uses, rename it",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migration() {
+        let sqlite = migration(&blog_model(), SqlDialect::Sqlite);
+        assert!(sqlite.starts_with("-- Generated by Benjamin from the data model\n"));
+        assert!(sqlite.contains(
+            "CREATE TABLE IF NOT EXISTS \"post\" (\n    \"id\" INTEGER PRIMARY KEY AUTOINCREMENT,\n    \"title\" TEXT NOT NULL,\n    \"published\" INTEGER NOT NULL,\n    \"published_at\" TEXT,\n    \"user_id\" INTEGER NOT NULL REFERENCES \"user\" (\"id\") ON DELETE CASCADE\n);"
+        ));
+        assert!(sqlite.contains(
+            "CREATE INDEX IF NOT EXISTS \"post_user_id_index\" ON \"post\" (\"user_id\");"
+        ));
+        assert!(sqlite.contains("\"user_id\" INTEGER NOT NULL UNIQUE REFERENCES \"user\""));
+        assert!(sqlite.contains("PRIMARY KEY (\"post_id\", \"tag_id\")"));
+        assert!(!sqlite.contains("\"profile_user_id_index\""));
+
+        let postgres = migration(&blog_model(), SqlDialect::Postgres);
+        assert!(postgres.contains("\"id\" BIGSERIAL PRIMARY KEY"));
+        assert!(postgres.contains("\"published\" BOOLEAN NOT NULL"));
+        assert!(postgres.contains("\"published_at\" TIMESTAMPTZ,"));
+        assert!(postgres.contains("\"user_id\" BIGINT NOT NULL REFERENCES \"user\""));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod auth_module;
+pub mod client_sdk;
+pub mod collections;
+pub mod data_access;
+pub mod migrations;
+pub mod openapi;
+pub mod serde_structs;
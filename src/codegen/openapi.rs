@@ -0,0 +1,206 @@
+use std::path::Path;
+
+use serde_json::{json, Map, Value};
+
+use crate::models::{FieldSchema, HttpMethod, RouteObject};
+
+// JSON Schema for a route's request or response schema
+pub fn json_schema(schema: &FieldSchema) -> Value {
+    match schema {
+        FieldSchema::Empty | FieldSchema::Unknown => json!({}),
+        FieldSchema::Number => json!({"type": "number"}),
+        FieldSchema::String => json!({"type": "string"}),
+        FieldSchema::Bool => json!({"type": "boolean"}),
+        FieldSchema::Optional(inner) => match json_schema(inner) {
+            // OpenAPI 3.1 allows a list of types, which keeps simple fields readable
+            Value::Object(mut inner) if inner.len() == 1 && inner["type"].is_string() => {
+                inner.insert(String::from("type"), json!([inner["type"], "null"]));
+                Value::Object(inner)
+            }
+            inner => json!({"anyOf": [inner, {"type": "null"}]}),
+        },
+        FieldSchema::Array(item) => json!({"type": "array", "items": json_schema(item)}),
+        FieldSchema::Object(fields) => {
+            let properties: Map<String, Value> = fields
+                .iter()
+                .map(|(key, field)| (key.clone(), json_schema(field)))
+                .collect();
+            let required: Vec<&String> = fields
+                .iter()
+                .filter(|(_, field)| !matches!(field, FieldSchema::Optional(_)))
+                .map(|(key, _)| key)
+                .collect();
+            let mut schema = json!({"type": "object", "properties": properties});
+            if !required.is_empty() {
+                schema["required"] = json!(required);
+            }
+            schema
+        }
+        // Struct names carry no fields, so only the name is kept
+        FieldSchema::Named(name) => json!({"title": name}),
+    }
+}
+
+fn has_body(schema: &FieldSchema) -> bool {
+    !matches!(schema, FieldSchema::Empty | FieldSchema::Unknown)
+}
+
+// Unique operation name such as "get_item_id" for GET /item/{id}
+pub fn operation_id(route: &RouteObject) -> String {
+    let path: Vec<String> = route
+        .route
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.to_lowercase())
+        .collect();
+    if path.is_empty() {
+        format!("{}_root", route.method.as_str())
+    } else {
+        format!("{}_{}", route.method.as_str(), path.join("_"))
+    }
+}
+
+fn operation(route: &RouteObject) -> Value {
+    let mut operation = json!({
+        "operationId": operation_id(route),
+        "summary": format!("{} {}", route.method, route.route),
+    });
+    if !route.path_params.is_empty() {
+        let parameters: Vec<Value> = route
+            .path_params
+            .iter()
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": {"type": "string"}
+                })
+            })
+            .collect();
+        operation["parameters"] = json!(parameters);
+    }
+    if has_body(&route.request_body) && route.method != HttpMethod::Get {
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": {"application/json": {"schema": json_schema(&route.request_body)}}
+        });
+    }
+    let mut success = json!({"description": "Successful response"});
+    if has_body(&route.response) {
+        success["content"] = json!({"application/json": {"schema": json_schema(&route.response)}});
+    }
+    operation["responses"] = json!({"200": success});
+    operation
+}
+
+// OpenAPI 3.1 document describing every extracted route
+pub fn openapi_document(description: &str, routes: &[RouteObject]) -> Value {
+    let mut paths: Map<String, Value> = Map::new();
+    for route in routes {
+        let path_item = paths
+            .entry(route.route.clone())
+            .or_insert_with(|| json!({}));
+        path_item[route.method.as_str()] = operation(route);
+    }
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Benjamin generated API",
+            "description": description,
+            "version": "1.0.0"
+        },
+        "paths": paths
+    })
+}
+
+// JSON for a .json path, YAML otherwise
+pub fn render_openapi(document: &Value, path: &Path) -> String {
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    if is_json {
+        serde_json::to_string_pretty(document).expect("Failed to encode OpenAPI document")
+    } else {
+        serde_yaml::to_string(document).expect("Failed to encode OpenAPI document")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn route(method: HttpMethod, path: &str, request: Value, response: Value) -> RouteObject {
+        RouteObject::new(
+            method,
+            path,
+            FieldSchema::from(request),
+            FieldSchema::from(response),
+        )
+    }
+
+    #[test]
+    fn test_openapi_document() {
+        let item = json!({"id": "number", "name": "string", "note": "Option<string>"});
+        let routes = vec![
+            route(
+                HttpMethod::Post,
+                "/item",
+                json!({"name": "string"}),
+                item.clone(),
+            ),
+            route(HttpMethod::Get, "/item/{id}", json!("None"), item.clone()),
+            route(
+                HttpMethod::Delete,
+                "/item/{id}",
+                json!("None"),
+                json!("None"),
+            ),
+            route(HttpMethod::Get, "/items", json!("None"), json!([item])),
+        ];
+
+        let document = openapi_document("A todo list", &routes);
+        assert_eq!(document["openapi"], "3.1.0");
+        assert_eq!(document["info"]["description"], "A todo list");
+
+        let create = &document["paths"]["/item"]["post"];
+        assert_eq!(create["operationId"], "post_item");
+        assert_eq!(
+            create["requestBody"]["content"]["application/json"]["schema"],
+            json!({"type": "object", "properties": {"name": {"type": "string"}}, "required": ["name"]})
+        );
+
+        let read = &document["paths"]["/item/{id}"]["get"];
+        assert_eq!(
+            read["parameters"],
+            json!([{"name": "id", "in": "path", "required": true, "schema": {"type": "string"}}])
+        );
+        assert!(read.get("requestBody").is_none());
+        let item_schema = &read["responses"]["200"]["content"]["application/json"]["schema"];
+        assert_eq!(item_schema["required"], json!(["id", "name"]));
+        assert_eq!(
+            item_schema["properties"]["note"],
+            json!({"type": ["string", "null"]})
+        );
+
+        let delete = &document["paths"]["/item/{id}"]["delete"];
+        assert!(delete["responses"]["200"].get("content").is_none());
+        assert_eq!(
+            document["paths"]["/items"]["get"]["responses"]["200"]["content"]["application/json"]
+                ["schema"]["type"],
+            "array"
+        );
+    }
+
+    #[test]
+    fn test_render_openapi() {
+        let document = openapi_document("", &[]);
+
+        let yaml = render_openapi(&document, Path::new("/project/openapi.yaml"));
+        assert!(yaml.contains("openapi: 3.1.0\n"));
+        assert_eq!(serde_yaml::from_str::<Value>(&yaml).unwrap(), document);
+
+        let json = render_openapi(&document, Path::new("/project/openapi.JSON"));
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), document);
+    }
+}
@@ -0,0 +1,513 @@
+use std::collections::HashSet;
+
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::models::ExternalApiSample;
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+// Keywords that cannot be raw identifiers, so they get a suffix instead
+const PATH_KEYWORDS: &[&str] = &["self", "crate", "super"];
+
+// Type names the generated code already uses, so structs get a suffix instead
+const RESERVED_TYPE_NAMES: &[&str] = &[
+    "Self",
+    "Option",
+    "Some",
+    "None",
+    "Result",
+    "Ok",
+    "Err",
+    "String",
+    "Vec",
+    "Box",
+    "Clone",
+    "Copy",
+    "Debug",
+    "Default",
+    "Deserialize",
+    "Serialize",
+    "Drop",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "Iterator",
+    "IntoIterator",
+    "From",
+    "Into",
+    "ToOwned",
+    "ToString",
+    "Send",
+    "Sync",
+    "Sized",
+    "PartialEq",
+    "Eq",
+    "PartialOrd",
+    "Ord",
+];
+
+// Type of a JSON value, merged across every sample of it
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Null,
+    Bool,
+    // Whether any sample is below zero or above i64::MAX
+    Int { negative: bool, big: bool },
+    Float,
+    Text,
+    Array(Box<Shape>),
+    Object(Vec<Field>),
+    Optional(Box<Shape>),
+    // Items of an empty array, which take the shape of any other sample
+    Unknown,
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    key: String,
+    shape: Shape,
+}
+
+fn infer(value: &Value) -> Shape {
+    match value {
+        Value::Null => Shape::Null,
+        Value::Bool(_) => Shape::Bool,
+        Value::Number(number) if number.is_f64() => Shape::Float,
+        Value::Number(number) => Shape::Int {
+            negative: number.as_i64().is_some_and(|n| n < 0),
+            big: !number.is_i64(),
+        },
+        Value::String(_) => Shape::Text,
+        Value::Array(items) => {
+            let item = items
+                .iter()
+                .map(infer)
+                .reduce(merge)
+                .unwrap_or(Shape::Unknown);
+            Shape::Array(Box::new(item))
+        }
+        Value::Object(fields) => Shape::Object(
+            fields
+                .iter()
+                .map(|(key, field)| Field {
+                    key: key.clone(),
+                    shape: infer(field),
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn optional(shape: Shape) -> Shape {
+    match shape {
+        Shape::Optional(_) => shape,
+        Shape::Null => Shape::Optional(Box::new(Shape::Any)),
+        shape => Shape::Optional(Box::new(shape)),
+    }
+}
+
+// Combine the shapes of two samples of the same value
+fn merge(a: Shape, b: Shape) -> Shape {
+    match (a, b) {
+        (a, b) if a == b => a,
+        (Shape::Unknown, other) | (other, Shape::Unknown) => other,
+        (Shape::Null, other) | (other, Shape::Null) => optional(other),
+        (Shape::Optional(a), b) | (b, Shape::Optional(a)) => optional(merge(*a, b)),
+        (
+            Shape::Int {
+                negative: a_negative,
+                big: a_big,
+            },
+            Shape::Int {
+                negative: b_negative,
+                big: b_big,
+            },
+        ) => Shape::Int {
+            negative: a_negative || b_negative,
+            big: a_big || b_big,
+        },
+        (Shape::Int { .. }, Shape::Float) | (Shape::Float, Shape::Int { .. }) => Shape::Float,
+        (Shape::Array(a), Shape::Array(b)) => Shape::Array(Box::new(merge(*a, *b))),
+        (Shape::Any, _) | (_, Shape::Any) => Shape::Any,
+        (Shape::Object(a), Shape::Object(b)) => {
+            let mut fields: Vec<Field> = vec![];
+            for field in &a {
+                let shape = match b.iter().find(|other| other.key == field.key) {
+                    Some(other) => merge(field.shape.clone(), other.shape.clone()),
+                    None => optional(field.shape.clone()),
+                };
+                fields.push(Field {
+                    key: field.key.clone(),
+                    shape,
+                });
+            }
+            for field in b
+                .iter()
+                .filter(|field| !a.iter().any(|f| f.key == field.key))
+            {
+                fields.push(Field {
+                    key: field.key.clone(),
+                    shape: optional(field.shape.clone()),
+                });
+            }
+            Shape::Object(fields)
+        }
+        _ => Shape::Any,
+    }
+}
+
+fn split_words(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = vec![];
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in text.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        // camelCase boundary
+        if c.is_ascii_uppercase() && previous_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+pub fn pascal_case(text: &str) -> String {
+    let name: String = split_words(text)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    match name.chars().next() {
+        None => String::from("Value"),
+        Some(first) if first.is_ascii_digit() => format!("Type{}", name),
+        Some(_) => name,
+    }
+}
+
+// Name for a generated struct or alias, clear of the names the generated code uses
+pub fn struct_name(text: &str) -> String {
+    let name = pascal_case(text);
+    if RESERVED_TYPE_NAMES.contains(&name.as_str()) {
+        return format!("{}Value", name);
+    }
+    name
+}
+
+pub fn snake_case(text: &str) -> String {
+    let name = split_words(text).join("_");
+    match name.chars().next() {
+        None => String::from("field"),
+        Some(first) if first.is_ascii_digit() => format!("field_{}", name),
+        Some(_) if PATH_KEYWORDS.contains(&name.as_str()) => format!("{}_", name),
+        Some(_) if RUST_KEYWORDS.contains(&name.as_str()) => format!("r#{}", name),
+        Some(_) => name,
+    }
+}
+
+fn singular(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if name.len() > 3 && name.ends_with('s') && !name.ends_with("ss") {
+        name[..name.len() - 1].to_string()
+    } else {
+        format!("{}Item", name)
+    }
+}
+
+// Renders structs depth first, keeping every struct name unique
+struct Generator {
+    structs: Vec<String>,
+    used_names: HashSet<String>,
+}
+
+impl Generator {
+    fn unique_name(&mut self, name: String) -> String {
+        let mut candidate = name.clone();
+        let mut suffix = 2;
+        while self.used_names.contains(&candidate) {
+            candidate = format!("{}{}", name, suffix);
+            suffix += 1;
+        }
+        self.used_names.insert(candidate.clone());
+        candidate
+    }
+
+    fn rust_type(&mut self, shape: &Shape, name_hint: &str) -> String {
+        match shape {
+            Shape::Null | Shape::Unknown | Shape::Any => String::from("serde_json::Value"),
+            Shape::Bool => String::from("bool"),
+            // Neither i64 nor u64 holds both a negative sample and one above i64::MAX
+            Shape::Int {
+                negative: true,
+                big: true,
+            } => String::from("serde_json::Number"),
+            Shape::Int { big: true, .. } => String::from("u64"),
+            Shape::Int { .. } => String::from("i64"),
+            Shape::Float => String::from("f64"),
+            Shape::Text => String::from("String"),
+            Shape::Array(item) => {
+                format!("Vec<{}>", self.rust_type(item, &singular(name_hint)))
+            }
+            Shape::Optional(inner) => format!("Option<{}>", self.rust_type(inner, name_hint)),
+            Shape::Object(fields) => self.render_struct(fields, name_hint),
+        }
+    }
+
+    fn render_struct(&mut self, fields: &[Field], name_hint: &str) -> String {
+        let name = self.unique_name(struct_name(name_hint));
+        let mut used_fields: HashSet<String> = HashSet::new();
+        let mut lines: Vec<String> = vec![];
+        for field in fields {
+            let mut field_name = snake_case(&field.key);
+            while !used_fields.insert(field_name.clone()) {
+                field_name = format!("{}_", field_name);
+            }
+            let field_type = self.rust_type(&field.shape, &field.key);
+            if field_name.trim_start_matches("r#") != field.key {
+                lines.push(format!("    #[serde(rename = {:?})]", field.key));
+            }
+            if matches!(field.shape, Shape::Optional(_)) {
+                lines.push(String::from("    #[serde(default)]"));
+            }
+            lines.push(format!("    pub {}: {},", field_name, field_type));
+        }
+
+        self.structs.push(format!(
+            "#[derive(Debug, Clone, Deserialize)]\npub struct {} {{\n{}\n}}",
+            name,
+            lines.join("\n")
+        ));
+        name
+    }
+}
+
+// Deserialize structs for a JSON payload, nested types first and root_name last
+pub fn generate_structs(root_name: &str, payload: &Value) -> String {
+    let mut generator = Generator {
+        structs: vec![],
+        used_names: HashSet::new(),
+    };
+    match infer(payload) {
+        Shape::Object(fields) => {
+            generator.render_struct(&fields, root_name);
+        }
+        shape => {
+            let root_type = generator.rust_type(&shape, root_name);
+            let alias = struct_name(root_name);
+            generator
+                .structs
+                .push(format!("pub type {} = {};", alias, root_type));
+        }
+    }
+    generator.structs.join("\n\n")
+}
+
+// Root type name for the response of an external url, such as LatestRatesResponse
+pub fn type_name_for_url(url: &str) -> String {
+    let segment = Url::parse(url)
+        .ok()
+        .and_then(|parsed| {
+            parsed
+                .path_segments()?
+                .rfind(|segment| {
+                    !segment.is_empty() && !segment.chars().all(|c| c.is_ascii_digit())
+                })
+                .map(String::from)
+                .or(parsed.host_str().map(String::from))
+        })
+        .unwrap_or_default();
+    format!("{}Response", pascal_case(&segment))
+}
+
+// Types for every JSON sample, ready to paste into the generated project
+pub fn generate_external_types(samples: &[ExternalApiSample]) -> Option<String> {
+    let mut used_names: HashSet<String> = HashSet::new();
+    let mut sections: Vec<String> = vec![];
+    for sample in samples {
+        let Ok(payload) = serde_json::from_str::<Value>(&sample.body) else {
+            continue;
+        };
+        let mut root_name = type_name_for_url(&sample.url);
+        while !used_names.insert(root_name.clone()) {
+            root_name = format!("{}Alt", root_name);
+        }
+        // Each url gets its own module, so nested struct names never clash
+        let module = snake_case(&root_name).trim_start_matches("r#").to_string();
+        let structs = generate_structs(&root_name, &payload)
+            .lines()
+            .map(|line| {
+                if line.is_empty() {
+                    String::new()
+                } else {
+                    format!("    {}", line)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        sections.push(format!(
+            "// {}\npub mod {} {{\n    use serde::Deserialize;\n\n{}\n}}",
+            sample.url, module, structs
+        ));
+    }
+    if sections.is_empty() {
+        return None;
+    }
+    Some(sections.join("\n\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_generate_structs() {
+        let payload = json!({
+            "base": "USD",
+            "timeStamp": 1700000000,
+            "type": "latest",
+            "rates": [
+                {"code": "EUR", "rate": 0.9, "note": null},
+                {"code": "GBP", "rate": 1, "note": "fixed", "source": "ecb"}
+            ],
+            "meta": {"2xx": true}
+        });
+
+        let structs = generate_structs("LatestResponse", &payload);
+        assert_eq!(
+            structs,
+            r#"#[derive(Debug, Clone, Deserialize)]
+pub struct Meta {
+    #[serde(rename = "2xx")]
+    pub field_2xx: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rate {
+    pub code: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub rate: f64,
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LatestResponse {
+    pub base: String,
+    pub meta: Meta,
+    pub rates: Vec<Rate>,
+    #[serde(rename = "timeStamp")]
+    pub time_stamp: i64,
+    pub r#type: String,
+}"#
+        );
+
+        assert_eq!(
+            generate_structs("PricesResponse", &json!([[1, 2.5], []])),
+            "pub type PricesResponse = Vec<Vec<f64>>;"
+        );
+    }
+
+    #[test]
+    fn test_generate_structs_with_path_keywords() {
+        let payload = json!({
+            "self": {"href": "/orders/1"},
+            "Self": "alias",
+            "crate": 3,
+            "super": true,
+            "match": "exact"
+        });
+
+        let structs = generate_structs("OrderResponse", &payload);
+        assert!(structs.contains("pub struct SelfValue {"));
+        assert!(structs.contains("    #[serde(rename = \"self\")]\n    pub self__: SelfValue,"));
+        assert!(structs.contains("    #[serde(rename = \"Self\")]\n    pub self_: String,"));
+        assert!(structs.contains("    #[serde(rename = \"crate\")]\n    pub crate_: i64,"));
+        assert!(structs.contains("    #[serde(rename = \"super\")]\n    pub super_: bool,"));
+        assert!(structs.contains("    pub r#match: String,"));
+        syn::parse_file(&structs).expect("Generated structs should parse");
+    }
+
+    #[test]
+    fn test_generate_structs_with_reserved_names() {
+        let payload = json!({
+            "option": {"enabled": true},
+            "string": {"value": "text"},
+            "vec": [{"x": 1}],
+            "result": {"ok": true},
+            "id": 18446744073709551615u64,
+            "balance": [5, 18446744073709551615u64],
+            "delta": [-5, 18446744073709551615u64]
+        });
+
+        let structs = generate_structs("Option", &payload);
+        assert!(structs.contains("pub struct OptionValue {"));
+        assert!(structs.contains("pub struct OptionValue2 {"));
+        assert!(structs.contains("pub struct StringValue {"));
+        assert!(structs.contains("pub struct ResultValue {"));
+        assert!(structs.contains("pub struct VecItem {"));
+        assert!(structs.contains("    pub option: OptionValue2,"));
+        assert!(structs.contains("    pub id: u64,"));
+        assert!(structs.contains("    pub balance: Vec<u64>,"));
+        assert!(structs.contains("    pub delta: Vec<serde_json::Number>,"));
+        assert!(!structs.contains("pub struct Option {"));
+        assert!(!structs.contains("pub struct String {"));
+        syn::parse_file(&structs).expect("Generated structs should parse");
+    }
+
+    #[test]
+    fn test_generate_external_types() {
+        assert_eq!(
+            type_name_for_url("https://api.rates.io/v1/latest-rates?base=USD"),
+            "LatestRatesResponse"
+        );
+        assert_eq!(
+            type_name_for_url("https://api.rates.io/"),
+            "ApiRatesIoResponse"
+        );
+
+        let samples = vec![
+            ExternalApiSample {
+                url: String::from("https://api.rates.io/v1/latest"),
+                status: 200,
+                content_type: None,
+                body: String::from(r#"{"base": "USD"}"#),
+            },
+            ExternalApiSample {
+                url: String::from("https://example.com/page"),
+                status: 200,
+                content_type: None,
+                body: String::from("<html></html>"),
+            },
+        ];
+        let types = generate_external_types(&samples).unwrap();
+        assert!(types.starts_with("// https://api.rates.io/v1/latest\npub mod latest_response {"));
+        assert!(types.contains("    pub struct LatestResponse {\n        pub base: String,\n    }"));
+        assert!(!types.contains("example.com"));
+    }
+}
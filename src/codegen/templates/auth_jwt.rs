@@ -0,0 +1,265 @@
+// Authentication added by Benjamin from its reviewed JWT template. Do not edit by hand.
+// Passwords are hashed with Argon2id and logins hand out HS256 tokens for the
+// `Authorization: Bearer` header. Logout revokes the token until it expires.
+#![allow(dead_code)]
+
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Payload, ResourceDef, Service, ServiceRequest, ServiceResponse, Transform},
+    error::ErrorUnauthorized,
+    http::header::AUTHORIZATION,
+    web, Error, FromRequest, HttpRequest, HttpResponse,
+};
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+pub const TOKEN_LIFETIME_SECS: u64 = 60 * 60;
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+// Paths that need a logged in user, whatever the method
+pub const PROTECTED_ROUTES: &[&str] = &[/* PROTECTED_ROUTES */];
+
+#[derive(Debug, Deserialize)]
+pub struct Credentials {
+    #[serde(alias = "email", alias = "name", alias = "login")]
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    sub: String,
+    exp: u64,
+    jti: String,
+}
+
+// Accounts, signing secret and revoked tokens, shared with App::app_data
+pub struct AuthState {
+    // username -> Argon2 password hash
+    users: Mutex<HashMap<String, String>>,
+    // ids of tokens that were logged out
+    revoked: Mutex<HashSet<String>>,
+    secret: Vec<u8>,
+}
+
+impl AuthState {
+    // Signs with AUTH_JWT_SECRET, or a random secret that ends all sessions on restart
+    pub fn new() -> web::Data<AuthState> {
+        let secret = env::var("AUTH_JWT_SECRET")
+            .ok()
+            .filter(|secret| secret.len() >= 32)
+            .map(String::into_bytes)
+            .unwrap_or_else(|| random_bytes::<32>().to_vec());
+        web::Data::new(AuthState {
+            users: Mutex::new(HashMap::new()),
+            revoked: Mutex::new(HashSet::new()),
+            secret,
+        })
+    }
+}
+
+// The logged in user; as a handler argument it refuses requests without a valid token
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthenticatedUser {
+    pub username: String,
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).expect("Failed to read random bytes");
+    bytes
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::encode_b64(&random_bytes::<16>())?;
+    let hash = Argon2::default().hash_password(password.as_bytes(), &salt)?;
+    Ok(hash.to_string())
+}
+
+fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+fn bearer_token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+}
+
+// Claims of a token that is signed, unexpired and not revoked
+fn valid_claims(state: &AuthState, token: &str) -> Option<Claims> {
+    let claims = decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(&state.secret),
+        &Validation::new(Algorithm::HS256),
+    )
+    .ok()?
+    .claims;
+    let revoked = state.revoked.lock().expect("Revocation list poisoned");
+    (!revoked.contains(&claims.jti)).then_some(claims)
+}
+
+fn current_user(req: &HttpRequest) -> Option<AuthenticatedUser> {
+    let state = req.app_data::<web::Data<AuthState>>()?;
+    let claims = valid_claims(state, &bearer_token(req)?)?;
+    Some(AuthenticatedUser {
+        username: claims.sub,
+    })
+}
+
+pub async fn signup(state: web::Data<AuthState>, credentials: web::Json<Credentials>) -> HttpResponse {
+    let username = credentials.username.trim().to_string();
+    if username.is_empty() || credentials.password.len() < MIN_PASSWORD_LENGTH {
+        return HttpResponse::BadRequest().json(json!({
+            "error": format!("username is required and passwords need at least {} characters", MIN_PASSWORD_LENGTH)
+        }));
+    }
+    let Ok(hash) = hash_password(&credentials.password) else {
+        return HttpResponse::InternalServerError().json(json!({"error": "could not hash the password"}));
+    };
+    let mut users = state.users.lock().expect("User store poisoned");
+    if users.contains_key(&username) {
+        return HttpResponse::Conflict().json(json!({"error": "username is already taken"}));
+    }
+    users.insert(username.clone(), hash);
+    HttpResponse::Created().json(json!({"username": username}))
+}
+
+pub async fn login(state: web::Data<AuthState>, credentials: web::Json<Credentials>) -> HttpResponse {
+    let username = credentials.username.trim().to_string();
+    let hash = state
+        .users
+        .lock()
+        .expect("User store poisoned")
+        .get(&username)
+        .cloned();
+    let is_valid = hash.is_some_and(|hash| verify_password(&credentials.password, &hash));
+    if !is_valid {
+        return HttpResponse::Unauthorized().json(json!({"error": "invalid username or password"}));
+    }
+
+    let jti: String = random_bytes::<16>()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let claims = Claims {
+        sub: username.clone(),
+        exp: now_secs() + TOKEN_LIFETIME_SECS,
+        jti,
+    };
+    match encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(&state.secret),
+    ) {
+        Ok(token) => HttpResponse::Ok().json(json!({
+            "token": token,
+            "token_type": "Bearer",
+            "expires_in": TOKEN_LIFETIME_SECS,
+            "username": username
+        })),
+        Err(_) => HttpResponse::InternalServerError().json(json!({"error": "could not sign the token"})),
+    }
+}
+
+pub async fn logout(req: HttpRequest, state: web::Data<AuthState>) -> HttpResponse {
+    let Some(claims) = bearer_token(&req).and_then(|token| valid_claims(&state, &token)) else {
+        return HttpResponse::Unauthorized().json(json!({"error": "not logged in"}));
+    };
+    state
+        .revoked
+        .lock()
+        .expect("Revocation list poisoned")
+        .insert(claims.jti);
+    HttpResponse::Ok().json(json!({"message": "logged out"}))
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(current_user(req).ok_or_else(|| ErrorUnauthorized("login required")))
+    }
+}
+
+// Middleware answering 401 on PROTECTED_ROUTES unless the request has a valid token: App::new().wrap(auth::RequireLogin)
+pub struct RequireLogin;
+
+impl<S, B> Transform<S, ServiceRequest> for RequireLogin
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RequireLoginMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let protected = PROTECTED_ROUTES
+            .iter()
+            .map(|path| ResourceDef::new(*path))
+            .collect();
+        ready(Ok(RequireLoginMiddleware { service, protected }))
+    }
+}
+
+pub struct RequireLoginMiddleware<S> {
+    service: S,
+    protected: Vec<ResourceDef>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireLoginMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let is_protected = self.protected.iter().any(|path| path.is_match(req.path()));
+        if is_protected && current_user(req.request()).is_none() {
+            let response = HttpResponse::Unauthorized().json(json!({"error": "login required"}));
+            return Box::pin(ready(Ok(req.into_response(response).map_into_right_body())));
+        }
+        let response = self.service.call(req);
+        Box::pin(async move { response.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
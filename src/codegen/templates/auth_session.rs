@@ -0,0 +1,262 @@
+// Authentication added by Benjamin from its reviewed session template. Do not edit by hand.
+// Passwords are hashed with Argon2id and sessions are random tokens kept on the server,
+// sent back as an HttpOnly cookie and in the login response for Bearer clients.
+// Sessions expire SESSION_LIFETIME_SECS after login.
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    body::EitherBody,
+    cookie::{time, Cookie, SameSite},
+    dev::{forward_ready, Payload, ResourceDef, Service, ServiceRequest, ServiceResponse, Transform},
+    error::ErrorUnauthorized,
+    http::header::AUTHORIZATION,
+    web, Error, FromRequest, HttpRequest, HttpResponse,
+};
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+pub const SESSION_COOKIE: &str = "session";
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+pub const SESSION_LIFETIME_SECS: u64 = 60 * 60;
+
+// Paths that need a logged in user, whatever the method
+pub const PROTECTED_ROUTES: &[&str] = &[/* PROTECTED_ROUTES */];
+
+#[derive(Debug, Deserialize)]
+pub struct Credentials {
+    #[serde(alias = "email", alias = "name", alias = "login")]
+    pub username: String,
+    pub password: String,
+}
+
+struct Session {
+    username: String,
+    expires_at: Instant,
+}
+
+// Accounts and open sessions, shared with App::app_data
+#[derive(Default)]
+pub struct AuthState {
+    // username -> Argon2 password hash
+    users: Mutex<HashMap<String, String>>,
+    // session token -> session
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl AuthState {
+    pub fn new() -> web::Data<AuthState> {
+        web::Data::new(AuthState::default())
+    }
+}
+
+// The logged in user; as a handler argument it refuses requests without a session
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthenticatedUser {
+    pub username: String,
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).expect("Failed to read random bytes");
+    bytes
+}
+
+fn new_session_token() -> String {
+    random_bytes::<32>()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::encode_b64(&random_bytes::<16>())?;
+    let hash = Argon2::default().hash_password(password.as_bytes(), &salt)?;
+    Ok(hash.to_string())
+}
+
+fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+// Session token from an `Authorization: Bearer` header or the session cookie
+fn request_token(req: &HttpRequest) -> Option<String> {
+    let bearer = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    bearer.or_else(|| {
+        req.cookie(SESSION_COOKIE)
+            .map(|cookie| cookie.value().to_string())
+    })
+}
+
+// User of a live session; an expired session is dropped and counts as no session
+fn current_user(req: &HttpRequest) -> Option<AuthenticatedUser> {
+    let state = req.app_data::<web::Data<AuthState>>()?;
+    let token = request_token(req)?;
+    let mut sessions = state.sessions.lock().expect("Session store poisoned");
+    let session = sessions.get(&token)?;
+    if session.expires_at <= Instant::now() {
+        sessions.remove(&token);
+        return None;
+    }
+    Some(AuthenticatedUser {
+        username: session.username.clone(),
+    })
+}
+
+pub async fn signup(state: web::Data<AuthState>, credentials: web::Json<Credentials>) -> HttpResponse {
+    let username = credentials.username.trim().to_string();
+    if username.is_empty() || credentials.password.len() < MIN_PASSWORD_LENGTH {
+        return HttpResponse::BadRequest().json(json!({
+            "error": format!("username is required and passwords need at least {} characters", MIN_PASSWORD_LENGTH)
+        }));
+    }
+    let Ok(hash) = hash_password(&credentials.password) else {
+        return HttpResponse::InternalServerError().json(json!({"error": "could not hash the password"}));
+    };
+    let mut users = state.users.lock().expect("User store poisoned");
+    if users.contains_key(&username) {
+        return HttpResponse::Conflict().json(json!({"error": "username is already taken"}));
+    }
+    users.insert(username.clone(), hash);
+    HttpResponse::Created().json(json!({"username": username}))
+}
+
+pub async fn login(state: web::Data<AuthState>, credentials: web::Json<Credentials>) -> HttpResponse {
+    let username = credentials.username.trim().to_string();
+    let hash = state
+        .users
+        .lock()
+        .expect("User store poisoned")
+        .get(&username)
+        .cloned();
+    let is_valid = hash.is_some_and(|hash| verify_password(&credentials.password, &hash));
+    if !is_valid {
+        return HttpResponse::Unauthorized().json(json!({"error": "invalid username or password"}));
+    }
+
+    let token = new_session_token();
+    let now = Instant::now();
+    let mut sessions = state.sessions.lock().expect("Session store poisoned");
+    sessions.retain(|_, session| session.expires_at > now);
+    sessions.insert(
+        token.clone(),
+        Session {
+            username: username.clone(),
+            expires_at: now + Duration::from_secs(SESSION_LIFETIME_SECS),
+        },
+    );
+    drop(sessions);
+    let cookie = Cookie::build(SESSION_COOKIE, token.clone())
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .max_age(time::Duration::seconds(SESSION_LIFETIME_SECS as i64))
+        .finish();
+    HttpResponse::Ok().cookie(cookie).json(json!({
+        "token": token,
+        "username": username,
+        "expires_in": SESSION_LIFETIME_SECS,
+    }))
+}
+
+pub async fn logout(req: HttpRequest, state: web::Data<AuthState>) -> HttpResponse {
+    let removed = request_token(&req).and_then(|token| {
+        state
+            .sessions
+            .lock()
+            .expect("Session store poisoned")
+            .remove(&token)
+    });
+    let was_live = removed.is_some_and(|session| session.expires_at > Instant::now());
+    if !was_live {
+        return HttpResponse::Unauthorized().json(json!({"error": "not logged in"}));
+    }
+    let mut cookie = Cookie::build(SESSION_COOKIE, "").path("/").finish();
+    cookie.make_removal();
+    HttpResponse::Ok()
+        .cookie(cookie)
+        .json(json!({"message": "logged out"}))
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(current_user(req).ok_or_else(|| ErrorUnauthorized("login required")))
+    }
+}
+
+// Middleware answering 401 on PROTECTED_ROUTES unless the request has a session: App::new().wrap(auth::RequireLogin)
+pub struct RequireLogin;
+
+impl<S, B> Transform<S, ServiceRequest> for RequireLogin
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RequireLoginMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let protected = PROTECTED_ROUTES
+            .iter()
+            .map(|path| ResourceDef::new(*path))
+            .collect();
+        ready(Ok(RequireLoginMiddleware { service, protected }))
+    }
+}
+
+pub struct RequireLoginMiddleware<S> {
+    service: S,
+    protected: Vec<ResourceDef>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireLoginMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let is_protected = self.protected.iter().any(|path| path.is_match(req.path()));
+        if is_protected && current_user(req.request()).is_none() {
+            let response = HttpResponse::Unauthorized().json(json!({"error": "login required"}));
+            return Box::pin(ready(Ok(req.into_response(response).map_into_right_body())));
+        }
+        let response = self.service.call(req);
+        Box::pin(async move { response.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
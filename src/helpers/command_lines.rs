@@ -9,6 +9,7 @@ use crossterm::{
 pub enum PrintCommand {
     AICall,
     UnitTest,
+    Quality,
     Issue,
 }
 
@@ -19,6 +20,7 @@ impl PrintCommand {
         let statement_color = match self {
             Self::AICall => Color::Cyan,
             Self::UnitTest => Color::Magenta,
+            Self::Quality => Color::Yellow,
             Self::Issue => Color::Red,
         };
 
@@ -37,7 +39,7 @@ pub fn get_user_response(question: &str) -> String {
 
     stdout.execute(SetForegroundColor(Color::Blue)).unwrap();
 
-    println!("");
+    println!();
     println!("{}", question);
 
     stdout.execute(ResetColor).unwrap();
@@ -56,7 +58,7 @@ pub fn confirm_safe_code() -> bool {
     let mut stdout: std::io::Stdout = stdout();
     loop {
         stdout.execute(SetForegroundColor(Color::Blue)).unwrap();
-        println!("");
+        println!();
         print!("WARNING: You are about to run code written entirely by AI.");
         println!("Review your code and confirm you wish to continue.");
 
@@ -68,13 +70,15 @@ pub fn confirm_safe_code() -> bool {
 
         stdout.execute(ResetColor).unwrap();
         let mut response = String::new();
-        stdin().read_line(&mut response).expect("Failed to read response");
+        stdin()
+            .read_line(&mut response)
+            .expect("Failed to read response");
 
         let response = response.trim().to_lowercase();
 
         match response.as_str() {
             "1" | "ok" | "y" => return true,
-            "2" | "no"| "n" => return false,
+            "2" | "no" | "n" => return false,
             _ => {
                 println!("Invalid input!");
             }
@@ -91,5 +95,3 @@ mod test {
         PrintCommand::AICall.print_agent_message("Managing agent", "Testing testing, process");
     }
 }
-
-
@@ -0,0 +1,270 @@
+use std::{collections::BTreeMap, time::Instant};
+
+use reqwest::{
+    header::{HeaderMap, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE, LOCATION},
+    Client, Response, Url,
+};
+use serde_json::Value;
+
+use crate::{
+    models::{ExternalApiProfile, ExternalApiSample},
+    testing::shape::infer_shape,
+};
+
+const MAX_REDIRECTS: usize = 5;
+
+// Keep a payload small enough for a prompt: a few array items and short strings
+pub fn trim_payload(value: &Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().take(3).map(trim_payload).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, field)| (key.clone(), trim_payload(field)))
+                .collect(),
+        ),
+        Value::String(text) if text.chars().count() > 200 => {
+            Value::String(format!("{}...", text.chars().take(200).collect::<String>()))
+        }
+        _ => value.clone(),
+    }
+}
+
+fn is_rate_limit_header(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("ratelimit") || name.contains("rate-limit") || name == "retry-after"
+}
+
+fn header_value(headers: &HeaderMap, name: impl reqwest::header::AsHeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+// Follow redirects by hand so every hop is recorded
+async fn send_following_redirects(
+    client: &Client,
+    url: &str,
+    redirects: &mut Vec<String>,
+) -> Result<Response, String> {
+    let mut current = Url::parse(url).map_err(|e| format!("invalid url: {}", e))?;
+    loop {
+        let response = client
+            .get(current.clone())
+            .send()
+            .await
+            .map_err(|e| match e {
+                e if e.is_timeout() => String::from("request timed out"),
+                e if e.is_connect() => format!("connection failed: {}", e),
+                e => format!("request failed: {}", e),
+            })?;
+        let location = header_value(response.headers(), LOCATION);
+        match location {
+            Some(location) if response.status().is_redirection() => {
+                if redirects.len() == MAX_REDIRECTS {
+                    return Err(format!("more than {} redirects", MAX_REDIRECTS));
+                }
+                current = current
+                    .join(&location)
+                    .map_err(|e| format!("invalid redirect location: {}", e))?;
+                redirects.push(current.to_string());
+            }
+            _ => return Ok(response),
+        }
+    }
+}
+
+// Profile of one external url, along with its full response for the mock server
+pub async fn profile_external_url(
+    client: &Client,
+    url: &str,
+) -> (ExternalApiProfile, Option<ExternalApiSample>) {
+    let started_at = Instant::now();
+    let mut redirects: Vec<String> = vec![];
+    let mut profile = ExternalApiProfile {
+        url: url.to_string(),
+        status: None,
+        latency_ms: 0,
+        content_type: None,
+        redirects: vec![],
+        rate_limit_headers: BTreeMap::new(),
+        cors_allow_origin: None,
+        sample_payload: None,
+        payload_shape: None,
+        error: None,
+    };
+
+    let response = send_following_redirects(client, url, &mut redirects).await;
+    profile.redirects = redirects;
+    let response = match response {
+        Ok(response) => response,
+        Err(error) => {
+            profile.latency_ms = started_at.elapsed().as_millis() as u64;
+            profile.error = Some(error);
+            return (profile, None);
+        }
+    };
+
+    let status = response.status().as_u16();
+    let headers = response.headers().clone();
+    profile.status = Some(status);
+    profile.content_type = header_value(&headers, CONTENT_TYPE);
+    profile.cors_allow_origin = header_value(&headers, ACCESS_CONTROL_ALLOW_ORIGIN);
+    profile.rate_limit_headers = headers
+        .iter()
+        .filter(|(name, _)| is_rate_limit_header(name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => {
+            profile.latency_ms = started_at.elapsed().as_millis() as u64;
+            profile.error = Some(format!("failed to read body: {}", e));
+            return (profile, None);
+        }
+    };
+    profile.latency_ms = started_at.elapsed().as_millis() as u64;
+    if let Ok(payload) = serde_json::from_str::<Value>(&body) {
+        profile.payload_shape = Some(infer_shape(&payload));
+        profile.sample_payload = Some(trim_payload(&payload));
+    }
+
+    let sample = ExternalApiSample {
+        url: url.to_string(),
+        status,
+        content_type: profile.content_type.clone(),
+        body: body.chars().take(100_000).collect(),
+    };
+    (profile, Some(sample))
+}
+
+// Profile every url at once; results keep the order of urls
+pub async fn profile_external_urls(
+    client: &Client,
+    urls: &[String],
+) -> Vec<(ExternalApiProfile, Option<ExternalApiSample>)> {
+    let tasks: Vec<_> = urls
+        .iter()
+        .map(|url| {
+            let client = client.clone();
+            let url = url.clone();
+            tokio::spawn(async move { profile_external_url(&client, &url).await })
+        })
+        .collect();
+
+    let mut profiles = vec![];
+    for task in tasks {
+        profiles.push(task.await.expect("External url profiling task panicked"));
+    }
+    profiles
+}
+
+// Profiles of the working urls, as passed to the backend prompts
+pub fn describe_for_prompt(profiles: &[ExternalApiProfile]) -> String {
+    let working: Vec<&ExternalApiProfile> = profiles
+        .iter()
+        .filter(|profile| profile.status == Some(200))
+        .collect();
+    serde_json::to_string(&working).unwrap_or_default()
+}
+
+// One line per url shown to the user
+pub fn profile_summary(profile: &ExternalApiProfile) -> String {
+    match (&profile.error, profile.status) {
+        (Some(error), _) => format!(
+            "{} failed after {}ms: {}",
+            profile.url, profile.latency_ms, error
+        ),
+        (None, status) => {
+            let mut summary = format!(
+                "{} -> {} in {}ms ({})",
+                profile.url,
+                status.unwrap_or_default(),
+                profile.latency_ms,
+                profile.content_type.as_deref().unwrap_or("no content type")
+            );
+            if !profile.redirects.is_empty() {
+                summary = format!(
+                    "{}, redirected to {}",
+                    summary,
+                    profile.redirects.join(" -> ")
+                );
+            }
+            if !profile.rate_limit_headers.is_empty() {
+                let limits: Vec<String> = profile
+                    .rate_limit_headers
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect();
+                summary = format!("{}, rate limits {}", summary, limits.join(", "));
+            }
+            summary
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::test_support::{spawn_fake_server, FakeResponse};
+
+    #[test]
+    fn test_trim_payload() {
+        let payload = json!({"rates": [1, 2, 3, 4, 5], "note": "x".repeat(300)});
+        let trimmed = trim_payload(&payload);
+
+        assert_eq!(trimmed["rates"], json!([1, 2, 3]));
+        assert_eq!(trimmed["note"].as_str().unwrap().len(), 203);
+    }
+
+    #[tokio::test]
+    async fn test_profile_external_urls() {
+        let base_url = spawn_fake_server(|request| match request.path.as_str() {
+            "/old" => FakeResponse::new(301, "").with_header("location", "/rates"),
+            "/rates" => FakeResponse::json(200, json!({"base": "USD", "rates": [{"eur": 0.9}]}))
+                .with_header("x-ratelimit-remaining", "59")
+                .with_header("access-control-allow-origin", "*"),
+            _ => FakeResponse::new(500, "down"),
+        })
+        .await;
+        let client = Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap();
+        let urls = vec![
+            format!("{}/old", base_url),
+            format!("{}/broken", base_url),
+            String::from("http://127.0.0.1:1/unreachable"),
+        ];
+
+        let profiles = profile_external_urls(&client, &urls).await;
+
+        let (rates, sample) = &profiles[0];
+        assert_eq!(rates.status, Some(200));
+        assert_eq!(rates.redirects, vec![format!("{}/rates", base_url)]);
+        assert_eq!(rates.content_type.as_deref(), Some("application/json"));
+        assert_eq!(rates.rate_limit_headers["x-ratelimit-remaining"], "59");
+        assert_eq!(rates.cors_allow_origin.as_deref(), Some("*"));
+        assert_eq!(
+            rates.payload_shape,
+            Some(json!({"base": "string", "rates": [{"eur": "number"}]}))
+        );
+        assert_eq!(sample.as_ref().unwrap().url, urls[0]);
+
+        assert_eq!(profiles[1].0.status, Some(500));
+        assert!(profiles[1].0.payload_shape.is_none());
+
+        let unreachable = &profiles[2].0;
+        assert!(unreachable.status.is_none());
+        assert!(profile_summary(unreachable).contains("connection failed"));
+
+        let profiles: Vec<ExternalApiProfile> = profiles.into_iter().map(|(p, _)| p).collect();
+        let prompt: Vec<Value> = serde_json::from_str(&describe_for_prompt(&profiles)).unwrap();
+        assert_eq!(prompt.len(), 1);
+        assert_eq!(prompt[0]["sample_payload"]["base"], "USD");
+    }
+}
@@ -1,10 +1,18 @@
-use std::{env, error::Error, fs};
+use std::{
+    collections::BTreeMap,
+    env,
+    error::Error,
+    fs, io,
+    path::PathBuf,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use dotenv::dotenv;
-use reqwest::Client;
 use serde::de::DeserializeOwned;
+use serde_json::Value;
 
-use crate::{apis::call_gpt, models::Message};
+use crate::{apis::call_gpt, codegen::openapi::render_openapi, models::Message};
 
 use super::PrintCommand;
 
@@ -70,18 +78,83 @@ pub async fn ai_task_request_decoded<T: DeserializeOwned>(
     decoded_response
 }
 
-// Check whether request url is valid
-pub async fn check_status_code(client: &Client, url: &str) -> Result<u16, reqwest::Error> {
-    let response = client.get(url).send().await?;
-    Ok(response.status().as_u16())
+// Reply without the ``` fence models sometimes wrap JSON in
+pub fn strip_code_fence(response: &str) -> &str {
+    let response = response.trim();
+    response
+        .strip_prefix("```json")
+        .or_else(|| response.strip_prefix("```"))
+        .and_then(|fenced| fenced.trim_end().strip_suffix("```"))
+        .map(str::trim)
+        .unwrap_or(response)
+}
+
+// Read optional setting, falling back to a default
+pub fn read_env_or<T: FromStr>(key: &str, default: T) -> T {
+    dotenv().ok();
+    env::var(key)
+        .ok()
+        .and_then(|value| value.trim().parse::<T>().ok())
+        .unwrap_or(default)
+}
+
+// Get Web Server Project Path
+pub fn read_web_server_project_path() -> String {
+    dotenv().ok();
+    env::var("WEB_SERVER_PROJECT_ABSOLUTE_PATH")
+        .expect("WEB_SERVER_PROJECT_ABSOLUTE_PATH Key not found")
+}
+
+// Create the log file path for this run's web server output
+pub fn create_server_log_path() -> PathBuf {
+    let default_log_dir = PathBuf::from(read_web_server_project_path()).join("logs");
+    let log_dir: PathBuf = read_env_or("SERVER_LOG_DIR", default_log_dir);
+    fs::create_dir_all(&log_dir).expect("Failed to create server log directory");
+
+    let started_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    log_dir.join(format!("server-{}.log", started_at))
+}
+
+// File that keeps the fuzz inputs which broke the web server
+pub fn read_fuzz_regression_path() -> PathBuf {
+    let default_path =
+        PathBuf::from(read_web_server_project_path()).join("benjamin-fuzz-regressions.json");
+    read_env_or("FUZZ_REGRESSION_PATH", default_path)
+}
+
+// Fixed seed from FUZZ_SEED, or a fresh one for every run
+pub fn read_fuzz_seed() -> u64 {
+    let fresh_seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(1);
+    read_env_or("FUZZ_SEED", fresh_seed)
+}
+
+// Split comma separated lint names
+pub fn parse_lint_list(lints: &str) -> Vec<String> {
+    lints
+        .split(',')
+        .map(|lint| lint.trim().to_string())
+        .filter(|lint| !lint.is_empty())
+        .collect()
+}
+
+// Get lints treated as errors by the quality stage
+pub fn read_clippy_deny_lints() -> Vec<String> {
+    let lints: String = read_env_or("CLIPPY_DENY_LINTS", String::from("warnings"));
+    parse_lint_list(&lints)
 }
 
 // Get Code Template
 pub fn read_code_template_contents() -> String {
     dotenv().ok();
-    let code_template_absolute_path =
-        env::var("CODE_EXECUTE_TEMPLATE_ABSOLUTE_PATH").expect("CODE_EXECUTE_TEMPLATE_ABSOLUTE_PATH Key not found");
-    let path: String = String::from(code_template_absolute_path);
+    let code_template_absolute_path = env::var("CODE_EXECUTE_TEMPLATE_ABSOLUTE_PATH")
+        .expect("CODE_EXECUTE_TEMPLATE_ABSOLUTE_PATH Key not found");
+    let path: String = code_template_absolute_path;
     fs::read_to_string(path).expect("Failed to read code template")
 }
 
@@ -90,16 +163,24 @@ pub fn read_exec_main_contents() -> String {
     dotenv().ok();
     let execute_main_absolute_path =
         env::var("EXEC_MAIN_ABSOLUTE_PATH").expect("EXEC_MAIN_ABSOLUTE_PATH Key not found");
-    let path: String = String::from(execute_main_absolute_path);
+    let path: String = execute_main_absolute_path;
     fs::read_to_string(path).expect("Failed to read code template")
 }
 
+// Get path of the main.rs file being executed
+pub fn read_exec_main_path() -> PathBuf {
+    dotenv().ok();
+    let execute_main_absolute_path =
+        env::var("EXEC_MAIN_ABSOLUTE_PATH").expect("EXEC_MAIN_ABSOLUTE_PATH Key not found");
+    PathBuf::from(execute_main_absolute_path)
+}
+
 // Save new backend codes
 pub fn save_backend_code(contents: &str) {
     dotenv().ok();
     let execute_main_absolute_path =
         env::var("EXEC_MAIN_ABSOLUTE_PATH").expect("EXEC_MAIN_ABSOLUTE_PATH Key not found");
-    let path: String = String::from(execute_main_absolute_path);
+    let path: String = execute_main_absolute_path;
     fs::write(path, contents).expect("Failed to write main.rs file")
 }
 
@@ -108,10 +189,119 @@ pub fn save_api_endpoints(api_endpoints: &str) {
     dotenv().ok();
     let api_schema_absolute_path =
         env::var("API_SCHEMA_ABSOLUTE_PATH").expect("API_SCHEMA_ABSOLUTE_PATH Key not found");
-    let path: String = String::from(api_schema_absolute_path);
+    let path: String = api_schema_absolute_path;
     fs::write(path, api_endpoints).expect("Failed to write api endpoints to file")
 }
 
+// Directory the SQL migrations are written to, one subdirectory per database
+pub fn read_migrations_dir() -> PathBuf {
+    let default_dir = PathBuf::from(read_web_server_project_path()).join("migrations");
+    read_env_or("MIGRATIONS_DIR", default_dir)
+}
+
+// Save a migration as {dir}/{database}/0001_create_tables.sql
+pub fn save_migration(database: &str, contents: &str) -> io::Result<PathBuf> {
+    let dir = read_migrations_dir().join(database);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("0001_create_tables.sql");
+    fs::write(&path, contents)?;
+    Ok(path)
+}
+
+// Data-access module, next to main.rs unless DATA_ACCESS_PATH says otherwise
+pub fn read_data_access_path() -> PathBuf {
+    let default_path = read_exec_main_path().with_file_name("db.rs");
+    read_env_or("DATA_ACCESS_PATH", default_path)
+}
+
+// Auth module, next to main.rs unless AUTH_MODULE_PATH says otherwise
+pub fn read_auth_module_path() -> PathBuf {
+    let default_path = read_exec_main_path().with_file_name("auth.rs");
+    read_env_or("AUTH_MODULE_PATH", default_path)
+}
+
+// Add a dependency to the web server's Cargo.toml, unless it already has one by that name
+pub fn add_web_server_dependency(name: &str, spec: &str) -> io::Result<bool> {
+    let project_path = PathBuf::from(read_web_server_project_path());
+    let Some(manifest_path) = project_path
+        .ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|path| path.exists())
+    else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no Cargo.toml in or above the web server project",
+        ));
+    };
+
+    let manifest = fs::read_to_string(&manifest_path)?;
+    let declared = manifest.lines().any(|line| {
+        line.split_once('=')
+            .is_some_and(|(key, _)| key.trim() == name)
+    });
+    if declared {
+        return Ok(false);
+    }
+    let dependency = format!("{} = {}", name, spec);
+    let mut lines: Vec<&str> = manifest.lines().collect();
+    match lines
+        .iter()
+        .position(|line| line.trim() == "[dependencies]")
+    {
+        Some(index) => lines.insert(index + 1, &dependency),
+        None => lines.extend(["", "[dependencies]", &dependency]),
+    }
+    let updated = format!("{}\n", lines.join("\n"));
+    fs::write(&manifest_path, updated)?;
+    Ok(true)
+}
+
+// Directory the generated frontend is written to and served from
+pub fn read_frontend_dir() -> PathBuf {
+    let default_dir = PathBuf::from(read_web_server_project_path()).join("frontend");
+    read_env_or("FRONTEND_DIR", default_dir)
+}
+
+// Write the frontend files, overwriting those of an earlier attempt
+pub fn save_frontend_files(files: &BTreeMap<String, String>) -> io::Result<PathBuf> {
+    let dir = read_frontend_dir();
+    fs::create_dir_all(&dir)?;
+    for (name, contents) in files {
+        fs::write(dir.join(name), contents)?;
+    }
+    Ok(dir)
+}
+
+// Directory for the request collections QA uses by hand
+pub fn read_collections_dir() -> PathBuf {
+    let default_dir = PathBuf::from(read_web_server_project_path()).join("collections");
+    read_env_or("COLLECTIONS_DIR", default_dir)
+}
+
+// Directory for the generated client libraries
+pub fn read_client_sdk_dir() -> PathBuf {
+    let default_dir = PathBuf::from(read_web_server_project_path()).join("clients");
+    read_env_or("CLIENT_SDK_DIR", default_dir)
+}
+
+// OpenAPI contract the generated server has to implement, if one was given
+pub fn read_api_contract_path() -> Option<PathBuf> {
+    let path: String = read_env_or("OPENAPI_CONTRACT_PATH", String::new());
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+// OpenAPI document next to the project, in YAML unless the path ends in .json
+pub fn read_openapi_path() -> PathBuf {
+    let default_path = PathBuf::from(read_web_server_project_path()).join("openapi.yaml");
+    read_env_or("OPENAPI_PATH", default_path)
+}
+
+// Save OpenAPI document of the extracted routes
+pub fn save_openapi_document(document: &Value) {
+    let path = read_openapi_path();
+    fs::write(&path, render_openapi(document, &path)).expect("Failed to write OpenAPI document")
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -126,6 +316,24 @@ mod test {
         assert_eq!(msg.role, "system".to_string());
     }
 
+    #[test]
+    fn test_strip_code_fence() {
+        assert_eq!(strip_code_fence("```json\n{\"a\": 1}\n```\n"), "{\"a\": 1}");
+        assert_eq!(strip_code_fence("```\n[]\n```"), "[]");
+        assert_eq!(strip_code_fence(" {} "), "{}");
+    }
+
+    #[test]
+    fn test_parse_lint_list() {
+        let lints = parse_lint_list(" warnings, clippy::unwrap_used ,,clippy::pedantic ");
+
+        assert_eq!(
+            lints,
+            vec!["warnings", "clippy::unwrap_used", "clippy::pedantic"]
+        );
+        assert!(parse_lint_list("").is_empty());
+    }
+
     #[tokio::test]
     async fn test_ai_task_request() {
         let response = ai_task_request(
@@ -1,4 +1,7 @@
 mod command_lines;
+pub mod external_api;
 pub mod general;
+pub mod network;
+pub mod process;
 
-pub use command_lines::{get_user_response, PrintCommand, confirm_safe_code};
+pub use command_lines::{confirm_safe_code, get_user_response, PrintCommand};
@@ -0,0 +1,122 @@
+use std::{
+    io,
+    net::{IpAddr, TcpListener},
+};
+
+use super::general::read_env_or;
+
+// Ask the OS for a port nobody is listening on
+pub fn allocate_free_port(host: &str) -> io::Result<u16> {
+    let listener = TcpListener::bind((host, 0))?;
+    Ok(listener.local_addr()?.port())
+}
+
+// Host and port the generated server is told to bind to
+pub fn read_server_address() -> io::Result<(String, u16)> {
+    let host: String = read_env_or("SERVER_HOST", String::from("127.0.0.1"));
+    let port: u16 = match read_env_or::<u16>("SERVER_PORT", 0) {
+        0 => allocate_free_port(&host)?,
+        port => port,
+    };
+    Ok((host, port))
+}
+
+// Find the address passed to HttpServer::bind in generated code
+pub fn find_bind_address(code: &str) -> Option<(String, u16)> {
+    let bind_start = code.find(".bind(")? + ".bind(".len();
+    let bind_args = &code[bind_start..];
+    let bind_args = &bind_args[..bind_args.find(')')?];
+
+    // Either ("127.0.0.1", 8080) or "127.0.0.1:8080"
+    let cleaned: String = bind_args
+        .chars()
+        .filter(|c| !matches!(c, '(' | '"' | ' ' | '\n' | '\r' | '\t'))
+        .collect();
+    let (host, port) = cleaned.rsplit_once([',', ':'])?;
+    let port: u16 = port.parse().ok()?;
+
+    if host != "localhost" && host.parse::<IpAddr>().is_err() {
+        return None;
+    }
+    Some((host.to_string(), port))
+}
+
+// Whether the generated code reads its port from the PORT env var
+pub fn code_reads_port_env(code: &str) -> bool {
+    code.contains("\"PORT\"")
+}
+
+// Base url used to reach the generated server
+pub fn resolve_base_url(code: &str, host: &str, port: u16) -> String {
+    let (host, port) = if code_reads_port_env(code) {
+        (host.to_string(), port)
+    } else {
+        find_bind_address(code).unwrap_or((host.to_string(), port))
+    };
+    let host = if host == "0.0.0.0" {
+        String::from("127.0.0.1")
+    } else {
+        host
+    };
+    format!("http://{}:{}", host, port)
+}
+
+// Address the server answers on when started by hand, for the request collections.
+// Without SERVER_PORT that is the prompt's fallback port, not this run's free port
+pub fn default_base_url(code: &str) -> String {
+    let host: String = read_env_or("SERVER_HOST", String::from("127.0.0.1"));
+    let port: u16 = match read_env_or::<u16>("SERVER_PORT", 0) {
+        0 => 8080,
+        port => port,
+    };
+    resolve_base_url(code, &host, port)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_allocate_free_port() {
+        let port = allocate_free_port("127.0.0.1").expect("Failed to allocate port");
+        assert!(port > 0);
+        assert!(TcpListener::bind(("127.0.0.1", port)).is_ok());
+    }
+
+    #[test]
+    fn test_find_bind_address() {
+        let tuple_code = "HttpServer::new(app)\n    .bind((\"0.0.0.0\", 3000))?\n    .run()";
+        assert_eq!(
+            find_bind_address(tuple_code),
+            Some((String::from("0.0.0.0"), 3000))
+        );
+
+        let str_code = "HttpServer::new(app).bind(\"127.0.0.1:8081\")?.run()";
+        assert_eq!(
+            find_bind_address(str_code),
+            Some((String::from("127.0.0.1"), 8081))
+        );
+
+        let env_code = "HttpServer::new(app).bind((host, port))?.run()";
+        assert_eq!(find_bind_address(env_code), None);
+    }
+
+    #[test]
+    fn test_resolve_base_url() {
+        let env_code =
+            "let port = env::var(\"PORT\"); HttpServer::new(app).bind((\"127.0.0.1\", port))";
+        assert_eq!(
+            resolve_base_url(env_code, "127.0.0.1", 43210),
+            "http://127.0.0.1:43210"
+        );
+
+        let fixed_code = "HttpServer::new(app).bind((\"0.0.0.0\", 8080))";
+        assert_eq!(
+            resolve_base_url(fixed_code, "127.0.0.1", 43210),
+            "http://127.0.0.1:8080"
+        );
+
+        let fixed_code = "HttpServer::new(app).bind((\"0.0.0.0\", 3000))";
+        assert_eq!(default_base_url(fixed_code), "http://127.0.0.1:3000");
+    }
+}
@@ -0,0 +1,522 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    panic,
+    path::Path,
+    process::{ExitStatus, Output, Stdio},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::Client;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::{Child, Command},
+    sync::watch,
+    task::JoinHandle,
+    time::{sleep, sleep_until, timeout, Instant},
+};
+
+// Process groups spawned by the agents that are still alive
+static RUNNING_GROUPS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+fn register_group(group_id: u32) {
+    RUNNING_GROUPS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(group_id);
+}
+
+fn unregister_group(group_id: u32) {
+    RUNNING_GROUPS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|&id| id != group_id);
+}
+
+#[cfg(unix)]
+fn kill_process_group(group_id: u32) {
+    // Children are spawned as group leaders, so the group id is the leader pid
+    unsafe {
+        libc::killpg(group_id as libc::pid_t, libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_group_id: u32) {}
+
+// Kill every process group that is still registered
+pub fn kill_running_groups() {
+    let groups: Vec<u32> = RUNNING_GROUPS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .drain(..)
+        .collect();
+    for group_id in groups {
+        kill_process_group(group_id);
+    }
+}
+
+// Make sure no child process outlives Benjamin on Ctrl-C or panic
+pub fn install_cleanup_handlers() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        kill_running_groups();
+        default_hook(info);
+    }));
+
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            kill_running_groups();
+            std::process::exit(130);
+        }
+    });
+}
+
+fn new_command(program: &str, args: &[String], current_dir: &str) -> Command {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .current_dir(current_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    #[cfg(unix)]
+    command.process_group(0);
+
+    command
+}
+
+// Run a command to completion, killing its whole process tree on timeout
+pub async fn run_command_with_timeout(
+    program: &str,
+    args: &[String],
+    current_dir: &str,
+    time_limit: Duration,
+) -> io::Result<Output> {
+    let child = new_command(program, args, current_dir).spawn()?;
+    let group_id = child.id();
+    if let Some(group_id) = group_id {
+        register_group(group_id);
+    }
+
+    let result = timeout(time_limit, child.wait_with_output()).await;
+
+    if let Some(group_id) = group_id {
+        if result.is_err() {
+            kill_process_group(group_id);
+        }
+        unregister_group(group_id);
+    }
+
+    match result {
+        Ok(output) => output,
+        Err(_) => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!(
+                "'{} {}' timed out after {} seconds",
+                program,
+                args.join(" "),
+                time_limit.as_secs()
+            ),
+        )),
+    }
+}
+
+// Outcome of waiting for a server to answer requests
+#[derive(Debug, PartialEq)]
+pub enum Readiness {
+    Ready,
+    Exited(ExitStatus),
+    TimedOut,
+}
+
+// Lines printed by a server process, in the order they were read
+#[derive(Debug, Clone, Default)]
+pub struct ServerLog {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl ServerLog {
+    fn push(&self, line: String) {
+        self.lines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(line);
+    }
+
+    // Position to pass to lines_since later on
+    pub fn cursor(&self) -> usize {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn lines_since(&self, cursor: usize) -> Vec<String> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        lines.iter().skip(cursor).cloned().collect()
+    }
+
+    pub fn contents(&self) -> String {
+        self.lines_since(0).join("\n")
+    }
+}
+
+// Read a child stream line by line into the log and the log file
+fn capture_stream<R>(
+    reader: R,
+    stream_name: &'static str,
+    log: ServerLog,
+    log_file: Arc<Mutex<File>>,
+) -> JoinHandle<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let line = format!("[{}] {}", stream_name, line);
+            if let Ok(mut file) = log_file.lock() {
+                let _ = writeln!(file, "{}", line);
+            }
+            log.push(line);
+        }
+    })
+}
+
+// Long running child process, such as the generated web server
+#[derive(Debug)]
+pub struct ServerProcess {
+    child: Option<Child>,
+    group_id: Option<u32>,
+    log: ServerLog,
+    readers: Vec<JoinHandle<()>>,
+    // Deadline of the watchdog, which stops once this is dropped
+    watchdog: watch::Sender<Instant>,
+}
+
+impl ServerProcess {
+    // Spawn the process, appending its output to log_path and killing its
+    // process tree once max_lifetime has passed
+    pub fn spawn(
+        program: &str,
+        args: &[String],
+        current_dir: &str,
+        envs: &[(&str, String)],
+        log_path: &Path,
+        max_lifetime: Duration,
+    ) -> io::Result<Self> {
+        let mut log_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_path)?;
+        writeln!(log_file, "=== {} {} ===", program, args.join(" "))?;
+        let log_file = Arc::new(Mutex::new(log_file));
+
+        let mut command = new_command(program, args, current_dir);
+        for (key, value) in envs {
+            command.env(key, value);
+        }
+        let mut child = command.spawn()?;
+        let group_id = child.id();
+
+        let log = ServerLog::default();
+        let mut readers = vec![];
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(capture_stream(
+                stdout,
+                "stdout",
+                log.clone(),
+                log_file.clone(),
+            ));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(capture_stream(stderr, "stderr", log.clone(), log_file));
+        }
+
+        let (watchdog, mut deadline) = watch::channel(Instant::now() + max_lifetime);
+
+        if let Some(group_id) = group_id {
+            register_group(group_id);
+            tokio::spawn(async move {
+                loop {
+                    let current = *deadline.borrow_and_update();
+                    tokio::select! {
+                        _ = sleep_until(current) => {
+                            kill_process_group(group_id);
+                            return;
+                        }
+                        changed = deadline.changed() => {
+                            if changed.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+            });
+        }
+
+        Ok(Self {
+            child: Some(child),
+            group_id,
+            log,
+            readers,
+            watchdog,
+        })
+    }
+
+    // Push the watchdog deadline back, for stages that need the server longer
+    pub fn extend_lifetime(&self, extra: Duration) {
+        self.watchdog.send_modify(|deadline| *deadline += extra);
+    }
+
+    // Everything the process printed so far
+    pub fn log(&self) -> &ServerLog {
+        &self.log
+    }
+
+    // Exit status if the process has already stopped
+    pub fn has_exited(&mut self) -> io::Result<Option<ExitStatus>> {
+        match self.child.as_mut() {
+            Some(child) => child.try_wait(),
+            None => Ok(None),
+        }
+    }
+
+    // Poll probe_url until the server answers with any status, or stops
+    pub async fn wait_until_ready(
+        &mut self,
+        client: &Client,
+        probe_url: &str,
+        time_limit: Duration,
+    ) -> io::Result<Readiness> {
+        let deadline = Instant::now() + time_limit;
+        loop {
+            if let Some(exit_status) = self.has_exited()? {
+                return Ok(Readiness::Exited(exit_status));
+            }
+            if client.get(probe_url).send().await.is_ok() {
+                return Ok(Readiness::Ready);
+            }
+            if Instant::now() >= deadline {
+                return Ok(Readiness::TimedOut);
+            }
+            sleep(Duration::from_millis(250)).await;
+        }
+    }
+
+    // Kill the whole process tree and wait until its output is fully logged
+    pub async fn shutdown(mut self) -> io::Result<ExitStatus> {
+        if let Some(group_id) = self.group_id.take() {
+            kill_process_group(group_id);
+            unregister_group(group_id);
+        }
+        let mut child = self.child.take().expect("Server process already shut down");
+        // Covers platforms without process groups
+        let _ = child.start_kill();
+        let exit_status = child.wait().await?;
+
+        for reader in self.readers.drain(..) {
+            let _ = timeout(Duration::from_secs(2), reader).await;
+        }
+        Ok(exit_status)
+    }
+}
+
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        if let Some(group_id) = self.group_id.take() {
+            kill_process_group(group_id);
+            unregister_group(group_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_log_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("benjamin-process-test.log")
+    }
+
+    #[tokio::test]
+    async fn test_run_command_with_timeout() {
+        let output = run_command_with_timeout(
+            "sh",
+            &[String::from("-c"), String::from("echo done")],
+            ".",
+            Duration::from_secs(5),
+        )
+        .await
+        .expect("Failed to run command");
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "done");
+
+        let timed_out = run_command_with_timeout(
+            "sh",
+            &[String::from("-c"), String::from("sleep 30")],
+            ".",
+            Duration::from_millis(200),
+        )
+        .await;
+        assert_eq!(timed_out.unwrap_err().kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn test_server_process_lifecycle() {
+        let mut server = ServerProcess::spawn(
+            "sh",
+            &[String::from("-c"), String::from("sleep 30")],
+            ".",
+            &[],
+            &test_log_path(),
+            Duration::from_secs(60),
+        )
+        .expect("Failed to spawn process");
+        assert!(server.has_exited().unwrap().is_none());
+
+        let exit_status = server
+            .shutdown()
+            .await
+            .expect("Failed to shut down process");
+        assert!(!exit_status.success());
+
+        let mut crashed = ServerProcess::spawn(
+            "sh",
+            &[String::from("-c"), String::from("exit 3")],
+            ".",
+            &[],
+            &test_log_path(),
+            Duration::from_secs(60),
+        )
+        .expect("Failed to spawn process");
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(
+            crashed.has_exited().unwrap().and_then(|s| s.code()),
+            Some(3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extend_lifetime() {
+        let mut extended = ServerProcess::spawn(
+            "sh",
+            &[String::from("-c"), String::from("sleep 30")],
+            ".",
+            &[],
+            &test_log_path(),
+            Duration::from_millis(300),
+        )
+        .expect("Failed to spawn process");
+        extended.extend_lifetime(Duration::from_secs(30));
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(extended.has_exited().unwrap().is_none());
+        extended.shutdown().await.unwrap();
+
+        let mut expired = ServerProcess::spawn(
+            "sh",
+            &[String::from("-c"), String::from("sleep 30")],
+            ".",
+            &[],
+            &test_log_path(),
+            Duration::from_millis(300),
+        )
+        .expect("Failed to spawn process");
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(expired.has_exited().unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_ready() {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(1))
+            .build()
+            .unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let probe_url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = [0u8; 1024];
+                let _ = stream.read(&mut buffer).await;
+                let _ = stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\n\r\n")
+                    .await;
+            }
+        });
+
+        let mut server = ServerProcess::spawn(
+            "sh",
+            &[String::from("-c"), String::from("sleep 30")],
+            ".",
+            &[],
+            &test_log_path(),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+        let readiness = server
+            .wait_until_ready(&client, &probe_url, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(readiness, Readiness::Ready);
+
+        let unused_url = "http://127.0.0.1:9/";
+        let readiness = server
+            .wait_until_ready(&client, unused_url, Duration::from_millis(300))
+            .await
+            .unwrap();
+        assert_eq!(readiness, Readiness::TimedOut);
+        server.shutdown().await.unwrap();
+
+        let mut crashed = ServerProcess::spawn(
+            "sh",
+            &[String::from("-c"), String::from("echo boom >&2; exit 1")],
+            ".",
+            &[],
+            &test_log_path(),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+        let readiness = crashed
+            .wait_until_ready(&client, unused_url, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(matches!(readiness, Readiness::Exited(_)));
+        let crashed_log = crashed.log().clone();
+        crashed.shutdown().await.unwrap();
+        assert_eq!(crashed_log.contents(), "[stderr] boom");
+    }
+
+    #[tokio::test]
+    async fn test_server_log_capture() {
+        let log_path = std::env::temp_dir().join("benjamin-process-log-test.log");
+        let _ = std::fs::remove_file(&log_path);
+
+        let server = ServerProcess::spawn(
+            "sh",
+            &[
+                String::from("-c"),
+                String::from("echo started; sleep 0.3; echo 'request failed' >&2; sleep 30"),
+            ],
+            ".",
+            &[],
+            &log_path,
+            Duration::from_secs(60),
+        )
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let cursor = server.log().cursor();
+        assert_eq!(server.log().lines_since(0), vec!["[stdout] started"]);
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(
+            server.log().lines_since(cursor),
+            vec!["[stderr] request failed"]
+        );
+
+        server.shutdown().await.unwrap();
+        let log_file = std::fs::read_to_string(&log_path).unwrap();
+        assert!(log_file.contains("[stdout] started\n[stderr] request failed"));
+    }
+}
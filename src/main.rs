@@ -6,20 +6,39 @@ macro_rules! get_function_string {
 }
 
 mod ai_functions;
+mod analysis;
 mod apis;
+mod codegen;
 mod helpers;
 mod models;
+mod testing;
 
-use helpers::get_user_response;
+use analysis::openapi_contract::load_api_contract;
+use helpers::{
+    general::read_api_contract_path, get_user_response, process::install_cleanup_handlers,
+};
 
 use crate::models::ManagingAgent;
 #[tokio::main]
 async fn main() {
-    let user_response = get_user_response("What website are we going to build today?");
+    install_cleanup_handlers();
 
-    let mut managing_agent = ManagingAgent::new(user_response)
+    let api_contract = read_api_contract_path().map(|path| {
+        load_api_contract(&path)
+            .unwrap_or_else(|e| panic!("Failed to read API contract {}: {}", path.display(), e))
+    });
+    let question = match &api_contract {
+        Some(_) => "What website are we going to build from the API contract? (press enter to build it as described)",
+        None => "What website are we going to build today?",
+    };
+    let user_response = get_user_response(question);
+
+    let mut managing_agent = ManagingAgent::new(user_response, api_contract)
         .await
         .expect("Error creating agent");
 
-    managing_agent.execute_project().await;
+    // The failure was already reported, only the exit status is left
+    if managing_agent.execute_project().await.is_err() {
+        std::process::exit(1);
+    }
 }
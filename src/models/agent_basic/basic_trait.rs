@@ -2,6 +2,7 @@ use crate::models::Message;
 
 use super::basic_agent::AgentState;
 
+#[allow(dead_code)]
 pub trait BasicTraits {
     fn new(objective: String, position: String) -> Self;
     fn update_state(&mut self, new_state: AgentState);
@@ -9,4 +10,4 @@ pub trait BasicTraits {
     fn get_position(&self) -> &String;
     fn get_state(&self) -> &AgentState;
     fn get_memory(&self) -> &Vec<Message>;
-}
\ No newline at end of file
+}
@@ -1,2 +1,2 @@
 pub mod basic_agent;
-pub mod basic_trait;
\ No newline at end of file
+pub mod basic_trait;
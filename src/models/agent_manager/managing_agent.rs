@@ -1,24 +1,35 @@
 use crate::{
     ai_functions::ai_func_managing::convert_user_input_to_goal,
-    helpers::general::ai_task_request,
+    analysis::openapi_contract::describe_contract,
+    helpers::{
+        general::{ai_task_request, read_env_or},
+        PrintCommand,
+    },
     models::{
         agent_architect::AgentSolutionArchitect,
-        agent_backend::AgentBackendDeveloper,
+        agent_auth::AgentAuthDeveloper,
+        agent_backend::{AgentBackendDeveloper, ContractError},
+        agent_database::AgentDatabaseDesigner,
+        agent_frontend::AgentFrontendDeveloper,
         agents::agent_traits::SpecialFunctions,
         basic_agent::{AgentState, BasicAgent},
-        FactSheet,
+        ApiContract, FactSheet,
     },
 };
 
 #[derive(Debug)]
 pub struct ManagingAgent {
-    _attributes: BasicAgent,
+    attributes: BasicAgent,
     fact_sheet: FactSheet,
     agents: Vec<Box<dyn SpecialFunctions>>,
 }
 
 impl ManagingAgent {
-    pub async fn new(user_req: String) -> Result<Self, Box<dyn std::error::Error>> {
+    // The request may be empty when an API contract describes the project
+    pub async fn new(
+        user_req: String,
+        api_contract: Option<ApiContract>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let attributes = BasicAgent {
             objective: "Manage agents who are building an excellent website for the user"
                 .to_string(),
@@ -27,8 +38,17 @@ impl ManagingAgent {
             memory: vec![],
         };
 
+        let goal_request = match &api_contract {
+            Some(contract) => format!(
+                "{} \n API_CONTRACT: {}",
+                user_req,
+                describe_contract(contract)
+            ),
+            None => user_req,
+        };
+
         let project_description = ai_task_request(
-            user_req,
+            goal_request,
             &attributes.position,
             get_function_string!(convert_user_input_to_goal),
             convert_user_input_to_goal,
@@ -40,13 +60,20 @@ impl ManagingAgent {
         let fact_sheet = FactSheet {
             project_description,
             project_scope: None,
+            api_contract,
             external_urls: None,
+            external_api_samples: None,
+            external_api_profiles: None,
+            data_model: None,
+            protected_routes: None,
             backend_code: None,
             api_endpoint_schema: None,
+            endpoint_test_results: None,
+            load_test_results: None,
         };
 
         Ok(Self {
-            _attributes: attributes,
+            attributes,
             fact_sheet,
             agents,
         })
@@ -58,16 +85,37 @@ impl ManagingAgent {
 
     fn create_agents(&mut self) {
         self.add_agent(Box::new(AgentSolutionArchitect::new()));
+        if read_env_or("BUILD_DATABASE", false) {
+            self.add_agent(Box::new(AgentDatabaseDesigner::new()));
+        }
+        if read_env_or("BUILD_AUTH", false) {
+            self.add_agent(Box::new(AgentAuthDeveloper::new()));
+        }
         self.add_agent(Box::new(AgentBackendDeveloper::new()));
+        if read_env_or("BUILD_FRONTEND", false) {
+            self.add_agent(Box::new(AgentFrontendDeveloper::new()));
+        }
     }
 
-    pub async fn execute_project(&mut self) {
+    // Stops early when the backend cannot satisfy the API contract
+    pub async fn execute_project(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         self.create_agents();
 
         for agent in self.agents.iter_mut() {
-            let _: Result<(), Box<dyn std::error::Error>> =
-                agent.execute(&mut self.fact_sheet).await;
+            if let Err(e) = agent.execute(&mut self.fact_sheet).await {
+                let err_msg = format!("Agent did not finish its work: {}", e);
+                PrintCommand::Issue.print_agent_message(&self.attributes.position, &err_msg);
+
+                if e.downcast_ref::<ContractError>().is_some() {
+                    PrintCommand::Issue.print_agent_message(
+                        &self.attributes.position,
+                        "Stopping the project, the backend does not match the API contract",
+                    );
+                    return Err(e);
+                }
+            }
         }
+        Ok(())
     }
 }
 
@@ -79,10 +127,13 @@ mod test {
     async fn test_managing_agent() {
         let user_request = "need a full stack that fetch and tracks my fitness progress. Needs to include timezone info from the web.";
 
-        let mut managing_agent = ManagingAgent::new(user_request.to_string())
+        let mut managing_agent = ManagingAgent::new(user_request.to_string(), None)
             .await
             .expect("Error creating managing agent");
 
-        managing_agent.execute_project().await;
+        managing_agent
+            .execute_project()
+            .await
+            .expect("Project should finish");
     }
 }
@@ -1,18 +1,19 @@
 use std::time::Duration;
 
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{redirect::Policy, Client};
 
 use crate::{
     ai_functions::ai_func_architect::{print_project_scope, print_site_urls},
     helpers::{
-        general::{ai_task_request_decoded, check_status_code},
+        external_api::{profile_external_urls, profile_summary},
+        general::ai_task_request_decoded,
         PrintCommand,
     },
     models::{
         basic_agent::{AgentState, BasicAgent},
         basic_trait::BasicTraits,
-        FactSheet,
+        ExternalApiProfile, ExternalApiSample, FactSheet,
     },
 };
 
@@ -38,7 +39,7 @@ impl AgentSolutionArchitect {
     }
 
     async fn call_project_scope(&mut self, fact_sheet: &mut FactSheet) -> ProjectScope {
-        let msg_context: String = format!("{}", fact_sheet.project_description);
+        let msg_context: String = fact_sheet.project_description.clone();
 
         let ai_response = ai_task_request_decoded::<ProjectScope>(
             msg_context,
@@ -48,7 +49,7 @@ impl AgentSolutionArchitect {
         )
         .await;
 
-        fact_sheet.project_scope = Some(ai_response.clone());
+        fact_sheet.project_scope = Some(ai_response);
 
         self.attributes
             .update_state(crate::models::basic_agent::AgentState::Finished);
@@ -98,29 +99,41 @@ impl SpecialFunctions for AgentSolutionArchitect {
                 }
                 AgentState::UnitTesting => {
                     let mut exclude_urls: Vec<String> = vec![];
+                    let mut samples: Vec<ExternalApiSample> = vec![];
+                    let mut profiles: Vec<ExternalApiProfile> = vec![];
                     let client: Client = Client::builder()
                         .timeout(Duration::from_secs(5))
+                        .redirect(Policy::none())
                         .build()
                         .unwrap();
                     let urls: &Vec<String> = fact_sheet
                         .external_urls
                         .as_ref()
                         .expect("No URL object on factsheet");
-                    for url in urls {
-                        let endpoint_str = format!("Testing URL Endpoint: {}", url);
-                        PrintCommand::UnitTest
-                            .print_agent_message(&self.attributes.position, &endpoint_str);
-                        match check_status_code(&client, url).await {
-                            Ok(status_code) => {
-                                if status_code != 200 {
-                                    exclude_urls.push(url.clone());
-                                }
-                            }
-                            Err(e) => println!("Error checking {}: {}", url, e),
+                    let endpoint_str = format!("Profiling {} URL Endpoints...", urls.len());
+                    PrintCommand::UnitTest
+                        .print_agent_message(&self.attributes.position, &endpoint_str);
+
+                    for (profile, sample) in profile_external_urls(&client, urls).await {
+                        if profile.status == Some(200) {
+                            PrintCommand::UnitTest.print_agent_message(
+                                &self.attributes.position,
+                                &profile_summary(&profile),
+                            );
+                            samples.extend(sample);
+                        } else {
+                            PrintCommand::Issue.print_agent_message(
+                                &self.attributes.position,
+                                &profile_summary(&profile),
+                            );
+                            exclude_urls.push(profile.url.clone());
                         }
+                        profiles.push(profile);
                     }
+                    fact_sheet.external_api_samples = Some(samples);
+                    fact_sheet.external_api_profiles = Some(profiles);
 
-                    if exclude_urls.len() > 0 {
+                    if !exclude_urls.is_empty() {
                         let new_urls = fact_sheet
                             .external_urls
                             .as_ref()
@@ -155,9 +168,16 @@ mod test {
         let mut fact_sheet = FactSheet {
             project_description: String::from("Build a fullstack website with user login and logout that shows latest Forex prices"),
             project_scope: None,
+            api_contract: None,
             external_urls: None,
+            external_api_samples: None,
+            external_api_profiles: None,
+            data_model: None,
+            protected_routes: None,
             backend_code: None,
             api_endpoint_schema: None,
+            endpoint_test_results: None,
+            load_test_results: None,
 
         };
 
@@ -166,8 +186,8 @@ mod test {
             .await
             .expect("Unable to execute solution architect agent");
 
-        assert!(fact_sheet.project_scope != None);
-        assert!(fact_sheet.external_urls != None);
+        assert!(fact_sheet.project_scope.is_some());
+        assert!(fact_sheet.external_urls.is_some());
 
         dbg!(fact_sheet);
     }
@@ -0,0 +1,175 @@
+use std::fs;
+
+use async_trait::async_trait;
+
+use crate::{
+    ai_functions::ai_func_auth::print_protected_routes,
+    codegen::auth_module::{auth_module, protected_route_problems, AuthStrategy},
+    helpers::{
+        general::{
+            add_web_server_dependency, ai_task_request, read_auth_module_path, read_env_or,
+            strip_code_fence,
+        },
+        PrintCommand,
+    },
+    models::{
+        basic_agent::{AgentState, BasicAgent},
+        FactSheet,
+    },
+};
+
+use super::agent_traits::SpecialFunctions;
+
+#[derive(Debug)]
+pub struct AgentAuthDeveloper {
+    attributes: BasicAgent,
+    protected_routes: Option<Vec<String>>,
+    route_problems: Option<String>,
+    fix_count: u8,
+}
+
+impl AgentAuthDeveloper {
+    pub fn new() -> Self {
+        Self {
+            attributes: BasicAgent {
+                position: "Authentication Developer".to_string(),
+                objective: "Adds reviewed authentication and guards the protected routes"
+                    .to_string(),
+                state: AgentState::Discovery,
+                memory: vec![],
+            },
+            protected_routes: None,
+            route_problems: None,
+            fix_count: 0,
+        }
+    }
+
+    // Returns the problems found in the reply, if any
+    async fn call_protected_routes(&mut self, fact_sheet: &FactSheet) -> Vec<String> {
+        let mut msg_context = format!("PROJECT_DESCRIPTION: {} \n", fact_sheet.project_description);
+        let contract_paths: Option<Vec<String>> =
+            fact_sheet.api_contract.as_ref().map(|contract| {
+                let mut paths: Vec<String> = vec![];
+                for operation in &contract.operations {
+                    if !paths.contains(&operation.route.route) {
+                        paths.push(operation.route.route.clone());
+                    }
+                }
+                paths
+            });
+        if let Some(paths) = &contract_paths {
+            msg_context = format!("{} API_CONTRACT_PATHS: {:?} \n", msg_context, paths);
+        }
+        if let Some(route_problems) = self.route_problems.take() {
+            msg_context = format!(
+                "{} PREVIOUS_PROTECTED_ROUTES: {:?} \n PROTECTED_ROUTE_PROBLEMS: {} \n",
+                msg_context, self.protected_routes, route_problems
+            );
+        }
+
+        let ai_response = ai_task_request(
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_protected_routes),
+            print_protected_routes,
+        )
+        .await;
+
+        let protected_routes: Vec<String> =
+            match serde_json::from_str(strip_code_fence(&ai_response)) {
+                Ok(protected_routes) => protected_routes,
+                Err(e) => return vec![format!("the reply is not a JSON array of paths: {}", e)],
+            };
+        let mut problems = protected_route_problems(&protected_routes);
+        if let Some(paths) = &contract_paths {
+            for path in protected_routes.iter().filter(|path| !paths.contains(path)) {
+                problems.push(format!(
+                    "protected route {} is not in the API contract",
+                    path
+                ));
+            }
+        }
+        self.protected_routes = Some(protected_routes);
+        problems
+    }
+}
+
+#[async_trait]
+impl SpecialFunctions for AgentAuthDeveloper {
+    fn get_attributes_from_agent(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    async fn execute(
+        &mut self,
+        fact_sheet: &mut FactSheet,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let is_login_required = fact_sheet
+            .project_scope
+            .is_some_and(|scope| scope.is_user_login_and_logout);
+        if !is_login_required {
+            PrintCommand::UnitTest.print_agent_message(
+                &self.attributes.position,
+                "Authentication: No login required, nothing to add",
+            );
+            self.attributes.state = AgentState::Finished;
+        }
+
+        while self.attributes.state != AgentState::Finished {
+            match &self.attributes.state {
+                AgentState::Discovery => {
+                    let problems = self.call_protected_routes(fact_sheet).await;
+                    if problems.is_empty() {
+                        self.attributes.state = AgentState::Working;
+                        continue;
+                    }
+                    for problem in &problems {
+                        let problem_msg = format!("Authentication: {}", problem);
+                        PrintCommand::Issue
+                            .print_agent_message(&self.attributes.position, &problem_msg);
+                    }
+                    let max_fix_attempts: u8 = read_env_or("AUTH_FIX_MAX_ATTEMPTS", 2);
+                    if self.fix_count >= max_fix_attempts {
+                        self.attributes.state = AgentState::Finished;
+                        return Err("The protected routes still have problems".into());
+                    }
+                    self.fix_count += 1;
+                    self.route_problems = Some(problems.join("\n"));
+                }
+                AgentState::Working => {
+                    let protected_routes = self
+                        .protected_routes
+                        .clone()
+                        .expect("No protected routes to write");
+                    let strategy: AuthStrategy =
+                        read_env_or("AUTH_STRATEGY", AuthStrategy::Session);
+                    let module_path = read_auth_module_path();
+                    fs::write(&module_path, auth_module(strategy, &protected_routes))?;
+                    let written_msg = format!(
+                        "Authentication: {} module written to {}, protecting {:?}",
+                        strategy,
+                        module_path.display(),
+                        protected_routes
+                    );
+                    PrintCommand::UnitTest
+                        .print_agent_message(&self.attributes.position, &written_msg);
+
+                    for (name, spec) in strategy.dependencies() {
+                        if add_web_server_dependency(name, spec)? {
+                            let added_msg = format!(
+                                "Authentication: {} added to the web server's dependencies",
+                                name
+                            );
+                            PrintCommand::UnitTest
+                                .print_agent_message(&self.attributes.position, &added_msg);
+                        }
+                    }
+                    fact_sheet.protected_routes = Some(protected_routes);
+                    self.attributes.state = AgentState::Finished;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,43 +1,124 @@
 use std::{
-    env, process::{Command, Stdio}, time::Duration
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+    time::Duration,
 };
 
 use async_trait::async_trait;
-use dotenv::dotenv;
 use reqwest::Client;
-use tokio::time::sleep;
 
 use crate::{
     ai_functions::ai_func_backend::{
         print_backend_webserver_code, print_fixed_code, print_improved_webserver_code,
-        print_rest_api_endpoints,
+        print_lint_fixed_code, print_rest_api_endpoints,
+    },
+    analysis::actix_routes::{compare_extracted_routes, parse_actix_routes, path_key},
+    codegen::{
+        auth_module::{auth_prompt_context, AuthStrategy},
+        client_sdk::write_client_sdks,
+        collections::write_request_collections,
+        data_access::data_access_layer,
+        openapi::openapi_document,
+        serde_structs::generate_external_types,
     },
     helpers::{
         confirm_safe_code,
+        external_api::describe_for_prompt,
         general::{
             ai_task_request,
-            check_status_code,
+            create_server_log_path,
+            read_auth_module_path,
+            read_client_sdk_dir,
+            read_clippy_deny_lints,
             read_code_template_contents,
+            read_collections_dir,
+            read_data_access_path,
+            read_env_or,
             read_exec_main_contents,
+            read_exec_main_path,
+            read_fuzz_regression_path,
+            read_fuzz_seed,
+            read_web_server_project_path,
             save_api_endpoints,
             save_backend_code,
+            save_openapi_document,
             // WEB_SERVER_PROJECT_PATH,
         },
+        network::{default_base_url, read_server_address, resolve_base_url},
+        process::{run_command_with_timeout, Readiness, ServerProcess},
         PrintCommand,
     },
     models::{
         basic_agent::{AgentState, BasicAgent},
         FactSheet,
     },
+    testing::{
+        auth::{open_session, run_auth_flow, session_safe_routes},
+        contract::contract_violations,
+        crud::run_crud_scenarios,
+        endpoint::runtime_bug_report,
+        fuzz::{load_regression_cases, plan_fuzz_cases, run_fuzz_cases, save_regression_cases},
+        load::{load_summary, load_targets, run_load_test},
+        mock::{point_code_at_mock, MockProject, MockServer},
+        regression::EndpointSnapshot,
+        routes::{check_routes, RouteCheck},
+    },
+};
+
+use super::{
+    agent_traits::{RouteObject, SpecialFunctions},
+    route_schema::decode_routes,
 };
 
-use super::agent_traits::{RouteObject, SpecialFunctions};
+// Returned by execute when the server still breaks the API contract after the fix budget
+#[derive(Debug)]
+pub struct ContractError;
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The generated server does not satisfy the API contract")
+    }
+}
+
+impl Error for ContractError {}
+
+// Name to declare a module written next to main.rs with
+fn module_name(path: &Path, default: &str) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| default.to_string())
+}
+
+// Data-access layer the database agent wrote next to main.rs, for the prompts
+fn data_access_context(fact_sheet: &FactSheet) -> Option<String> {
+    let data_model = fact_sheet.data_model.as_ref()?;
+    let module = module_name(&read_data_access_path(), "db");
+    Some(format!(
+        "DATA_ACCESS_LAYER (module {}): {}",
+        module,
+        data_access_layer(data_model)
+    ))
+}
+
+// Auth module the auth agent wrote next to main.rs, for the prompts
+fn auth_context(fact_sheet: &FactSheet) -> Option<String> {
+    let protected_routes = fact_sheet.protected_routes.as_ref()?;
+    let module = module_name(&read_auth_module_path(), "auth");
+    let strategy: AuthStrategy = read_env_or("AUTH_STRATEGY", AuthStrategy::Session);
+    Some(auth_prompt_context(&module, strategy, protected_routes))
+}
 
 #[derive(Debug)]
 pub struct AgentBackendDeveloper {
     attributes: BasicAgent,
     bug_errors: Option<String>,
     bug_count: u8,
+    lint_errors: Option<String>,
+    lint_count: u8,
+    server_log_path: Option<PathBuf>,
+    runtime_fix_count: u8,
+    endpoint_snapshot: EndpointSnapshot,
 }
 
 impl AgentBackendDeveloper {
@@ -51,16 +132,44 @@ impl AgentBackendDeveloper {
             },
             bug_errors: None,
             bug_count: 0,
+            lint_errors: None,
+            lint_count: 0,
+            server_log_path: None,
+            runtime_fix_count: 0,
+            endpoint_snapshot: EndpointSnapshot::default(),
         }
     }
 
     async fn call_initial_backend_code(&mut self, fact_sheet: &mut FactSheet) {
         let code_template_str = read_code_template_contents();
 
-        let msg_context = format!(
+        let mut msg_context = format!(
             "CODE TEMPLATE: {} \n PROJECT_DESCRIPTION: {} \n",
             code_template_str, fact_sheet.project_description
         );
+        if let Some(profiles) = &fact_sheet.external_api_profiles {
+            msg_context = format!(
+                "{} EXTERNAL_APIS: {} \n",
+                msg_context,
+                describe_for_prompt(profiles)
+            );
+        }
+        if let Some(types) = fact_sheet
+            .external_api_samples
+            .as_deref()
+            .and_then(generate_external_types)
+        {
+            msg_context = format!("{} EXTERNAL_API_TYPES: {} \n", msg_context, types);
+        }
+        if let Some(contract) = &fact_sheet.api_contract {
+            msg_context = format!("{} API_CONTRACT: {} \n", msg_context, contract.document);
+        }
+        if let Some(data_access) = data_access_context(fact_sheet) {
+            msg_context = format!("{} {} \n", msg_context, data_access);
+        }
+        if let Some(auth) = auth_context(fact_sheet) {
+            msg_context = format!("{} {} \n", msg_context, auth);
+        }
 
         let ai_response = ai_task_request(
             msg_context,
@@ -75,10 +184,32 @@ impl AgentBackendDeveloper {
     }
 
     async fn call_improve_backend_code(&mut self, fact_sheet: &mut FactSheet) {
-        let msg_context = format!(
+        // Full samples are only kept for the mock server, the profiles already describe them
+        let prompt_fact_sheet = FactSheet {
+            external_api_samples: None,
+            api_contract: None,
+            ..fact_sheet.clone()
+        };
+        let mut msg_context = format!(
             "CODE TEMPLATE: {:?} \n PROJECT_DESCRIPTION: {:?} \n",
-            fact_sheet.backend_code, fact_sheet
+            fact_sheet.backend_code, prompt_fact_sheet
         );
+        if let Some(types) = fact_sheet
+            .external_api_samples
+            .as_deref()
+            .and_then(generate_external_types)
+        {
+            msg_context = format!("{} EXTERNAL_API_TYPES: {} \n", msg_context, types);
+        }
+        if let Some(contract) = &fact_sheet.api_contract {
+            msg_context = format!("{} API_CONTRACT: {} \n", msg_context, contract.document);
+        }
+        if let Some(data_access) = data_access_context(fact_sheet) {
+            msg_context = format!("{} {} \n", msg_context, data_access);
+        }
+        if let Some(auth) = auth_context(fact_sheet) {
+            msg_context = format!("{} {} \n", msg_context, auth);
+        }
 
         let ai_response = ai_task_request(
             msg_context,
@@ -110,8 +241,231 @@ impl AgentBackendDeveloper {
         fact_sheet.backend_code = Some(ai_response);
     }
 
+    async fn call_fix_lint_warnings(&mut self, fact_sheet: &mut FactSheet) {
+        let msg_context = format!(
+            "CODE_INPUT: {:?} \n LINT_WARNINGS: {:?} \n THIS FUNCTION ONLY OUTPUTS CODE. JUST OUTPUT THE CODE.",
+            fact_sheet.backend_code,
+            self.lint_errors.take()
+        );
+
+        let ai_response = ai_task_request(
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_lint_fixed_code),
+            print_lint_fixed_code,
+        )
+        .await;
+
+        save_backend_code(&ai_response);
+        fact_sheet.backend_code = Some(ai_response);
+    }
+
+    async fn run_code_formatter(&self, fact_sheet: &mut FactSheet, web_server_project_path: &str) {
+        PrintCommand::Quality.print_agent_message(
+            &self.attributes.position,
+            "Backend code quality: Formatting code with rustfmt...",
+        );
+
+        let build_timeout = Duration::from_secs(read_env_or("BUILD_TIMEOUT_SECS", 300));
+        let format_backend_code = run_command_with_timeout(
+            "cargo",
+            &[String::from("fmt")],
+            web_server_project_path,
+            build_timeout,
+        )
+        .await;
+
+        match format_backend_code {
+            Ok(output) if output.status.success() => {
+                fact_sheet.backend_code = Some(read_exec_main_contents());
+            }
+            Ok(_) => {
+                // Unparsable code is left untouched, the build step reports it as a bug
+                PrintCommand::Quality.print_agent_message(
+                    &self.attributes.position,
+                    "Backend code quality: rustfmt could not parse the code, skipping formatting",
+                );
+            }
+            Err(e) => {
+                let err_msg = format!("Backend code quality: Failed to run rustfmt: {}", e);
+                PrintCommand::Issue.print_agent_message(&self.attributes.position, &err_msg);
+            }
+        }
+    }
+
+    // Returns the clippy findings, if any denied lint fired
+    async fn run_lint_check(&self, web_server_project_path: &str) -> Option<String> {
+        PrintCommand::Quality.print_agent_message(
+            &self.attributes.position,
+            "Backend code quality: Running cargo clippy...",
+        );
+
+        let mut clippy_args: Vec<String> = vec![String::from("clippy"), String::from("--")];
+        for lint in read_clippy_deny_lints() {
+            clippy_args.push(String::from("-D"));
+            clippy_args.push(lint);
+        }
+
+        let build_timeout = Duration::from_secs(read_env_or("BUILD_TIMEOUT_SECS", 300));
+        let lint_backend_server = run_command_with_timeout(
+            "cargo",
+            &clippy_args,
+            web_server_project_path,
+            build_timeout,
+        )
+        .await;
+
+        match lint_backend_server {
+            Ok(output) if output.status.success() => None,
+            Ok(output) => Some(String::from_utf8_lossy(&output.stderr).to_string()),
+            Err(e) => {
+                let err_msg = format!("Backend code quality: Failed to run clippy: {}", e);
+                PrintCommand::Issue.print_agent_message(&self.attributes.position, &err_msg);
+                None
+            }
+        }
+    }
+
+    // Generate the client libraries and run the Rust client's tests against the running server,
+    // returning a bug report if the client cannot call it
+    async fn build_client_sdks(
+        &self,
+        fact_sheet: &FactSheet,
+        route_checks: &[RouteCheck],
+        base_url: &str,
+        server: &ServerProcess,
+    ) -> Option<String> {
+        let routes = fact_sheet.api_endpoint_schema.clone().unwrap_or_default();
+        // The client cannot log in, so protected routes are left out of its smoke test
+        let protected_keys: Vec<String> = fact_sheet
+            .protected_routes
+            .iter()
+            .flatten()
+            .map(|path| path_key(path))
+            .collect();
+        let passing_routes: Vec<RouteObject> = route_checks
+            .iter()
+            .filter(|check| {
+                !check.is_bug()
+                    && check.result.passed()
+                    && !protected_keys.contains(&path_key(&check.route.route))
+            })
+            .map(|check| check.route.clone())
+            .collect();
+        let client_dir = read_client_sdk_dir();
+        let rust_client_dir =
+            match write_client_sdks(&client_dir, &routes, &passing_routes, base_url) {
+                Ok(rust_client_dir) => rust_client_dir,
+                Err(e) => {
+                    let err_msg =
+                        format!("Backend client libraries: Failed to write clients: {}", e);
+                    PrintCommand::Issue.print_agent_message(&self.attributes.position, &err_msg);
+                    return None;
+                }
+            };
+        let written_msg = format!(
+            "Backend client libraries: Rust and TypeScript clients written to {}, building the Rust client...",
+            client_dir.display()
+        );
+        PrintCommand::UnitTest.print_agent_message(&self.attributes.position, &written_msg);
+
+        // Compiling can take longer than the server is allowed to live
+        let build_timeout = Duration::from_secs(read_env_or("BUILD_TIMEOUT_SECS", 300));
+        let rust_client_dir = rust_client_dir.to_string_lossy();
+        server.extend_lifetime(build_timeout);
+        let client_build = run_command_with_timeout(
+            "cargo",
+            &[String::from("test"), String::from("--no-run")],
+            &rust_client_dir,
+            build_timeout,
+        )
+        .await;
+        match client_build {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => {
+                let err_msg = format!(
+                    "Backend client libraries: Rust client does not build: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                PrintCommand::Issue.print_agent_message(&self.attributes.position, &err_msg);
+                return None;
+            }
+            Err(e) => {
+                let err_msg = format!("Backend client libraries: Failed to run cargo test: {}", e);
+                PrintCommand::Issue.print_agent_message(&self.attributes.position, &err_msg);
+                return None;
+            }
+        }
+
+        server.extend_lifetime(build_timeout);
+        let client_test = run_command_with_timeout(
+            "cargo",
+            &[String::from("test")],
+            &rust_client_dir,
+            build_timeout,
+        )
+        .await;
+        match client_test {
+            Ok(output) if output.status.success() => {
+                PrintCommand::UnitTest.print_agent_message(
+                    &self.attributes.position,
+                    "Backend client libraries: Rust client built and called the server successfully",
+                );
+                None
+            }
+            Ok(output) => {
+                let failures = format!(
+                    "{}{}",
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                let err_msg = format!(
+                    "Backend client libraries: Rust client tests failed: {}",
+                    failures
+                );
+                PrintCommand::Issue.print_agent_message(&self.attributes.position, &err_msg);
+                Some(format!(
+                    "THE GENERATED RUST CLIENT FAILED AGAINST THE SERVER: \n {}",
+                    failures
+                ))
+            }
+            Err(e) => {
+                let err_msg = format!("Backend client libraries: Failed to run cargo test: {}", e);
+                PrintCommand::Issue.print_agent_message(&self.attributes.position, &err_msg);
+                None
+            }
+        }
+    }
+
+    fn record_bug(&mut self, bug_errors: String) {
+        self.bug_count += 1;
+        self.bug_errors = Some(bug_errors);
+
+        if self.bug_count > 2 {
+            PrintCommand::Issue.print_agent_message(
+                &self.attributes.position,
+                "Backend code unit testing: Too many bugs found in code",
+            );
+            panic!("Error: Too many bugs");
+        }
+    }
+
+    // Routes come from parsing the code; the model only reads code the parser cannot handle
     async fn call_extract_rest_api_endpoints(&self) -> String {
         let backend_code = read_exec_main_contents();
+        let parsed_routes = parse_actix_routes(&backend_code);
+        let cross_check: bool = read_env_or("ROUTE_EXTRACTION_CROSS_CHECK", false);
+
+        if let Ok(routes) = &parsed_routes {
+            if !routes.is_empty() && !cross_check {
+                let parsed_msg = format!(
+                    "Backend code unit testing: {} routes read from the code",
+                    routes.len()
+                );
+                PrintCommand::UnitTest.print_agent_message(&self.attributes.position, &parsed_msg);
+                return serde_json::to_string(routes).expect("Failed to encode API endpoints");
+            }
+        }
 
         let msg_context = format!("CODE_INPUT: {:?}", backend_code);
 
@@ -123,7 +477,33 @@ impl AgentBackendDeveloper {
         )
         .await;
 
-        ai_response
+        match parsed_routes {
+            Ok(routes) if !routes.is_empty() => {
+                let (model_routes, _) = decode_routes(&ai_response);
+                for difference in compare_extracted_routes(&routes, &model_routes) {
+                    let difference_msg =
+                        format!("Backend code unit testing: Route mismatch, {}", difference);
+                    PrintCommand::Issue
+                        .print_agent_message(&self.attributes.position, &difference_msg);
+                }
+                serde_json::to_string(&routes).expect("Failed to encode API endpoints")
+            }
+            Ok(_) => {
+                PrintCommand::Issue.print_agent_message(
+                    &self.attributes.position,
+                    "Backend code unit testing: No routes found by the parser, using the model's routes",
+                );
+                ai_response
+            }
+            Err(e) => {
+                let parse_msg = format!(
+                    "Backend code unit testing: Could not parse the code ({}), using the model's routes",
+                    e
+                );
+                PrintCommand::Issue.print_agent_message(&self.attributes.position, &parse_msg);
+                ai_response
+            }
+        }
     }
 }
 
@@ -145,10 +525,12 @@ impl SpecialFunctions for AgentBackendDeveloper {
                     continue;
                 }
                 AgentState::Working => {
-                    if self.bug_count == 0 {
-                        self.call_improve_backend_code(fact_sheet).await;
-                    } else {
+                    if self.bug_count > 0 {
                         self.call_fix_code_bugs(fact_sheet).await;
+                    } else if self.lint_errors.is_some() {
+                        self.call_fix_lint_warnings(fact_sheet).await;
+                    } else {
+                        self.call_improve_backend_code(fact_sheet).await;
                     }
                     self.attributes.state = AgentState::UnitTesting;
                     continue;
@@ -162,20 +544,32 @@ impl SpecialFunctions for AgentBackendDeveloper {
                     if !is_safe_code {
                         println!("Better go work on some AI alignment instead...");
                     }
+                    let web_server_project_absolute_path = read_web_server_project_path();
+                    self.run_code_formatter(fact_sheet, &web_server_project_absolute_path)
+                        .await;
+
                     PrintCommand::UnitTest.print_agent_message(
                         &self.attributes.position,
                         "Backend code unit testing: Building project...",
                     );
-                    dotenv().ok();
-                    let web_server_project_absolute_path = env::var("WEB_SERVER_PROJECT_ABSOLUTE_PATH")
-                        .expect("WEB_SERVER_PROJECT_ABSOLUTE_PATH Key not found");
-                    let build_backend_server = Command::new("cargo")
-                        .arg("build")
-                        .current_dir(web_server_project_absolute_path.clone())
-                        .stdout(Stdio::piped())
-                        .stderr(Stdio::piped())
-                        .output()
-                        .expect("Failed to build backend application");
+                    let build_timeout = Duration::from_secs(read_env_or("BUILD_TIMEOUT_SECS", 300));
+                    let build_backend_server = match run_command_with_timeout(
+                        "cargo",
+                        &[String::from("build")],
+                        &web_server_project_absolute_path,
+                        build_timeout,
+                    )
+                    .await
+                    {
+                        Ok(output) => output,
+                        Err(e) => {
+                            let err_msg =
+                                format!("Backend code unit testing: Build failed to run: {}", e);
+                            PrintCommand::Issue
+                                .print_agent_message(&self.attributes.position, &err_msg);
+                            return Err(Box::new(e));
+                        }
+                    };
 
                     if build_backend_server.status.success() {
                         self.bug_count = 0;
@@ -187,33 +581,58 @@ impl SpecialFunctions for AgentBackendDeveloper {
                         let error_arr = build_backend_server.stderr;
                         let error_arr = String::from_utf8(error_arr).unwrap();
 
-                        self.bug_count += 1;
-                        self.bug_errors = Some(error_arr);
+                        self.record_bug(error_arr);
+                        self.attributes.state = AgentState::Working;
+                        continue;
+                    }
 
-                        if self.bug_count > 2 {
-                            PrintCommand::Issue.print_agent_message(
+                    if let Some(lint_errors) =
+                        self.run_lint_check(&web_server_project_absolute_path).await
+                    {
+                        let max_lint_attempts: u8 = read_env_or("CLIPPY_MAX_ATTEMPTS", 2);
+                        if self.lint_count < max_lint_attempts {
+                            self.lint_count += 1;
+                            self.lint_errors = Some(lint_errors);
+                            PrintCommand::Quality.print_agent_message(
                                 &self.attributes.position,
-                                "Backend code unit testing: Too many bugs found in code",
+                                "Backend code quality: Clippy findings sent back for fixing",
                             );
-                            panic!("Error: Too many bugs");
+                            self.attributes.state = AgentState::Working;
+                            continue;
                         }
-
-                        self.attributes.state = AgentState::Working;
-                        continue;
+                        PrintCommand::Issue.print_agent_message(
+                            &self.attributes.position,
+                            "Backend code quality: Lint budget exhausted, continuing with remaining warnings",
+                        );
+                    } else {
+                        PrintCommand::Quality.print_agent_message(
+                            &self.attributes.position,
+                            "Backend code quality: No clippy findings",
+                        );
                     }
 
                     let api_endpoints_str = self.call_extract_rest_api_endpoints().await;
 
-                    let api_endpoints: Vec<RouteObject> = serde_json::from_str(&api_endpoints_str)
-                        .expect("Failed to decode API endpoints");
+                    // An entry the model got wrong, such as an unknown method, only drops that route
+                    let (api_endpoints, skipped_routes) = decode_routes(&api_endpoints_str);
+                    for skipped in skipped_routes {
+                        let skipped_msg =
+                            format!("Backend code unit testing: Route entry {}", skipped);
+                        PrintCommand::Issue
+                            .print_agent_message(&self.attributes.position, &skipped_msg);
+                    }
 
-                    let check_endpoints: Vec<RouteObject> = api_endpoints
-                        .iter()
-                        .filter(|&route| route.method == "get" && route.is_route_dynamic == "false")
-                        .cloned()
-                        .collect();
+                    fact_sheet.api_endpoint_schema = Some(api_endpoints.clone());
 
-                    fact_sheet.api_endpoint_schema = Some(check_endpoints.clone());
+                    // A contract fixes the routes and shapes to test against
+                    let test_routes: Vec<RouteObject> = match &fact_sheet.api_contract {
+                        Some(contract) => contract
+                            .operations
+                            .iter()
+                            .map(|operation| operation.route.clone())
+                            .collect(),
+                        None => api_endpoints.clone(),
+                    };
 
                     // Run backend application
                     PrintCommand::UnitTest.print_agent_message(
@@ -221,62 +640,519 @@ impl SpecialFunctions for AgentBackendDeveloper {
                         "Backend code unit testing: Starting web server",
                     );
 
-                    let mut run_backend_server = Command::new("cargo")
-                        .arg("run")
-                        .current_dir(web_server_project_absolute_path)
-                        .stdout(Stdio::piped())
-                        .stderr(Stdio::piped())
-                        .spawn()
-                        .expect("Failed to run backend application");
+                    let (server_host, server_port) = read_server_address()?;
+                    let base_url = resolve_base_url(
+                        fact_sheet.backend_code.as_deref().unwrap_or_default(),
+                        &server_host,
+                        server_port,
+                    );
+                    let address_msg = format!(
+                        "Backend code unit testing: Web server expected at {}",
+                        base_url
+                    );
+                    PrintCommand::UnitTest
+                        .print_agent_message(&self.attributes.position, &address_msg);
+
+                    let collections_dir = read_collections_dir();
+                    match write_request_collections(
+                        &collections_dir,
+                        &api_endpoints,
+                        &default_base_url(fact_sheet.backend_code.as_deref().unwrap_or_default()),
+                        "Benjamin generated API",
+                    ) {
+                        Ok(_) => {
+                            let collections_msg = format!(
+                                "Backend code unit testing: Request collections written to {}",
+                                collections_dir.display()
+                            );
+                            PrintCommand::UnitTest
+                                .print_agent_message(&self.attributes.position, &collections_msg);
+                        }
+                        Err(e) => {
+                            let err_msg = format!(
+                                "Backend code unit testing: Failed to write request collections: {}",
+                                e
+                            );
+                            PrintCommand::Issue
+                                .print_agent_message(&self.attributes.position, &err_msg);
+                        }
+                    }
+
+                    let server_log_path = self
+                        .server_log_path
+                        .get_or_insert_with(create_server_log_path)
+                        .clone();
+                    let log_msg = format!(
+                        "Backend code unit testing: Web server output logged to {}",
+                        server_log_path.display()
+                    );
+                    PrintCommand::UnitTest.print_agent_message(&self.attributes.position, &log_msg);
+
+                    // Replay recorded external API responses instead of calling the real APIs
+                    let samples = fact_sheet.external_api_samples.clone().unwrap_or_default();
+                    let mock_server =
+                        if !samples.is_empty() && read_env_or("MOCK_EXTERNAL_APIS", true) {
+                            Some(MockServer::start(samples.clone()).await?)
+                        } else {
+                            None
+                        };
+                    let mock_project = match &mock_server {
+                        Some(mock_server) => {
+                            let mock_msg = format!(
+                                "Backend code unit testing: External APIs replayed from {}",
+                                mock_server.base_url()
+                            );
+                            PrintCommand::UnitTest
+                                .print_agent_message(&self.attributes.position, &mock_msg);
+                            let mocked_code = point_code_at_mock(
+                                fact_sheet.backend_code.as_deref().unwrap_or_default(),
+                                &samples,
+                                mock_server.base_url(),
+                            );
+                            Some(MockProject::create(
+                                Path::new(&web_server_project_absolute_path),
+                                &read_exec_main_path(),
+                                &mocked_code,
+                            )?)
+                        }
+                        None => None,
+                    };
+
+                    // The mocked copy builds into the project's own target directory and runs
+                    // from the project directory, so only the URLs differ from a normal run
+                    let mut run_args = vec![String::from("run")];
+                    let mut run_envs =
+                        vec![("HOST", server_host), ("PORT", server_port.to_string())];
+                    if let Some(mock_project) = &mock_project {
+                        run_args.push(String::from("--manifest-path"));
+                        run_args.push(mock_project.manifest_path().display().to_string());
+                        if std::env::var_os("CARGO_TARGET_DIR").is_none() {
+                            let target_dir =
+                                Path::new(&web_server_project_absolute_path).join("target");
+                            run_envs.push(("CARGO_TARGET_DIR", target_dir.display().to_string()));
+                        }
+                    }
+
+                    let run_timeout = Duration::from_secs(read_env_or("RUN_TIMEOUT_SECS", 120));
+                    let mut run_backend_server = ServerProcess::spawn(
+                        "cargo",
+                        &run_args,
+                        &web_server_project_absolute_path,
+                        &run_envs,
+                        &server_log_path,
+                        run_timeout,
+                    )?;
+
+                    let probe_url = format!(
+                        "{}{}",
+                        base_url,
+                        read_env_or("SERVER_HEALTH_ROUTE", String::from("/"))
+                    );
+                    let ready_timeout =
+                        Duration::from_secs(read_env_or("SERVER_READY_TIMEOUT_SECS", 60));
+                    let probe_client = Client::builder()
+                        .timeout(Duration::from_secs(2))
+                        .build()
+                        .unwrap();
 
                     PrintCommand::UnitTest.print_agent_message(
                         &self.attributes.position,
-                        "Backend code unit testing: Lauching tests on server in 5 seconds...",
+                        "Backend code unit testing: Waiting for web server to answer...",
                     );
 
-                    let seconds_sleep = Duration::from_secs(5);
-                    sleep(seconds_sleep).await;
+                    let readiness = run_backend_server
+                        .wait_until_ready(&probe_client, &probe_url, ready_timeout)
+                        .await?;
 
-                    for endpoint in check_endpoints {
-                        let testing_msg = format!("Testing endpoint '{}'...", endpoint.route);
-                        PrintCommand::UnitTest
-                            .print_agent_message(&self.attributes.position, &testing_msg);
-
-                        let client = Client::builder()
-                            .timeout(Duration::from_secs(5))
-                            .build()
-                            .unwrap();
-
-                        let url = format!("http://localhost:8080{}", endpoint.route);
-                        match check_status_code(&client, &url).await {
-                            Ok(status_code) => {
-                                if status_code != 200 {
-                                    let err_msg = format!(
-                                        "WARNING: Failed to call backend url endpoint {}",
-                                        endpoint.route
+                    // The server is built by now, so the mocked copy is no longer needed
+                    drop(mock_project);
+
+                    if readiness != Readiness::Ready {
+                        let server_log = run_backend_server.log().clone();
+                        run_backend_server.shutdown().await?;
+                        let failure = match readiness {
+                            Readiness::Exited(exit_status) => {
+                                format!("Web server stopped before answering ({})", exit_status)
+                            }
+                            _ => format!(
+                                "Web server did not answer on {} within {} seconds",
+                                probe_url,
+                                ready_timeout.as_secs()
+                            ),
+                        };
+                        let err_msg = format!("Backend code unit testing: {}", failure);
+                        PrintCommand::Issue
+                            .print_agent_message(&self.attributes.position, &err_msg);
+                        self.record_bug(format!(
+                            "{}\nSERVER LOGS:\n{}",
+                            failure,
+                            server_log.contents()
+                        ));
+                        self.attributes.state = AgentState::Working;
+                        continue;
+                    }
+
+                    let client = Client::builder()
+                        .timeout(Duration::from_secs(5))
+                        .build()
+                        .unwrap();
+                    let protected_routes = fact_sheet.protected_routes.clone().unwrap_or_default();
+
+                    // Auth goes first, so the other tests can call protected routes with a session
+                    let is_login_required = fact_sheet
+                        .project_scope
+                        .is_some_and(|scope| scope.is_user_login_and_logout);
+                    let mut auth_reports: Vec<String> = vec![];
+                    let mut session = None;
+                    if is_login_required {
+                        PrintCommand::UnitTest.print_agent_message(
+                            &self.attributes.position,
+                            "Backend code unit testing: Testing sign up, login and logout...",
+                        );
+                        let auth_checks = run_auth_flow(
+                            &client,
+                            run_backend_server.log(),
+                            &base_url,
+                            &test_routes,
+                            &protected_routes,
+                        )
+                        .await;
+                        for auth_check in auth_checks {
+                            match auth_check.bug_report() {
+                                Some(bug_report) => {
+                                    PrintCommand::Issue.print_agent_message(
+                                        &self.attributes.position,
+                                        &auth_check.summary(),
                                     );
-                                    PrintCommand::UnitTest
-                                        .print_agent_message(&self.attributes.position, &err_msg);
+                                    auth_reports.push(bug_report);
+                                }
+                                None => PrintCommand::UnitTest.print_agent_message(
+                                    &self.attributes.position,
+                                    &auth_check.summary(),
+                                ),
+                            }
+                        }
+
+                        // The flow logs its user out, so the other tests get a user of their own
+                        session = open_session(
+                            &client,
+                            run_backend_server.log(),
+                            &base_url,
+                            &test_routes,
+                        )
+                        .await;
+                        if session.is_none() {
+                            PrintCommand::Issue.print_agent_message(
+                                &self.attributes.position,
+                                "Backend code unit testing: Could not log in, protected routes are tested without a session",
+                            );
+                        }
+                    }
+                    // With a session, a 401 or 403 from a protected route is a bug, not an expected refusal
+                    let (session_client, session_routes, refusing_routes) = match &session {
+                        Some(credentials) => (
+                            Client::builder()
+                                .timeout(Duration::from_secs(5))
+                                .default_headers(credentials.headers())
+                                .build()
+                                .unwrap(),
+                            session_safe_routes(&test_routes),
+                            vec![],
+                        ),
+                        None => (
+                            client.clone(),
+                            test_routes.clone(),
+                            protected_routes.clone(),
+                        ),
+                    };
+
+                    PrintCommand::UnitTest.print_agent_message(
+                        &self.attributes.position,
+                        "Backend code unit testing: Testing every route with example requests...",
+                    );
+                    let route_checks = check_routes(
+                        &session_client,
+                        &mut run_backend_server,
+                        &base_url,
+                        &session_routes,
+                        &refusing_routes,
+                    )
+                    .await?;
+
+                    fact_sheet.endpoint_test_results = Some(
+                        route_checks
+                            .iter()
+                            .map(|check| check.to_test_result())
+                            .collect(),
+                    );
+
+                    let load_targets = load_targets(&route_checks);
+                    let mut bug_reports: Vec<String> = auth_reports;
+                    for route_check in &route_checks {
+                        let command = if route_check.is_bug() {
+                            PrintCommand::Issue
+                        } else {
+                            PrintCommand::UnitTest
+                        };
+                        command
+                            .print_agent_message(&self.attributes.position, &route_check.summary());
+                        if route_check.is_bug() {
+                            bug_reports.push(route_check.bug_report());
+                        }
+                    }
+                    if let Some(contract) = &fact_sheet.api_contract {
+                        for violation in
+                            contract_violations(contract, &api_endpoints, &route_checks)
+                        {
+                            PrintCommand::Issue.print_agent_message(
+                                &self.attributes.position,
+                                &violation.summary(),
+                            );
+                            bug_reports.push(violation.bug_report());
+                        }
+                    }
+
+                    // Compare with the last version each route passed in
+                    let comparison = self.endpoint_snapshot.compare(&route_checks);
+                    for regression in &comparison.regressions {
+                        PrintCommand::Issue
+                            .print_agent_message(&self.attributes.position, &regression.summary());
+                    }
+                    let regression_reports: Vec<String> = comparison
+                        .regressions
+                        .iter()
+                        .map(|regression| regression.bug_report())
+                        .collect();
+                    let previous_code = self.endpoint_snapshot.code().map(String::from);
+                    if let (true, Some(previous_code)) =
+                        (comparison.should_roll_back(), previous_code)
+                    {
+                        run_backend_server.shutdown().await?;
+                        let rollback_msg = format!(
+                            "Backend code unit testing: {} regressions against {} fixes, rolling back to the previous version",
+                            comparison.regressions.len(),
+                            comparison.fixes.len()
+                        );
+                        PrintCommand::Issue
+                            .print_agent_message(&self.attributes.position, &rollback_msg);
+                        save_backend_code(&previous_code);
+                        fact_sheet.backend_code = Some(previous_code);
+                        fact_sheet.api_endpoint_schema =
+                            self.endpoint_snapshot.api_endpoints().map(<[_]>::to_vec);
+
+                        let max_runtime_attempts: u8 = read_env_or("RUNTIME_FIX_MAX_ATTEMPTS", 3);
+                        if self.runtime_fix_count < max_runtime_attempts {
+                            self.runtime_fix_count += 1;
+                            self.record_bug(runtime_bug_report(&regression_reports));
+                            self.attributes.state = AgentState::Working;
+                            continue;
+                        }
+                        self.attributes.state = AgentState::Finished;
+                        if fact_sheet.api_contract.is_some() {
+                            return Err(Box::new(ContractError));
+                        }
+                        continue;
+                    }
+                    bug_reports.extend(regression_reports);
+                    self.endpoint_snapshot.record(
+                        &route_checks,
+                        fact_sheet.backend_code.as_deref().unwrap_or_default(),
+                        &api_endpoints,
+                    );
+
+                    let is_crud_required = fact_sheet
+                        .project_scope
+                        .is_some_and(|scope| scope.is_crud_required);
+                    if is_crud_required && is_login_required && session.is_none() {
+                        PrintCommand::Issue.print_agent_message(
+                            &self.attributes.position,
+                            "Backend code unit testing: CRUD scenarios skipped until login works",
+                        );
+                    } else if is_crud_required && run_backend_server.has_exited()?.is_none() {
+                        PrintCommand::UnitTest.print_agent_message(
+                            &self.attributes.position,
+                            "Backend code unit testing: Running CRUD scenarios...",
+                        );
+                        let crud_reports = run_crud_scenarios(
+                            &session_client,
+                            run_backend_server.log(),
+                            &base_url,
+                            &session_routes,
+                        )
+                        .await;
+                        for crud_report in crud_reports {
+                            match crud_report.bug_report() {
+                                Some(bug_report) => {
+                                    PrintCommand::Issue.print_agent_message(
+                                        &self.attributes.position,
+                                        &crud_report.summary(),
+                                    );
+                                    bug_reports.push(bug_report);
                                 }
+                                None => PrintCommand::UnitTest.print_agent_message(
+                                    &self.attributes.position,
+                                    &crud_report.summary(),
+                                ),
                             }
-                            Err(e) => {
-                                run_backend_server
-                                    .kill()
-                                    .expect("Failed to kill backend server");
-                                let err_msg = format!("Error checking backend {}", e);
-                                PrintCommand::UnitTest
-                                    .print_agent_message(&self.attributes.position, &err_msg);
+                        }
+                    }
+
+                    let fuzz_cases_per_route: usize = read_env_or("FUZZ_CASES_PER_ROUTE", 20);
+                    if fuzz_cases_per_route > 0 && run_backend_server.has_exited()?.is_none() {
+                        let fuzz_seed = read_fuzz_seed();
+                        let fuzz_msg = format!(
+                            "Backend code unit testing: Fuzzing endpoints (seed {})...",
+                            fuzz_seed
+                        );
+                        PrintCommand::UnitTest
+                            .print_agent_message(&self.attributes.position, &fuzz_msg);
+
+                        let regression_path = read_fuzz_regression_path();
+                        let fuzz_cases = plan_fuzz_cases(
+                            &session_routes,
+                            &load_regression_cases(&regression_path),
+                            fuzz_cases_per_route,
+                            fuzz_seed,
+                        );
+                        let findings = run_fuzz_cases(
+                            &session_client,
+                            &mut run_backend_server,
+                            &base_url,
+                            &fuzz_cases,
+                        )
+                        .await?;
+
+                        if findings.is_empty() {
+                            let passed_msg = format!(
+                                "Backend code unit testing: {} fuzz cases passed",
+                                fuzz_cases.len()
+                            );
+                            PrintCommand::UnitTest
+                                .print_agent_message(&self.attributes.position, &passed_msg);
+                        } else {
+                            save_regression_cases(&regression_path, &findings)?;
+                            for finding in &findings {
+                                PrintCommand::Issue.print_agent_message(
+                                    &self.attributes.position,
+                                    &finding.summary(),
+                                );
+                            }
+                            // A handful of inputs is enough for the model to see the pattern
+                            bug_reports.extend(findings.iter().take(5).map(|f| f.bug_report()));
+                        }
+                    }
+
+                    if read_env_or("GENERATE_CLIENT_SDKS", false)
+                        && run_backend_server.has_exited()?.is_none()
+                    {
+                        let client_report = self
+                            .build_client_sdks(
+                                fact_sheet,
+                                &route_checks,
+                                &base_url,
+                                &run_backend_server,
+                            )
+                            .await;
+                        bug_reports.extend(client_report);
+                    }
+
+                    // Load stage only measures servers that already behave correctly
+                    let load_duration =
+                        Duration::from_secs(read_env_or("LOAD_TEST_DURATION_SECS", 0));
+                    if !load_duration.is_zero()
+                        && bug_reports.is_empty()
+                        && run_backend_server.has_exited()?.is_none()
+                    {
+                        let concurrency: usize = read_env_or("LOAD_TEST_CONCURRENCY", 8);
+                        let load_msg = format!(
+                            "Backend code load testing: {} concurrent clients for {} seconds per route...",
+                            concurrency,
+                            load_duration.as_secs()
+                        );
+                        PrintCommand::UnitTest
+                            .print_agent_message(&self.attributes.position, &load_msg);
+
+                        // Keep the watchdog from killing the server halfway through
+                        let load_time = load_duration * load_targets.len() as u32;
+                        run_backend_server.extend_lifetime(load_time + Duration::from_secs(30));
+                        let log_cursor = run_backend_server.log().cursor();
+                        let load_results = run_load_test(
+                            &session_client,
+                            &base_url,
+                            &load_targets,
+                            concurrency,
+                            load_duration,
+                        )
+                        .await;
+                        if run_backend_server.has_exited()?.is_some() {
+                            PrintCommand::Issue.print_agent_message(
+                                &self.attributes.position,
+                                "Backend code load testing: Web server stopped during the load test, results discarded",
+                            );
+                            let load_logs = run_backend_server.log().lines_since(log_cursor);
+                            let log_tail = &load_logs[load_logs.len().saturating_sub(50)..];
+                            bug_reports.push(format!(
+                                "WEB SERVER STOPPED UNDER LOAD ({} concurrent clients per route) \n SERVER LOGS:\n{}",
+                                concurrency,
+                                log_tail.join("\n")
+                            ));
+                        } else {
+                            for load_result in &load_results {
+                                let command = if load_result.errors > 0 {
+                                    PrintCommand::Issue
+                                } else {
+                                    PrintCommand::UnitTest
+                                };
+                                command.print_agent_message(
+                                    &self.attributes.position,
+                                    &load_summary(load_result),
+                                );
                             }
+                            fact_sheet.load_test_results = Some(load_results);
                         }
                     }
-                    save_api_endpoints(&api_endpoints_str);
+
+                    if run_backend_server.has_exited()?.is_some() {
+                        PrintCommand::Issue.print_agent_message(
+                            &self.attributes.position,
+                            "Backend code unit testing: Web server crashed during testing",
+                        );
+                    }
+                    // Normalised schema, whatever form the routes were extracted in
+                    let normalised_endpoints = serde_json::to_string_pretty(&api_endpoints)
+                        .expect("Failed to encode API endpoints");
+                    save_api_endpoints(&normalised_endpoints);
+                    save_openapi_document(&openapi_document(
+                        &fact_sheet.project_description,
+                        &api_endpoints,
+                    ));
                     PrintCommand::UnitTest.print_agent_message(
                         &self.attributes.position,
                         "Backend testing completed...",
                     );
-                    run_backend_server
-                        .kill()
-                        .expect("Failed to kill backend server on completion");
+                    run_backend_server.shutdown().await?;
+
+                    if !bug_reports.is_empty() {
+                        let max_runtime_attempts: u8 = read_env_or("RUNTIME_FIX_MAX_ATTEMPTS", 3);
+                        if self.runtime_fix_count < max_runtime_attempts {
+                            self.runtime_fix_count += 1;
+                            PrintCommand::Issue.print_agent_message(
+                                &self.attributes.position,
+                                "Backend code unit testing: Endpoint failures sent back for fixing",
+                            );
+                            self.record_bug(runtime_bug_report(&bug_reports));
+                            self.attributes.state = AgentState::Working;
+                            continue;
+                        }
+                        PrintCommand::Issue.print_agent_message(
+                            &self.attributes.position,
+                            "Backend code unit testing: Runtime fix budget exhausted, endpoints still failing",
+                        );
+                        if fact_sheet.api_contract.is_some() {
+                            self.attributes.state = AgentState::Finished;
+                            return Err(Box::new(ContractError));
+                        }
+                    }
 
                     self.attributes.state = AgentState::Finished;
                 }
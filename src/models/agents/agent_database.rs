@@ -0,0 +1,214 @@
+use std::fs;
+
+use async_trait::async_trait;
+
+use crate::{
+    ai_functions::ai_func_database::print_data_model,
+    codegen::{
+        data_access::{data_access_layer, data_access_statements},
+        migrations::{data_model_problems, migration, SqlDialect},
+    },
+    helpers::{
+        general::{
+            add_web_server_dependency, ai_task_request, read_data_access_path, read_env_or,
+            save_migration, strip_code_fence,
+        },
+        PrintCommand,
+    },
+    models::{
+        basic_agent::{AgentState, BasicAgent},
+        DataModel, FactSheet,
+    },
+    testing::migrations::validate_sqlite_migration,
+};
+
+use super::agent_traits::SpecialFunctions;
+
+// Dependency the data-access layer needs in the web server project
+const RUSQLITE_DEPENDENCY: &str = r#"{ version = "0.31", features = ["bundled"] }"#;
+
+#[derive(Debug)]
+pub struct AgentDatabaseDesigner {
+    attributes: BasicAgent,
+    data_model: Option<DataModel>,
+    model_problems: Option<String>,
+    fix_count: u8,
+}
+
+impl AgentDatabaseDesigner {
+    pub fn new() -> Self {
+        Self {
+            attributes: BasicAgent {
+                position: "Database Designer".to_string(),
+                objective: "Design the data model, migrations and data-access layer".to_string(),
+                state: AgentState::Discovery,
+                memory: vec![],
+            },
+            data_model: None,
+            model_problems: None,
+            fix_count: 0,
+        }
+    }
+
+    // Count a fix attempt, false once DATABASE_FIX_MAX_ATTEMPTS is spent
+    fn try_fix(&mut self) -> bool {
+        let max_fix_attempts: u8 = read_env_or("DATABASE_FIX_MAX_ATTEMPTS", 2);
+        if self.fix_count >= max_fix_attempts {
+            return false;
+        }
+        self.fix_count += 1;
+        true
+    }
+
+    // Returns the problems found in the reply, if any
+    async fn call_data_model(&mut self, fact_sheet: &FactSheet) -> Vec<String> {
+        let mut msg_context = format!("PROJECT_DESCRIPTION: {} \n", fact_sheet.project_description);
+        if let Some(model_problems) = self.model_problems.take() {
+            let previous_model =
+                serde_json::to_string(&self.data_model).expect("Failed to encode data model");
+            msg_context = format!(
+                "{} PREVIOUS_DATA_MODEL: {} \n DATA_MODEL_PROBLEMS: {} \n",
+                msg_context, previous_model, model_problems
+            );
+        }
+
+        let ai_response = ai_task_request(
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_data_model),
+            print_data_model,
+        )
+        .await;
+
+        match serde_json::from_str::<DataModel>(strip_code_fence(&ai_response)) {
+            Ok(data_model) => {
+                let problems = data_model_problems(&data_model);
+                self.data_model = Some(data_model);
+                problems
+            }
+            Err(e) => vec![format!("the reply is not a valid data model: {}", e)],
+        }
+    }
+
+    // Print the problems and send them back, false once the fix budget is spent
+    fn send_back_problems(&mut self, problems: Vec<String>) -> bool {
+        for problem in &problems {
+            let problem_msg = format!("Database unit testing: {}", problem);
+            PrintCommand::Issue.print_agent_message(&self.attributes.position, &problem_msg);
+        }
+        if !self.try_fix() {
+            return false;
+        }
+        PrintCommand::Issue.print_agent_message(
+            &self.attributes.position,
+            "Database unit testing: Data model problems sent back for fixing",
+        );
+        self.model_problems = Some(problems.join("\n"));
+        self.attributes.state = AgentState::Discovery;
+        true
+    }
+}
+
+#[async_trait]
+impl SpecialFunctions for AgentDatabaseDesigner {
+    fn get_attributes_from_agent(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    async fn execute(
+        &mut self,
+        fact_sheet: &mut FactSheet,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let is_crud_required = fact_sheet
+            .project_scope
+            .is_some_and(|scope| scope.is_crud_required);
+        if !is_crud_required {
+            PrintCommand::UnitTest.print_agent_message(
+                &self.attributes.position,
+                "Database design: No CRUD required, keeping the template's store",
+            );
+            self.attributes.state = AgentState::Finished;
+        }
+
+        while self.attributes.state != AgentState::Finished {
+            match &self.attributes.state {
+                AgentState::Discovery => {
+                    let problems = self.call_data_model(fact_sheet).await;
+                    if problems.is_empty() {
+                        self.attributes.state = AgentState::Working;
+                    } else if !self.send_back_problems(problems) {
+                        self.attributes.state = AgentState::Finished;
+                        return Err("The data model still has problems".into());
+                    }
+                }
+                AgentState::Working => {
+                    let data_model = self.data_model.as_ref().expect("No data model to write");
+                    let sqlite_path =
+                        save_migration("sqlite", &migration(data_model, SqlDialect::Sqlite))?;
+                    let written_msg = format!(
+                        "Database design: Migration written to {}",
+                        sqlite_path.display()
+                    );
+                    PrintCommand::UnitTest
+                        .print_agent_message(&self.attributes.position, &written_msg);
+                    if read_env_or("DATABASE_POSTGRES", false) {
+                        let postgres_path = save_migration(
+                            "postgres",
+                            &migration(data_model, SqlDialect::Postgres),
+                        )?;
+                        let written_msg = format!(
+                            "Database design: Postgres migration written to {}",
+                            postgres_path.display()
+                        );
+                        PrintCommand::UnitTest
+                            .print_agent_message(&self.attributes.position, &written_msg);
+                    }
+
+                    let data_access_path = read_data_access_path();
+                    fs::write(&data_access_path, data_access_layer(data_model))?;
+                    let written_msg = format!(
+                        "Database design: Data-access layer written to {}",
+                        data_access_path.display()
+                    );
+                    PrintCommand::UnitTest
+                        .print_agent_message(&self.attributes.position, &written_msg);
+                    self.attributes.state = AgentState::UnitTesting;
+                }
+                AgentState::UnitTesting => {
+                    PrintCommand::UnitTest.print_agent_message(
+                        &self.attributes.position,
+                        "Database unit testing: Applying the migration to a temporary SQLite database...",
+                    );
+                    let data_model = self.data_model.clone().expect("No data model to test");
+                    let problems = validate_sqlite_migration(
+                        &data_model,
+                        &migration(&data_model, SqlDialect::Sqlite),
+                        &data_access_statements(&data_model),
+                    )?;
+                    if !problems.is_empty() {
+                        if !self.send_back_problems(problems) {
+                            self.attributes.state = AgentState::Finished;
+                            return Err("The migration does not apply to SQLite".into());
+                        }
+                        continue;
+                    }
+                    PrintCommand::UnitTest.print_agent_message(
+                        &self.attributes.position,
+                        "Database unit testing: Migration applied and every query fits the schema",
+                    );
+
+                    if add_web_server_dependency("rusqlite", RUSQLITE_DEPENDENCY)? {
+                        PrintCommand::UnitTest.print_agent_message(
+                            &self.attributes.position,
+                            "Database design: rusqlite added to the web server's dependencies",
+                        );
+                    }
+                    fact_sheet.data_model = Some(data_model);
+                    self.attributes.state = AgentState::Finished;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,399 @@
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::{
+    ai_functions::{
+        ai_func_backend::print_fixed_code,
+        ai_func_frontend::{print_frontend_code, print_frontend_served_code},
+    },
+    helpers::{
+        confirm_safe_code,
+        general::{
+            ai_task_request, create_server_log_path, read_env_or, read_frontend_dir,
+            read_web_server_project_path, save_backend_code, save_frontend_files,
+        },
+        network::{read_server_address, resolve_base_url},
+        process::{run_command_with_timeout, Readiness, ServerProcess},
+        PrintCommand,
+    },
+    models::{
+        basic_agent::{AgentState, BasicAgent},
+        FactSheet,
+    },
+    testing::{
+        endpoint::runtime_bug_report,
+        frontend::{check_pages, page_path, parse_frontend_files, unknown_api_calls},
+        routes::check_routes,
+    },
+};
+
+use super::agent_traits::SpecialFunctions;
+
+#[derive(Debug)]
+pub struct AgentFrontendDeveloper {
+    attributes: BasicAgent,
+    frontend_files: BTreeMap<String, String>,
+    frontend_bugs: Option<String>,
+    serving_bugs: Option<String>,
+    is_frontend_served: bool,
+    fix_count: u8,
+    server_log_path: Option<PathBuf>,
+}
+
+impl AgentFrontendDeveloper {
+    pub fn new() -> Self {
+        Self {
+            attributes: BasicAgent {
+                position: "Frontend Developer".to_string(),
+                objective: "Develop a static frontend served by the web server".to_string(),
+                state: AgentState::Discovery,
+                memory: vec![],
+            },
+            frontend_files: BTreeMap::new(),
+            frontend_bugs: None,
+            serving_bugs: None,
+            is_frontend_served: false,
+            fix_count: 0,
+            server_log_path: None,
+        }
+    }
+
+    fn frontend_route() -> String {
+        read_env_or("FRONTEND_ROUTE", String::from("/app"))
+    }
+
+    // Count a fix attempt, false once FRONTEND_FIX_MAX_ATTEMPTS is spent
+    fn try_fix(&mut self) -> bool {
+        let max_fix_attempts: u8 = read_env_or("FRONTEND_FIX_MAX_ATTEMPTS", 2);
+        if self.fix_count >= max_fix_attempts {
+            return false;
+        }
+        self.fix_count += 1;
+        true
+    }
+
+    async fn call_frontend_code(&mut self, fact_sheet: &FactSheet) -> Result<(), String> {
+        let api_endpoints = serde_json::to_string(&fact_sheet.api_endpoint_schema)
+            .expect("Failed to encode API endpoints");
+        let mut msg_context = format!(
+            "PROJECT_DESCRIPTION: {} \n API_ENDPOINTS: {} \n FRONTEND_ROUTE: {} \n",
+            fact_sheet.project_description,
+            api_endpoints,
+            Self::frontend_route()
+        );
+        if let Some(frontend_bugs) = self.frontend_bugs.take() {
+            let previous_frontend = serde_json::to_string(&self.frontend_files)
+                .expect("Failed to encode frontend files");
+            msg_context = format!(
+                "{} PREVIOUS_FRONTEND: {} \n FRONTEND_BUGS: {} \n",
+                msg_context, previous_frontend, frontend_bugs
+            );
+        }
+
+        let ai_response = ai_task_request(
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_frontend_code),
+            print_frontend_code,
+        )
+        .await;
+
+        self.frontend_files = parse_frontend_files(&ai_response)?;
+        Ok(())
+    }
+
+    async fn call_serve_frontend(&mut self, fact_sheet: &mut FactSheet) {
+        let ai_response = match self.serving_bugs.take() {
+            Some(serving_bugs) => {
+                let msg_context = format!(
+                    "BROKEN_CODE: {:?} \n ERROR_BUGS: {:?} \n THIS FUNCTION ONLY OUTPUTS CODE. JUST OUTPUT THE CODE.",
+                    fact_sheet.backend_code, serving_bugs
+                );
+                ai_task_request(
+                    msg_context,
+                    &self.attributes.position,
+                    get_function_string!(print_fixed_code),
+                    print_fixed_code,
+                )
+                .await
+            }
+            None => {
+                let file_names: Vec<&String> = self.frontend_files.keys().collect();
+                let msg_context = format!(
+                    "BACKEND_CODE: {:?} \n FRONTEND_FILES: {:?} \n FRONTEND_DIR: {:?} \n FRONTEND_ROUTE: {} \n",
+                    fact_sheet.backend_code,
+                    file_names,
+                    read_frontend_dir(),
+                    Self::frontend_route()
+                );
+                ai_task_request(
+                    msg_context,
+                    &self.attributes.position,
+                    get_function_string!(print_frontend_served_code),
+                    print_frontend_served_code,
+                )
+                .await
+            }
+        };
+
+        save_backend_code(&ai_response);
+        fact_sheet.backend_code = Some(ai_response);
+        self.is_frontend_served = true;
+    }
+
+    // Build and run the server, then fetch the pages and call the API again;
+    // returns the serving and frontend failures
+    async fn test_frontend(
+        &mut self,
+        fact_sheet: &FactSheet,
+    ) -> Result<(Vec<String>, Vec<String>), Box<dyn std::error::Error>> {
+        let web_server_project_absolute_path = read_web_server_project_path();
+        PrintCommand::UnitTest.print_agent_message(
+            &self.attributes.position,
+            "Frontend unit testing: Building project...",
+        );
+        let build_timeout = Duration::from_secs(read_env_or("BUILD_TIMEOUT_SECS", 300));
+        let build_backend_server = run_command_with_timeout(
+            "cargo",
+            &[String::from("build")],
+            &web_server_project_absolute_path,
+            build_timeout,
+        )
+        .await?;
+        if !build_backend_server.status.success() {
+            PrintCommand::Issue.print_agent_message(
+                &self.attributes.position,
+                "Frontend unit testing: Build failed after serving the frontend",
+            );
+            let error_arr = String::from_utf8_lossy(&build_backend_server.stderr).to_string();
+            return Ok((vec![error_arr], vec![]));
+        }
+
+        let (server_host, server_port) = read_server_address()?;
+        let base_url = resolve_base_url(
+            fact_sheet.backend_code.as_deref().unwrap_or_default(),
+            &server_host,
+            server_port,
+        );
+        let server_log_path = self
+            .server_log_path
+            .get_or_insert_with(create_server_log_path)
+            .clone();
+        let frontend_dir = read_frontend_dir().to_string_lossy().to_string();
+        let run_timeout = Duration::from_secs(read_env_or("RUN_TIMEOUT_SECS", 120));
+        let mut run_backend_server = ServerProcess::spawn(
+            "cargo",
+            &[String::from("run")],
+            &web_server_project_absolute_path,
+            &[
+                ("HOST", server_host),
+                ("PORT", server_port.to_string()),
+                ("FRONTEND_DIR", frontend_dir),
+            ],
+            &server_log_path,
+            run_timeout,
+        )?;
+
+        let frontend_route = Self::frontend_route();
+        let probe_url = format!("{}{}", base_url, page_path(&frontend_route, "index.html"));
+        let ready_timeout = Duration::from_secs(read_env_or("SERVER_READY_TIMEOUT_SECS", 60));
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        let readiness = run_backend_server
+            .wait_until_ready(&client, &probe_url, ready_timeout)
+            .await?;
+        if readiness != Readiness::Ready {
+            let server_log = run_backend_server.log().clone();
+            run_backend_server.shutdown().await?;
+            let failure = format!("Web server did not answer on {}", probe_url);
+            let err_msg = format!("Frontend unit testing: {}", failure);
+            PrintCommand::Issue.print_agent_message(&self.attributes.position, &err_msg);
+            return Ok((
+                vec![format!(
+                    "{}\nSERVER LOGS:\n{}",
+                    failure,
+                    server_log.contents()
+                )],
+                vec![],
+            ));
+        }
+
+        PrintCommand::UnitTest.print_agent_message(
+            &self.attributes.position,
+            "Frontend unit testing: Fetching every page...",
+        );
+        let page_checks = check_pages(
+            &client,
+            run_backend_server.log(),
+            &base_url,
+            &frontend_route,
+            &self.frontend_files,
+        )
+        .await;
+
+        // Serving the frontend rewrites the backend, so its routes must still work
+        PrintCommand::UnitTest.print_agent_message(
+            &self.attributes.position,
+            "Frontend unit testing: Checking the API routes still work...",
+        );
+        let routes = fact_sheet.api_endpoint_schema.clone().unwrap_or_default();
+        let protected_routes = fact_sheet.protected_routes.clone().unwrap_or_default();
+        let route_checks = check_routes(
+            &client,
+            &mut run_backend_server,
+            &base_url,
+            &routes,
+            &protected_routes,
+        )
+        .await?;
+        run_backend_server.shutdown().await?;
+
+        let mut serving_reports: Vec<String> = vec![];
+        for page_check in &page_checks {
+            let command = if page_check.is_bug() {
+                PrintCommand::Issue
+            } else {
+                PrintCommand::UnitTest
+            };
+            command.print_agent_message(&self.attributes.position, &page_check.summary());
+            if page_check.is_bug() {
+                serving_reports.push(page_check.bug_report());
+            }
+        }
+        for route_check in route_checks.iter().filter(|check| check.is_bug()) {
+            PrintCommand::Issue
+                .print_agent_message(&self.attributes.position, &route_check.summary());
+            serving_reports.push(format!(
+                "API ROUTE BROKEN WHILE SERVING THE FRONTEND: {}",
+                route_check.bug_report()
+            ));
+        }
+
+        let mut frontend_reports: Vec<String> = vec![];
+        for api_call in unknown_api_calls(&self.frontend_files, &routes, &frontend_route) {
+            PrintCommand::Issue.print_agent_message(&self.attributes.position, &api_call.summary());
+            frontend_reports.push(api_call.bug_report());
+        }
+        Ok((serving_reports, frontend_reports))
+    }
+
+    // Writes, serves and tests the frontend until it passes or the fix budget runs out
+    async fn run_stages(
+        &mut self,
+        fact_sheet: &mut FactSheet,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        while self.attributes.state != AgentState::Finished {
+            match &self.attributes.state {
+                AgentState::Discovery => {
+                    if let Err(e) = self.call_frontend_code(fact_sheet).await {
+                        let err_msg = format!("Frontend code: Unusable reply, {}", e);
+                        PrintCommand::Issue
+                            .print_agent_message(&self.attributes.position, &err_msg);
+                        if !self.try_fix() {
+                            self.attributes.state = AgentState::Finished;
+                            return Err("The frontend could not be generated".into());
+                        }
+                        continue;
+                    }
+                    let frontend_dir = save_frontend_files(&self.frontend_files)?;
+                    let written_msg = format!(
+                        "Frontend code: {} files written to {}",
+                        self.frontend_files.len(),
+                        frontend_dir.display()
+                    );
+                    PrintCommand::UnitTest
+                        .print_agent_message(&self.attributes.position, &written_msg);
+
+                    self.attributes.state = if self.is_frontend_served {
+                        AgentState::UnitTesting
+                    } else {
+                        AgentState::Working
+                    };
+                }
+                AgentState::Working => {
+                    self.call_serve_frontend(fact_sheet).await;
+                    self.attributes.state = AgentState::UnitTesting;
+                }
+                AgentState::UnitTesting => {
+                    PrintCommand::UnitTest.print_agent_message(
+                        &self.attributes.position,
+                        "Frontend unit testing: Requesting user input",
+                    );
+                    let is_safe_code = confirm_safe_code();
+                    if !is_safe_code {
+                        println!("Better go work on some AI alignment instead...");
+                    }
+
+                    let (serving_reports, frontend_reports) =
+                        self.test_frontend(fact_sheet).await?;
+                    if serving_reports.is_empty() && frontend_reports.is_empty() {
+                        PrintCommand::UnitTest.print_agent_message(
+                            &self.attributes.position,
+                            "Frontend testing completed...",
+                        );
+                        self.attributes.state = AgentState::Finished;
+                        continue;
+                    }
+
+                    if !self.try_fix() {
+                        PrintCommand::Issue.print_agent_message(
+                            &self.attributes.position,
+                            "Frontend unit testing: Fix budget exhausted, frontend still failing",
+                        );
+                        self.attributes.state = AgentState::Finished;
+                        return Err("The generated frontend does not pass its checks".into());
+                    }
+                    // Serving is fixed first, the pages can only be checked once they load
+                    if !serving_reports.is_empty() {
+                        PrintCommand::Issue.print_agent_message(
+                            &self.attributes.position,
+                            "Frontend unit testing: Serving failures sent back for fixing",
+                        );
+                        self.serving_bugs = Some(runtime_bug_report(&serving_reports));
+                        self.attributes.state = AgentState::Working;
+                    } else {
+                        PrintCommand::Issue.print_agent_message(
+                            &self.attributes.position,
+                            "Frontend unit testing: Unknown API calls sent back for fixing",
+                        );
+                        self.frontend_bugs = Some(frontend_reports.join("\n"));
+                        self.attributes.state = AgentState::Discovery;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SpecialFunctions for AgentFrontendDeveloper {
+    fn get_attributes_from_agent(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    async fn execute(
+        &mut self,
+        fact_sheet: &mut FactSheet,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if fact_sheet.api_endpoint_schema.is_none() {
+            return Err("No API endpoints to build the frontend against".into());
+        }
+        // Restored on any error exit, so a failed frontend never leaves the backend changed
+        let original_backend_code = fact_sheet.backend_code.clone();
+
+        let result = self.run_stages(fact_sheet).await;
+        if result.is_err() && fact_sheet.backend_code != original_backend_code {
+            if let Some(original_backend_code) = original_backend_code {
+                save_backend_code(&original_backend_code);
+                fact_sheet.backend_code = Some(original_backend_code);
+            }
+        }
+        result
+    }
+}
@@ -1,8 +1,10 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use std::{collections::BTreeMap, fmt::Debug};
 
-use crate::models::basic_agent::BasicAgent;
+use crate::models::{basic_agent::BasicAgent, DataModel};
+
+pub use super::route_schema::RouteObject;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct ProjectScope {
@@ -12,25 +14,82 @@ pub struct ProjectScope {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-pub struct RouteObject {
-    pub is_route_dynamic: String,
+pub struct RouteTestResult {
     pub method: String,
-    pub request_body: serde_json::Value,
-    pub response: serde_json::Value,
     pub route: String,
+    pub status: Option<u16>,
+    pub passed: bool,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RouteLoadResult {
+    pub method: String,
+    pub route: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub requests_per_sec: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExternalApiSample {
+    pub url: String,
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExternalApiProfile {
+    pub url: String,
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    pub content_type: Option<String>,
+    pub redirects: Vec<String>,
+    pub rate_limit_headers: BTreeMap<String, String>,
+    pub cors_allow_origin: Option<String>,
+    pub sample_payload: Option<serde_json::Value>,
+    pub payload_shape: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContractOperation {
+    pub route: RouteObject,
+    pub success_statuses: Vec<u16>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiContract {
+    pub title: String,
+    pub description: String,
+    pub document: serde_json::Value,
+    pub operations: Vec<ContractOperation>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct FactSheet {
     pub project_description: String,
     pub project_scope: Option<ProjectScope>,
+    pub api_contract: Option<ApiContract>,
     pub external_urls: Option<Vec<String>>,
+    pub external_api_samples: Option<Vec<ExternalApiSample>>,
+    pub external_api_profiles: Option<Vec<ExternalApiProfile>>,
+    pub data_model: Option<DataModel>,
+    pub protected_routes: Option<Vec<String>>,
     pub backend_code: Option<String>,
     pub api_endpoint_schema: Option<Vec<RouteObject>>,
+    pub endpoint_test_results: Option<Vec<RouteTestResult>>,
+    pub load_test_results: Option<Vec<RouteLoadResult>>,
 }
 
 #[async_trait]
 pub trait SpecialFunctions: Debug {
+    #[allow(dead_code)]
     fn get_attributes_from_agent(&self) -> &BasicAgent;
     async fn execute(
         &mut self,
@@ -0,0 +1,184 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+// Column type of an entity field, portable between SQLite and Postgres
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub enum ColumnType {
+    Integer,
+    Real,
+    Text,
+    Boolean,
+    Timestamp,
+}
+
+impl ColumnType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ColumnType::Integer => "integer",
+            ColumnType::Real => "real",
+            ColumnType::Text => "text",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Timestamp => "timestamp",
+        }
+    }
+}
+
+impl FromStr for ColumnType {
+    type Err = String;
+
+    // Accepts the Rust, JSON and SQL names the model tends to use
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind.trim().to_lowercase().as_str() {
+            "integer" | "int" | "bigint" | "serial" | "i32" | "i64" | "u32" | "u64" | "usize" => {
+                Ok(ColumnType::Integer)
+            }
+            "real" | "number" | "float" | "double" | "decimal" | "numeric" | "f32" | "f64" => {
+                Ok(ColumnType::Real)
+            }
+            "text" | "string" | "str" | "varchar" | "char" | "uuid" | "email" => {
+                Ok(ColumnType::Text)
+            }
+            "boolean" | "bool" => Ok(ColumnType::Boolean),
+            "timestamp" | "datetime" | "date" | "time" | "timestamptz" => Ok(ColumnType::Timestamp),
+            _ => Err(format!("unknown column type {:?}", kind)),
+        }
+    }
+}
+
+impl TryFrom<String> for ColumnType {
+    type Error = String;
+
+    fn try_from(kind: String) -> Result<Self, Self::Error> {
+        kind.parse()
+    }
+}
+
+impl From<ColumnType> for String {
+    fn from(kind: ColumnType) -> Self {
+        kind.as_str().to_string()
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EntityField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: ColumnType,
+    #[serde(default)]
+    pub is_optional: bool,
+    #[serde(default)]
+    pub is_unique: bool,
+}
+
+// A table; every entity also gets an integer id primary key
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Entity {
+    pub name: String,
+    pub fields: Vec<EntityField>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationshipKind {
+    // The "to" entity gets a unique reference to the "from" entity
+    OneToOne,
+    // The "to" entity gets a reference to the "from" entity
+    OneToMany,
+    // A join table references both entities
+    ManyToMany,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawRelationship {
+    from: String,
+    to: String,
+    kind: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "RawRelationship")]
+pub struct Relationship {
+    pub from: String,
+    pub to: String,
+    pub kind: RelationshipKind,
+}
+
+impl TryFrom<RawRelationship> for Relationship {
+    type Error = String;
+
+    // Many-to-one is read as one-to-many in the other direction
+    fn try_from(raw: RawRelationship) -> Result<Self, Self::Error> {
+        let kind = raw.kind.trim().to_lowercase().replace(['-', ' '], "_");
+        let (from, to, kind) = match kind.as_str() {
+            "one_to_one" | "1:1" | "has_one" => (raw.from, raw.to, RelationshipKind::OneToOne),
+            "one_to_many" | "1:n" | "has_many" => (raw.from, raw.to, RelationshipKind::OneToMany),
+            "many_to_one" | "n:1" | "belongs_to" => (raw.to, raw.from, RelationshipKind::OneToMany),
+            "many_to_many" | "n:m" | "m:n" => (raw.from, raw.to, RelationshipKind::ManyToMany),
+            _ => return Err(format!("unknown relationship kind {:?}", raw.kind)),
+        };
+        Ok(Relationship { from, to, kind })
+    }
+}
+
+// Entity/relationship model of the project's data
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DataModel {
+    pub entities: Vec<Entity>,
+    #[serde(default)]
+    pub relationships: Vec<Relationship>,
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_data_model_lenient_parsing() {
+        let model: DataModel = serde_json::from_value(json!({
+            "entities": [
+                {"name": "User", "fields": [
+                    {"name": "email", "type": "String", "is_unique": true},
+                    {"name": "age", "type": "u32", "is_optional": true}
+                ]},
+                {"name": "Post", "fields": [{"name": "published_at", "type": "DateTime"}]}
+            ],
+            "relationships": [{"from": "Post", "to": "User", "kind": "many-to-one"}]
+        }))
+        .unwrap();
+
+        let user = &model.entities[0];
+        assert_eq!(user.fields[0].column_type, ColumnType::Text);
+        assert!(user.fields[0].is_unique && !user.fields[0].is_optional);
+        assert_eq!(user.fields[1].column_type, ColumnType::Integer);
+        assert_eq!(
+            model.entities[1].fields[0].column_type,
+            ColumnType::Timestamp
+        );
+        assert_eq!(
+            model.relationships,
+            vec![Relationship {
+                from: String::from("User"),
+                to: String::from("Post"),
+                kind: RelationshipKind::OneToMany,
+            }]
+        );
+        assert_eq!(
+            serde_json::to_value(&model.relationships[0]).unwrap(),
+            json!({"from": "User", "to": "Post", "kind": "one_to_many"})
+        );
+
+        let unknown_type =
+            json!({"entities": [{"name": "A", "fields": [{"name": "b", "type": "blob"}]}]});
+        assert!(serde_json::from_value::<DataModel>(unknown_type).is_err());
+    }
+}
@@ -0,0 +1,407 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Head,
+    Options,
+}
+
+impl HttpMethod {
+    // Lowercase name, as the extraction prompt writes it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HttpMethod::Get => "get",
+            HttpMethod::Post => "post",
+            HttpMethod::Put => "put",
+            HttpMethod::Patch => "patch",
+            HttpMethod::Delete => "delete",
+            HttpMethod::Head => "head",
+            HttpMethod::Options => "options",
+        }
+    }
+
+    pub fn to_method(self) -> Method {
+        match self {
+            HttpMethod::Get => Method::GET,
+            HttpMethod::Post => Method::POST,
+            HttpMethod::Put => Method::PUT,
+            HttpMethod::Patch => Method::PATCH,
+            HttpMethod::Delete => Method::DELETE,
+            HttpMethod::Head => Method::HEAD,
+            HttpMethod::Options => Method::OPTIONS,
+        }
+    }
+}
+
+impl FromStr for HttpMethod {
+    type Err = String;
+
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        match method.trim().to_lowercase().as_str() {
+            "get" => Ok(HttpMethod::Get),
+            "post" => Ok(HttpMethod::Post),
+            "put" => Ok(HttpMethod::Put),
+            "patch" => Ok(HttpMethod::Patch),
+            "delete" => Ok(HttpMethod::Delete),
+            "head" => Ok(HttpMethod::Head),
+            "options" => Ok(HttpMethod::Options),
+            _ => Err(format!("unknown http method {:?}", method)),
+        }
+    }
+}
+
+impl TryFrom<String> for HttpMethod {
+    type Error = String;
+
+    fn try_from(method: String) -> Result<Self, Self::Error> {
+        method.parse()
+    }
+}
+
+impl From<HttpMethod> for String {
+    fn from(method: HttpMethod) -> Self {
+        method.as_str().to_string()
+    }
+}
+
+// Uppercase, as used in messages such as "GET /items"
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str().to_uppercase())
+    }
+}
+
+// Type of a request or response body, parsed from the sketch the extraction step writes
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(from = "Value", into = "Value")]
+pub enum FieldSchema {
+    // "None": there is no body
+    #[default]
+    Empty,
+    // "not_provided" or any other sketch that promises nothing
+    Unknown,
+    Number,
+    String,
+    Bool,
+    Optional(Box<FieldSchema>),
+    Array(Box<FieldSchema>),
+    // An empty object means "any object"
+    Object(BTreeMap<String, FieldSchema>),
+    // Struct names and other kinds that cannot be checked
+    Named(String),
+}
+
+impl FieldSchema {
+    // Parse a scalar kind such as "u64", "Option<string>" or "Vec<Item>"
+    pub fn from_kind(kind: &str) -> Self {
+        let kind = kind.trim();
+        let lower = kind.to_lowercase();
+        // Inner kind of a generic such as Option<..>, keeping the original case
+        let generic_inner = |prefix: &str| {
+            if lower.starts_with(prefix) && lower.ends_with('>') {
+                kind.get(prefix.len()..kind.len() - 1)
+            } else {
+                None
+            }
+        };
+
+        if let Some(inner) = generic_inner("option<") {
+            return FieldSchema::Optional(Box::new(FieldSchema::from_kind(inner)));
+        }
+        if let Some(inner) = kind.strip_suffix('?') {
+            return FieldSchema::Optional(Box::new(FieldSchema::from_kind(inner)));
+        }
+        if let Some(inner) = generic_inner("vec<") {
+            return FieldSchema::Array(Box::new(FieldSchema::from_kind(inner)));
+        }
+        match lower.as_str() {
+            "" | "none" | "null" => FieldSchema::Empty,
+            "not_provided" | "any" | "unknown" => FieldSchema::Unknown,
+            "number" | "integer" | "int" | "float" | "u8" | "u16" | "u32" | "u64" | "usize"
+            | "i8" | "i16" | "i32" | "i64" | "isize" | "f32" | "f64" => FieldSchema::Number,
+            "string" | "str" | "char" | "date" | "datetime" => FieldSchema::String,
+            "bool" | "boolean" => FieldSchema::Bool,
+            "object" | "map" | "hashmap" => FieldSchema::Object(BTreeMap::new()),
+            "array" | "vec" | "list" => FieldSchema::Array(Box::new(FieldSchema::Unknown)),
+            _ => FieldSchema::Named(kind.to_string()),
+        }
+    }
+
+    // Whether there is no body, or nothing is known about it
+    pub fn is_unchecked(&self) -> bool {
+        matches!(
+            self,
+            FieldSchema::Empty | FieldSchema::Unknown | FieldSchema::Named(_)
+        )
+    }
+
+    // Normalised sketch, in the form the extraction prompt uses
+    pub fn to_sketch(&self) -> Value {
+        match self {
+            FieldSchema::Empty => Value::from("None"),
+            FieldSchema::Unknown => Value::from("not_provided"),
+            FieldSchema::Number => Value::from("number"),
+            FieldSchema::String => Value::from("string"),
+            FieldSchema::Bool => Value::from("bool"),
+            FieldSchema::Optional(inner) => match inner.to_sketch() {
+                Value::String(kind) => Value::from(format!("Option<{}>", kind)),
+                // Objects and arrays have no optional form in a sketch
+                sketch => sketch,
+            },
+            FieldSchema::Array(item) => match item.as_ref() {
+                FieldSchema::Unknown => Value::Array(vec![]),
+                item => Value::Array(vec![item.to_sketch()]),
+            },
+            FieldSchema::Object(fields) if fields.is_empty() => Value::from("object"),
+            FieldSchema::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(key, field)| (key.clone(), field.to_sketch()))
+                    .collect::<Map<String, Value>>(),
+            ),
+            FieldSchema::Named(name) => Value::from(name.as_str()),
+        }
+    }
+}
+
+impl From<&Value> for FieldSchema {
+    fn from(sketch: &Value) -> Self {
+        match sketch {
+            Value::Null => FieldSchema::Empty,
+            Value::Bool(_) => FieldSchema::Bool,
+            Value::Number(_) => FieldSchema::Number,
+            Value::String(kind) => FieldSchema::from_kind(kind),
+            Value::Array(items) => FieldSchema::Array(Box::new(
+                items
+                    .first()
+                    .map(FieldSchema::from)
+                    .unwrap_or(FieldSchema::Unknown),
+            )),
+            Value::Object(fields) => FieldSchema::Object(
+                fields
+                    .iter()
+                    .map(|(key, field)| (key.clone(), FieldSchema::from(field)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<Value> for FieldSchema {
+    fn from(sketch: Value) -> Self {
+        FieldSchema::from(&sketch)
+    }
+}
+
+impl From<FieldSchema> for Value {
+    fn from(schema: FieldSchema) -> Self {
+        schema.to_sketch()
+    }
+}
+
+impl fmt::Display for FieldSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_sketch())
+    }
+}
+
+// Names of the {placeholders} in a route
+pub fn path_params(route: &str) -> Vec<String> {
+    route
+        .split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+// Accepts true, "true" or "True", as the extraction prompt has written all three
+fn lenient_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Bool(flag) => flag,
+        Value::String(flag) => flag.trim().eq_ignore_ascii_case("true"),
+        _ => false,
+    })
+}
+
+// RouteObject as written by the extraction step or an older schema file
+#[derive(Deserialize)]
+struct RawRouteObject {
+    route: String,
+    method: HttpMethod,
+    #[serde(default, deserialize_with = "lenient_bool")]
+    is_route_dynamic: bool,
+    #[serde(default)]
+    request_body: FieldSchema,
+    #[serde(default)]
+    response: FieldSchema,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(from = "RawRouteObject")]
+pub struct RouteObject {
+    pub route: String,
+    pub method: HttpMethod,
+    pub is_route_dynamic: bool,
+    pub path_params: Vec<String>,
+    pub request_body: FieldSchema,
+    pub response: FieldSchema,
+}
+
+impl RouteObject {
+    pub fn new(
+        method: HttpMethod,
+        route: &str,
+        request_body: FieldSchema,
+        response: FieldSchema,
+    ) -> Self {
+        let path_params = path_params(route);
+        Self {
+            route: route.to_string(),
+            method,
+            is_route_dynamic: !path_params.is_empty(),
+            path_params,
+            request_body,
+            response,
+        }
+    }
+}
+
+impl From<RawRouteObject> for RouteObject {
+    fn from(raw: RawRouteObject) -> Self {
+        let mut route = RouteObject::new(raw.method, &raw.route, raw.request_body, raw.response);
+        route.is_route_dynamic |= raw.is_route_dynamic;
+        route
+    }
+}
+
+// Routes that decode, and a note for every entry that does not, such as one with an unknown method
+pub fn decode_routes(json: &str) -> (Vec<RouteObject>, Vec<String>) {
+    let entries: Vec<Value> = match serde_json::from_str(json) {
+        Ok(entries) => entries,
+        Err(e) => return (vec![], vec![format!("not a list of routes ({})", e)]),
+    };
+    let mut routes: Vec<RouteObject> = vec![];
+    let mut skipped: Vec<String> = vec![];
+    for entry in entries {
+        match serde_json::from_value::<RouteObject>(entry.clone()) {
+            Ok(route) => routes.push(route),
+            Err(e) => skipped.push(format!("skipped {} ({})", entry, e)),
+        }
+    }
+    (routes, skipped)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_lenient_route_object() {
+        let raw = json!([
+            {
+                "is_route_dynamic": "true",
+                "method": "GET",
+                "request_body": "None",
+                "response": {"id": "u64", "tags": ["string"], "note": "Option<String>"},
+                "route": "/item/{id}"
+            },
+            {
+                "is_route_dynamic": false,
+                "method": " post ",
+                "request_body": {"name": "string", "done": "boolean"},
+                "route": "/item"
+            }
+        ]);
+
+        let routes: Vec<RouteObject> = serde_json::from_value(raw).unwrap();
+        assert_eq!(routes[0].method, HttpMethod::Get);
+        assert!(routes[0].is_route_dynamic);
+        assert_eq!(routes[0].path_params, vec!["id"]);
+        assert_eq!(routes[0].request_body, FieldSchema::Empty);
+        assert_eq!(
+            routes[0].response,
+            FieldSchema::Object(BTreeMap::from([
+                (String::from("id"), FieldSchema::Number),
+                (
+                    String::from("note"),
+                    FieldSchema::Optional(Box::new(FieldSchema::String))
+                ),
+                (
+                    String::from("tags"),
+                    FieldSchema::Array(Box::new(FieldSchema::String))
+                ),
+            ]))
+        );
+        assert_eq!(routes[1].method, HttpMethod::Post);
+        assert!(!routes[1].is_route_dynamic);
+        assert_eq!(routes[1].response, FieldSchema::Empty);
+
+        assert!(
+            serde_json::from_value::<RouteObject>(json!({"method": "fetch", "route": "/"}))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_normalised_route_object() {
+        let route = RouteObject::new(
+            HttpMethod::Put,
+            "/item/{id}",
+            FieldSchema::from(&json!({"name": "str", "price": "f64", "owner": "User"})),
+            FieldSchema::from(&json!("vec<i32>")),
+        );
+
+        let normalised = serde_json::to_value(&route).unwrap();
+        assert_eq!(
+            normalised,
+            json!({
+                "route": "/item/{id}",
+                "method": "put",
+                "is_route_dynamic": true,
+                "path_params": ["id"],
+                "request_body": {"name": "string", "owner": "User", "price": "number"},
+                "response": ["number"]
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<RouteObject>(normalised).unwrap(),
+            route
+        );
+        assert_eq!(HttpMethod::Delete.to_string(), "DELETE");
+    }
+
+    #[test]
+    fn test_decode_routes() {
+        let (routes, skipped) = decode_routes(
+            r#"[
+                {"route": "/items", "method": "get"},
+                {"route": "/{tail}", "method": "ANY"},
+                {"method": "post"}
+            ]"#,
+        );
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].route, "/items");
+        assert_eq!(skipped.len(), 2);
+        assert!(skipped[0].contains("unknown http method \"ANY\""));
+
+        let (routes, skipped) = decode_routes("no routes here");
+        assert!(routes.is_empty());
+        assert!(skipped[0].starts_with("not a list of routes"));
+        assert_eq!(path_params("/item/{id}/tags/{tag}"), vec!["id", "tag"]);
+    }
+}
@@ -5,6 +5,11 @@ mod general;
 
 pub use agent_basic::{basic_agent, basic_trait};
 pub use agent_manager::managing_agent::ManagingAgent;
-pub use agents::agent_traits::FactSheet;
-pub use agents::{agent_architect, agent_backend};
+pub use agents::agent_traits::{
+    ApiContract, ContractOperation, ExternalApiProfile, ExternalApiSample, FactSheet,
+    RouteLoadResult, RouteObject, RouteTestResult,
+};
+pub use agents::data_model::{ColumnType, DataModel, RelationshipKind};
+pub use agents::route_schema::{FieldSchema, HttpMethod};
+pub use agents::{agent_architect, agent_auth, agent_backend, agent_database, agent_frontend};
 pub use general::llm::{APIResponse, ChatCompletion, Message};
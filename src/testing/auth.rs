@@ -0,0 +1,577 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION, COOKIE, SET_COOKIE},
+    Client, Method,
+};
+use serde_json::{Map, Value};
+
+use crate::{
+    analysis::actix_routes::path_key,
+    helpers::process::ServerLog,
+    models::{FieldSchema, RouteObject},
+    testing::{
+        endpoint::{send_request_with_headers, EndpointResult},
+        payload::{build_request_body, fill_route_placeholders, method_rank},
+    },
+};
+
+const SIGNUP_KEYWORDS: [&str; 4] = ["signup", "sign_up", "sign-up", "register"];
+const LOGIN_KEYWORDS: [&str; 4] = ["login", "signin", "sign_in", "sign-in"];
+const LOGOUT_KEYWORDS: [&str; 4] = ["logout", "signout", "sign_out", "sign-out"];
+const TOKEN_KEYS: [&str; 4] = ["token", "access_token", "accesstoken", "jwt"];
+
+// Session picked up from the login response
+#[derive(Debug, Clone, PartialEq)]
+pub enum Credentials {
+    Cookie(String),
+    Bearer(String),
+}
+
+impl Credentials {
+    pub fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let (name, value) = match self {
+            Self::Cookie(cookie) => (COOKIE, cookie.clone()),
+            Self::Bearer(token) => (AUTHORIZATION, format!("Bearer {}", token)),
+        };
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(name, value);
+        }
+        headers
+    }
+}
+
+// Routes that make up the sign up, login and logout flow
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthRoutes {
+    pub signup: Option<RouteObject>,
+    pub login: RouteObject,
+    pub logout: Option<RouteObject>,
+}
+
+impl AuthRoutes {
+    fn contains(&self, route: &RouteObject) -> bool {
+        [
+            Some(&self.login),
+            self.signup.as_ref(),
+            self.logout.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|auth_route| auth_route == route)
+    }
+}
+
+// One expectation of the auth flow
+#[derive(Debug, Clone)]
+pub struct AuthCheck {
+    pub description: String,
+    pub passed: bool,
+    pub result: Option<EndpointResult>,
+}
+
+impl AuthCheck {
+    pub fn summary(&self) -> String {
+        let verdict = if self.passed { "PASS" } else { "FAIL" };
+        format!("{} AUTH {}", verdict, self.description)
+    }
+
+    pub fn bug_report(&self) -> Option<String> {
+        if self.passed {
+            return None;
+        }
+        let request = self
+            .result
+            .as_ref()
+            .map(|result| result.bug_report())
+            .unwrap_or_default();
+        Some(format!(
+            "AUTH FLOW CHECK FAILED: {} \n {}",
+            self.description, request
+        ))
+    }
+}
+
+fn route_matches(route: &RouteObject, keywords: &[&str]) -> bool {
+    let path = route.route.to_lowercase();
+    keywords.iter().any(|keyword| path.contains(keyword))
+}
+
+// Find the auth routes by their names; login is required
+pub fn find_auth_routes(routes: &[RouteObject]) -> Option<AuthRoutes> {
+    let find = |keywords: &[&str], methods: &[Method]| {
+        routes
+            .iter()
+            .find(|route| {
+                route_matches(route, keywords) && methods.contains(&route.method.to_method())
+            })
+            .cloned()
+    };
+    Some(AuthRoutes {
+        signup: find(&SIGNUP_KEYWORDS, &[Method::POST]),
+        login: find(&LOGIN_KEYWORDS, &[Method::POST])?,
+        logout: find(
+            &LOGOUT_KEYWORDS,
+            &[Method::POST, Method::GET, Method::DELETE],
+        ),
+    })
+}
+
+// Unique username, email and password for this test run
+pub fn test_user() -> HashMap<&'static str, String> {
+    let suffix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    HashMap::from([
+        ("username", format!("benjamin_test_{}", suffix)),
+        ("email", format!("benjamin_test_{}@example.com", suffix)),
+        ("password", String::from("Benjamin-Test-Pass-1")),
+    ])
+}
+
+// Request body for an auth route with the test user's details filled in
+pub fn credentials_payload(sketch: &FieldSchema, user: &HashMap<&'static str, String>) -> Value {
+    let mut payload = match build_request_body(sketch) {
+        Some(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+    if payload.is_empty() {
+        payload.insert(String::from("username"), Value::Null);
+        payload.insert(String::from("password"), Value::Null);
+    }
+    for (key, value) in payload.iter_mut() {
+        let key = key.to_lowercase();
+        let user_value = if key.contains("password") {
+            user.get("password")
+        } else if key.contains("email") {
+            user.get("email")
+        } else if key.contains("user") || key == "name" || key == "login" {
+            user.get("username")
+        } else {
+            None
+        };
+        if let Some(user_value) = user_value {
+            *value = Value::String(user_value.clone());
+        }
+    }
+    Value::Object(payload)
+}
+
+fn find_token(value: &Value) -> Option<String> {
+    match value {
+        Value::Object(fields) => fields.iter().find_map(|(key, field)| {
+            match (TOKEN_KEYS.contains(&key.to_lowercase().as_str()), field) {
+                (true, Value::String(token)) if !token.is_empty() => Some(token.clone()),
+                _ => find_token(field),
+            }
+        }),
+        _ => None,
+    }
+}
+
+// Session cookie or bearer token handed out by a login response
+pub fn extract_credentials(result: &EndpointResult) -> Option<Credentials> {
+    let cookies: Vec<String> = result
+        .headers
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|cookie| cookie.to_str().ok())
+        .filter_map(|cookie| cookie.split(';').next())
+        .map(|cookie| cookie.trim().to_string())
+        .collect();
+    if !cookies.is_empty() {
+        return Some(Credentials::Cookie(cookies.join("; ")));
+    }
+
+    let body: Value = serde_json::from_str(&result.body).ok()?;
+    find_token(&body).map(Credentials::Bearer)
+}
+
+fn is_refused(result: &EndpointResult) -> bool {
+    matches!(result.status, Some(401) | Some(403))
+}
+
+struct AuthFlow<'a> {
+    client: &'a Client,
+    server_log: &'a ServerLog,
+    base_url: &'a str,
+    checks: Vec<AuthCheck>,
+}
+
+impl AuthFlow<'_> {
+    async fn send(
+        &self,
+        route: &RouteObject,
+        body: Option<&Value>,
+        headers: &HeaderMap,
+    ) -> EndpointResult {
+        let method = route.method.to_method();
+        let path = fill_route_placeholders(&route.route, &HashMap::new());
+        send_request_with_headers(
+            self.client,
+            self.server_log,
+            self.base_url,
+            method,
+            &path,
+            body,
+            headers,
+        )
+        .await
+    }
+
+    fn record(&mut self, description: String, passed: bool, result: Option<EndpointResult>) {
+        self.checks.push(AuthCheck {
+            description,
+            passed,
+            result,
+        });
+    }
+}
+
+// Sign up and log in a fresh test user, for the tests that call protected routes
+pub async fn open_session(
+    client: &Client,
+    server_log: &ServerLog,
+    base_url: &str,
+    routes: &[RouteObject],
+) -> Option<Credentials> {
+    let auth_routes = find_auth_routes(routes)?;
+    let flow = AuthFlow {
+        client,
+        server_log,
+        base_url,
+        checks: vec![],
+    };
+    let user = test_user();
+    let no_credentials = HeaderMap::new();
+
+    if let Some(signup) = &auth_routes.signup {
+        let payload = credentials_payload(&signup.request_body, &user);
+        flow.send(signup, Some(&payload), &no_credentials).await;
+    }
+    let payload = credentials_payload(&auth_routes.login.request_body, &user);
+    let login = flow
+        .send(&auth_routes.login, Some(&payload), &no_credentials)
+        .await;
+    if !login.passed() {
+        return None;
+    }
+    extract_credentials(&login)
+}
+
+// Every route but logout, which would end a session shared across the tests
+pub fn session_safe_routes(routes: &[RouteObject]) -> Vec<RouteObject> {
+    let logout = find_auth_routes(routes).and_then(|auth_routes| auth_routes.logout);
+    routes
+        .iter()
+        .filter(|route| Some(*route) != logout.as_ref())
+        .cloned()
+        .collect()
+}
+
+// Sign up, log in, probe protected routes with and without the session, then log out
+pub async fn run_auth_flow(
+    client: &Client,
+    server_log: &ServerLog,
+    base_url: &str,
+    routes: &[RouteObject],
+    protected_routes: &[String],
+) -> Vec<AuthCheck> {
+    let mut flow = AuthFlow {
+        client,
+        server_log,
+        base_url,
+        checks: vec![],
+    };
+    let Some(auth_routes) = find_auth_routes(routes) else {
+        flow.record(String::from("a login route exists"), false, None);
+        return flow.checks;
+    };
+    let user = test_user();
+    let no_credentials = HeaderMap::new();
+
+    if let Some(signup) = &auth_routes.signup {
+        let payload = credentials_payload(&signup.request_body, &user);
+        let result = flow.send(signup, Some(&payload), &no_credentials).await;
+        let description = format!("sign up through {}", signup.route);
+        flow.record(description, result.passed(), Some(result));
+    }
+
+    let payload = credentials_payload(&auth_routes.login.request_body, &user);
+    let login = flow
+        .send(&auth_routes.login, Some(&payload), &no_credentials)
+        .await;
+    let description = format!("log in through {}", auth_routes.login.route);
+    if !login.passed() {
+        flow.record(description, false, Some(login));
+        return flow.checks;
+    }
+    let Some(credentials) = extract_credentials(&login) else {
+        let description = format!(
+            "{} returns a session cookie or token",
+            auth_routes.login.route
+        );
+        flow.record(description, false, Some(login));
+        return flow.checks;
+    };
+    flow.record(description, true, Some(login));
+    let session = credentials.headers();
+
+    let protected_keys: Vec<String> = protected_routes.iter().map(|path| path_key(path)).collect();
+    for (path, key) in protected_routes.iter().zip(&protected_keys) {
+        if !routes.iter().any(|route| &path_key(&route.route) == key) {
+            let description = format!("protected route {} is served", path);
+            flow.record(description, false, None);
+        }
+    }
+
+    // Probe every other route without and with the session
+    let mut candidates: Vec<&RouteObject> = routes
+        .iter()
+        .filter(|route| !auth_routes.contains(route))
+        .collect();
+    candidates.sort_by_key(|route| method_rank(&route.method.to_method()));
+
+    let mut protected: Vec<&RouteObject> = vec![];
+    for route in candidates {
+        let body = build_request_body(&route.request_body);
+        let anonymous = flow.send(route, body.as_ref(), &no_credentials).await;
+        let declared = protected_keys.contains(&path_key(&route.route));
+        if !is_refused(&anonymous) {
+            if declared {
+                let description = format!(
+                    "{} {} refuses unauthenticated requests",
+                    route.method, route.route
+                );
+                flow.record(description, false, Some(anonymous));
+            }
+            continue;
+        }
+
+        let authenticated = flow.send(route, body.as_ref(), &session).await;
+        let description = format!(
+            "{} {} accepts the logged in session",
+            route.method, route.route
+        );
+        // A refusal or a server error with a valid session both fail the check
+        let accepted = !is_refused(&authenticated);
+        flow.record(description, authenticated.passed(), Some(authenticated));
+        if accepted {
+            protected.push(route);
+        }
+    }
+
+    let Some(logout) = &auth_routes.logout else {
+        return flow.checks;
+    };
+    let result = flow.send(logout, None, &session).await;
+    let description = format!("log out through {}", logout.route);
+    if !result.passed() {
+        flow.record(description, false, Some(result));
+        return flow.checks;
+    }
+    flow.record(description, true, Some(result));
+
+    for route in protected {
+        let body = build_request_body(&route.request_body);
+        let after_logout = flow.send(route, body.as_ref(), &session).await;
+        let description = format!(
+            "{} {} refuses the session after logout",
+            route.method, route.route
+        );
+        flow.record(description, is_refused(&after_logout), Some(after_logout));
+    }
+    flow.checks
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::test_support::{spawn_fake_server, test_route as route, FakeResponse};
+
+    fn auth_routes() -> Vec<RouteObject> {
+        let user_sketch = json!({"username": "string", "password": "string"});
+        vec![
+            route("post", "/signup", user_sketch.clone()),
+            route("post", "/login", user_sketch),
+            route("post", "/logout", json!("None")),
+            route("get", "/profile", json!("None")),
+            route("get", "/health", json!("None")),
+        ]
+    }
+
+    // Cookie session server; keep_sessions simulates a logout that does nothing
+    async fn spawn_auth_server(keep_sessions: bool) -> String {
+        let sessions: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+        spawn_fake_server(move |request| {
+            let mut sessions = sessions.lock().unwrap();
+            let cookie = request.headers.get("cookie").cloned().unwrap_or_default();
+            let logged_in = sessions.contains(&cookie);
+            match request.path.as_str() {
+                "/signup" => FakeResponse::new(201, ""),
+                "/login" if request.body.contains("benjamin_test_") => {
+                    sessions.push(String::from("session=abc"));
+                    FakeResponse::new(200, "").with_header("set-cookie", "session=abc; HttpOnly")
+                }
+                "/logout" if logged_in => {
+                    if !keep_sessions {
+                        sessions.clear();
+                    }
+                    FakeResponse::new(200, "")
+                }
+                "/profile" if logged_in => FakeResponse::json(200, json!({"name": "benjamin"})),
+                "/report" if logged_in => FakeResponse::new(500, "boom"),
+                "/health" => FakeResponse::new(200, "ok"),
+                _ => FakeResponse::new(401, "unauthorized"),
+            }
+        })
+        .await
+    }
+
+    #[test]
+    fn test_find_auth_routes() {
+        let found = find_auth_routes(&auth_routes()).expect("Expected auth routes");
+        assert_eq!(found.login.route, "/login");
+        assert_eq!(found.signup.map(|r| r.route), Some(String::from("/signup")));
+        assert_eq!(found.logout.map(|r| r.route), Some(String::from("/logout")));
+        assert!(find_auth_routes(&[route("get", "/items", json!("None"))]).is_none());
+    }
+
+    #[test]
+    fn test_credentials_payload() {
+        let user = test_user();
+        let payload = credentials_payload(
+            &FieldSchema::from(
+                json!({"email": "string", "password": "string", "remember": "bool"}),
+            ),
+            &user,
+        );
+        assert_eq!(payload["email"], json!(user["email"]));
+        assert_eq!(payload["password"], json!(user["password"]));
+        assert_eq!(payload["remember"], json!(true));
+
+        let payload = credentials_payload(&FieldSchema::Empty, &user);
+        assert_eq!(payload["username"], json!(user["username"]));
+    }
+
+    #[test]
+    fn test_extract_credentials() {
+        let mut result = EndpointResult {
+            method: Method::POST,
+            route: String::from("/login"),
+            status: Some(200),
+            headers: HeaderMap::new(),
+            body: json!({"data": {"access_token": "jwt-value"}}).to_string(),
+            error: None,
+            server_logs: vec![],
+        };
+        assert_eq!(
+            extract_credentials(&result),
+            Some(Credentials::Bearer(String::from("jwt-value")))
+        );
+
+        result
+            .headers
+            .insert(SET_COOKIE, HeaderValue::from_static("id=42; Path=/"));
+        assert_eq!(
+            extract_credentials(&result),
+            Some(Credentials::Cookie(String::from("id=42")))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_auth_flow() {
+        let base_url = spawn_auth_server(false).await;
+        let mut routes = auth_routes();
+        routes.push(route("get", "/report", json!("None")));
+        let checks = run_auth_flow(
+            &Client::new(),
+            &ServerLog::default(),
+            &base_url,
+            &routes,
+            &[String::from("/health"), String::from("/todos/{id}")],
+        )
+        .await;
+        let summaries: Vec<String> = checks.iter().map(|check| check.summary()).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                "PASS AUTH sign up through /signup",
+                "PASS AUTH log in through /login",
+                "FAIL AUTH protected route /todos/{id} is served",
+                "PASS AUTH GET /profile accepts the logged in session",
+                "FAIL AUTH GET /health refuses unauthenticated requests",
+                "FAIL AUTH GET /report accepts the logged in session",
+                "PASS AUTH log out through /logout",
+                "PASS AUTH GET /profile refuses the session after logout",
+                "PASS AUTH GET /report refuses the session after logout",
+            ]
+        );
+        assert!(checks[5].bug_report().unwrap().contains("500"));
+
+        let base_url = spawn_auth_server(true).await;
+        let checks = run_auth_flow(
+            &Client::new(),
+            &ServerLog::default(),
+            &base_url,
+            &auth_routes(),
+            &[],
+        )
+        .await;
+        let failed: Vec<String> = checks
+            .iter()
+            .filter_map(|check| check.bug_report())
+            .collect();
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].contains("GET /profile refuses the session after logout"));
+    }
+
+    #[tokio::test]
+    async fn test_open_session() {
+        let base_url = spawn_auth_server(false).await;
+        let server_log = ServerLog::default();
+        let credentials = open_session(&Client::new(), &server_log, &base_url, &auth_routes())
+            .await
+            .expect("Expected a session");
+        assert_eq!(
+            credentials,
+            Credentials::Cookie(String::from("session=abc"))
+        );
+
+        let session_client = Client::builder()
+            .default_headers(credentials.headers())
+            .build()
+            .unwrap();
+        let profile = session_client
+            .get(format!("{}/profile", base_url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(profile.status(), 200);
+
+        let safe_routes: Vec<String> = session_safe_routes(&auth_routes())
+            .into_iter()
+            .map(|route| route.route)
+            .collect();
+        assert_eq!(
+            safe_routes,
+            vec!["/signup", "/login", "/profile", "/health"]
+        );
+
+        let no_login = vec![route("get", "/health", json!("None"))];
+        assert!(
+            open_session(&Client::new(), &server_log, &base_url, &no_login)
+                .await
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{
+    analysis::actix_routes::route_key,
+    models::{ApiContract, RouteObject},
+    testing::routes::RouteCheck,
+};
+
+// A way the generated server departs from the API contract
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractViolation {
+    pub operation: String,
+    pub reason: String,
+}
+
+impl ContractViolation {
+    pub fn summary(&self) -> String {
+        format!("CONTRACT {}: {}", self.operation, self.reason)
+    }
+
+    // Failure description sent back through the fix loop
+    pub fn bug_report(&self) -> String {
+        format!(
+            "{} \n The API contract cannot change, change the code so it matches the contract",
+            self.summary()
+        )
+    }
+}
+
+// Operations the code does not serve, and success statuses the contract does not declare
+pub fn contract_violations(
+    contract: &ApiContract,
+    served: &[RouteObject],
+    checks: &[RouteCheck],
+) -> Vec<ContractViolation> {
+    let served_keys: Vec<String> = served.iter().map(route_key).collect();
+    let mut violations: Vec<ContractViolation> = vec![];
+
+    for operation in &contract.operations {
+        let key = route_key(&operation.route);
+        let name = format!("{} {}", operation.route.method, operation.route.route);
+        if !served_keys.contains(&key) {
+            violations.push(ContractViolation {
+                operation: name,
+                reason: String::from("declared in the contract but not served by the code"),
+            });
+            continue;
+        }
+
+        let status = checks
+            .iter()
+            .find(|check| route_key(&check.route) == key && check.result.passed())
+            .and_then(|check| check.result.status);
+        let Some(status) = status else {
+            continue;
+        };
+        if !operation.success_statuses.is_empty() && !operation.success_statuses.contains(&status) {
+            let declared: Vec<String> = operation
+                .success_statuses
+                .iter()
+                .map(|status| status.to_string())
+                .collect();
+            violations.push(ContractViolation {
+                operation: name,
+                reason: format!(
+                    "answered {} but the contract declares {}",
+                    status,
+                    declared.join(" or ")
+                ),
+            });
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::{header::HeaderMap, Method};
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        models::ContractOperation,
+        testing::{endpoint::EndpointResult, test_support::test_route},
+    };
+
+    fn operation(method: &str, path: &str, success_statuses: Vec<u16>) -> ContractOperation {
+        ContractOperation {
+            route: test_route(method, path, json!("None")),
+            success_statuses,
+        }
+    }
+
+    fn check(method: Method, route: &str, status: u16) -> RouteCheck {
+        RouteCheck {
+            route: test_route(method.as_str(), route, json!("None")),
+            result: EndpointResult {
+                method,
+                route: route.to_string(),
+                status: Some(status),
+                headers: HeaderMap::new(),
+                body: String::new(),
+                error: None,
+                server_logs: vec![],
+            },
+            shape_errors: vec![],
+            guessed_placeholders: false,
+            protected: false,
+        }
+    }
+
+    #[test]
+    fn test_contract_violations() {
+        let contract = ApiContract {
+            title: String::from("Todo API"),
+            description: String::new(),
+            document: Value::Null,
+            operations: vec![
+                operation("post", "/items", vec![201]),
+                operation("get", "/items/{itemId}", vec![200]),
+                operation("delete", "/items/{itemId}", vec![204]),
+            ],
+        };
+        let served = vec![
+            test_route("post", "/items", json!("None")),
+            test_route("get", "/items/{id}", json!("None")),
+        ];
+        let checks = vec![
+            check(Method::POST, "/items", 200),
+            check(Method::GET, "/items/{itemId}", 200),
+        ];
+
+        let summaries: Vec<String> = contract_violations(&contract, &served, &checks)
+            .iter()
+            .map(|violation| violation.summary())
+            .collect();
+        assert_eq!(
+            summaries,
+            vec![
+                "CONTRACT POST /items: answered 200 but the contract declares 201",
+                "CONTRACT DELETE /items/{itemId}: declared in the contract but not served by the code",
+            ]
+        );
+    }
+}
@@ -0,0 +1,518 @@
+use std::{collections::HashMap, fmt};
+
+use reqwest::{Client, Method};
+use serde_json::{Map, Value};
+
+use crate::{
+    helpers::process::ServerLog,
+    models::{HttpMethod, RouteObject},
+    testing::{
+        endpoint::{send_request, EndpointResult},
+        payload::{build_request_body, fill_route_placeholders},
+    },
+};
+
+// Step of the create -> read -> update -> read -> delete -> read sequence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrudStep {
+    Create,
+    Read,
+    Update,
+    ReadAfterUpdate,
+    Delete,
+    ReadAfterDelete,
+}
+
+impl fmt::Display for CrudStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Create => "create",
+            Self::Read => "read",
+            Self::Update => "update",
+            Self::ReadAfterUpdate => "read after update",
+            Self::Delete => "delete",
+            Self::ReadAfterDelete => "read after delete",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Routes that together manage one resource, such as /item and /item/{id}
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrudResource {
+    pub collection: String,
+    // Placeholder of each item route, since /item/{id} and /item/{item_id} can mix
+    pub id_placeholders: Vec<String>,
+    pub create: RouteObject,
+    pub read: Option<RouteObject>,
+    pub update: Option<RouteObject>,
+    pub delete: Option<RouteObject>,
+}
+
+// First step that broke, with the reason and the offending request
+#[derive(Debug, Clone)]
+pub struct CrudFailure {
+    pub step: CrudStep,
+    pub reason: String,
+    pub result: EndpointResult,
+}
+
+#[derive(Debug, Clone)]
+pub struct CrudReport {
+    pub collection: String,
+    pub completed_steps: Vec<CrudStep>,
+    pub failure: Option<CrudFailure>,
+}
+
+impl CrudReport {
+    pub fn summary(&self) -> String {
+        match &self.failure {
+            None => format!(
+                "PASS CRUD {} ({} steps)",
+                self.collection,
+                self.completed_steps.len()
+            ),
+            Some(failure) => format!(
+                "FAIL CRUD {} at step '{}': {}",
+                self.collection, failure.step, failure.reason
+            ),
+        }
+    }
+
+    pub fn bug_report(&self) -> Option<String> {
+        let failure = self.failure.as_ref()?;
+        Some(format!(
+            "CRUD SCENARIO FOR {} BROKE AT STEP '{}': {} \n {}",
+            self.collection,
+            failure.step,
+            failure.reason,
+            failure.result.bug_report()
+        ))
+    }
+}
+
+// Group routes into resources that have at least a create route
+pub fn find_crud_resources(routes: &[RouteObject]) -> Vec<CrudResource> {
+    let mut resources: Vec<CrudResource> = vec![];
+
+    for create in routes
+        .iter()
+        .filter(|route| route.method == HttpMethod::Post && route.path_params.is_empty())
+    {
+        let collection = create.route.trim_end_matches('/').to_string();
+        let mut item_routes: HashMap<Method, &RouteObject> = HashMap::new();
+
+        for route in routes {
+            let Some(item_prefix) = route.route.rsplit_once('/').map(|(prefix, _)| prefix) else {
+                continue;
+            };
+            if route.path_params.len() != 1 || item_prefix != collection {
+                continue;
+            }
+            item_routes.entry(route.method.to_method()).or_insert(route);
+        }
+
+        let item_route = |methods: &[Method]| {
+            methods
+                .iter()
+                .find_map(|method| item_routes.get(method))
+                .map(|route| (*route).clone())
+        };
+        let read = item_route(&[Method::GET]);
+        let update = item_route(&[Method::PUT, Method::PATCH]);
+        let delete = item_route(&[Method::DELETE]);
+        if read.is_none() && update.is_none() && delete.is_none() {
+            continue;
+        }
+        let mut id_placeholders: Vec<String> = vec![];
+        for route in [&read, &update, &delete].into_iter().flatten() {
+            if !id_placeholders.contains(&route.path_params[0]) {
+                id_placeholders.push(route.path_params[0].clone());
+            }
+        }
+
+        resources.push(CrudResource {
+            collection,
+            id_placeholders,
+            create: create.clone(),
+            read,
+            update,
+            delete,
+        });
+    }
+    resources
+}
+
+fn id_to_string(id: &Value) -> Option<String> {
+    match id {
+        Value::Number(id) => Some(id.to_string()),
+        Value::String(id) if !id.is_empty() => Some(id.clone()),
+        _ => None,
+    }
+}
+
+// Id of the created resource, from the response or else from what was sent
+fn created_id(response_body: &str, sent: Option<&Value>) -> Option<String> {
+    let response: Option<Value> = serde_json::from_str(response_body).ok();
+    if let Some(response) = &response {
+        if let Some(id) = response.get("id").and_then(id_to_string) {
+            return Some(id);
+        }
+        if let Some(id) = id_to_string(response) {
+            return Some(id);
+        }
+    }
+    sent?.get("id").and_then(id_to_string)
+}
+
+// Changed copy of a payload to send as the update, keeping its id
+pub fn updated_payload(payload: &Value) -> Value {
+    match payload {
+        Value::Object(fields) => {
+            let updated: Map<String, Value> = fields
+                .iter()
+                .map(|(key, value)| {
+                    let value = if key == "id" {
+                        value.clone()
+                    } else {
+                        updated_payload(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect();
+            Value::Object(updated)
+        }
+        Value::String(text) => Value::String(format!("{}-updated", text)),
+        Value::Bool(flag) => Value::Bool(!flag),
+        Value::Number(number) => match number.as_i64() {
+            Some(number) => Value::from(number + 1),
+            None => Value::from(number.as_f64().unwrap_or_default() + 1.0),
+        },
+        other => other.clone(),
+    }
+}
+
+// Fields that were sent but came back missing or different
+pub fn persisted_mismatches(sent: &Value, response_body: &str) -> Vec<String> {
+    let Value::Object(sent_fields) = sent else {
+        return vec![];
+    };
+    let Ok(Value::Object(stored)) = serde_json::from_str::<Value>(response_body) else {
+        return vec![String::from("response is not a JSON object")];
+    };
+
+    sent_fields
+        .iter()
+        // Servers assign ids and usually hash passwords
+        .filter(|(key, _)| *key != "id" && !key.to_lowercase().contains("password"))
+        .filter_map(|(key, expected)| match stored.get(key) {
+            None => Some(format!("field '{}' is missing", key)),
+            Some(actual) if actual != expected => Some(format!(
+                "field '{}' is {} but {} was sent",
+                key, actual, expected
+            )),
+            Some(_) => None,
+        })
+        .collect()
+}
+
+fn failure(step: CrudStep, reason: &str, result: EndpointResult) -> CrudFailure {
+    CrudFailure {
+        step,
+        reason: reason.to_string(),
+        result,
+    }
+}
+
+// Read the resource back and compare it with what was sent, if a read route exists
+async fn read_and_compare(
+    client: &Client,
+    server_log: &ServerLog,
+    base_url: &str,
+    resource: &CrudResource,
+    values: &HashMap<String, String>,
+    step: CrudStep,
+    expected: Option<&Value>,
+) -> Result<bool, CrudFailure> {
+    let Some(read_route) = &resource.read else {
+        return Ok(false);
+    };
+    let path = fill_route_placeholders(&read_route.route, values);
+    let read = send_request(client, server_log, base_url, Method::GET, &path, None).await;
+    if !read.passed() {
+        return Err(failure(step, "stored resource could not be read", read));
+    }
+    if let Some(expected) = expected {
+        let mismatches = persisted_mismatches(expected, &read.body);
+        if !mismatches.is_empty() {
+            return Err(failure(step, &mismatches.join(", "), read));
+        }
+    }
+    Ok(true)
+}
+
+async fn run_scenario(
+    client: &Client,
+    server_log: &ServerLog,
+    base_url: &str,
+    resource: &CrudResource,
+    completed_steps: &mut Vec<CrudStep>,
+) -> Result<(), CrudFailure> {
+    // Create
+    let payload = build_request_body(&resource.create.request_body);
+    let created = send_request(
+        client,
+        server_log,
+        base_url,
+        Method::POST,
+        &resource.create.route,
+        payload.as_ref(),
+    )
+    .await;
+    if !created.passed() {
+        return Err(failure(CrudStep::Create, "create was refused", created));
+    }
+    let Some(id) = created_id(&created.body, payload.as_ref()) else {
+        return Err(failure(
+            CrudStep::Create,
+            "no id in the create response or the request body",
+            created,
+        ));
+    };
+    completed_steps.push(CrudStep::Create);
+
+    let values: HashMap<String, String> = resource
+        .id_placeholders
+        .iter()
+        .map(|placeholder| (placeholder.clone(), id.clone()))
+        .collect();
+
+    // Read
+    let step = CrudStep::Read;
+    let expected = payload.as_ref();
+    if read_and_compare(
+        client, server_log, base_url, resource, &values, step, expected,
+    )
+    .await?
+    {
+        completed_steps.push(step);
+    }
+
+    // Update, then read the new values back
+    if let Some(update_route) = &resource.update {
+        let update = payload.as_ref().map(updated_payload);
+        let path = fill_route_placeholders(&update_route.route, &values);
+        let method = update_route.method.to_method();
+        let updated =
+            send_request(client, server_log, base_url, method, &path, update.as_ref()).await;
+        if !updated.passed() {
+            return Err(failure(CrudStep::Update, "update was refused", updated));
+        }
+        completed_steps.push(CrudStep::Update);
+
+        let step = CrudStep::ReadAfterUpdate;
+        let expected = update.as_ref();
+        if read_and_compare(
+            client, server_log, base_url, resource, &values, step, expected,
+        )
+        .await?
+        {
+            completed_steps.push(step);
+        }
+    }
+
+    // Delete, then make sure the resource is gone
+    let Some(delete_route) = &resource.delete else {
+        return Ok(());
+    };
+    let path = fill_route_placeholders(&delete_route.route, &values);
+    let deleted = send_request(client, server_log, base_url, Method::DELETE, &path, None).await;
+    if !deleted.passed() {
+        return Err(failure(CrudStep::Delete, "delete was refused", deleted));
+    }
+    completed_steps.push(CrudStep::Delete);
+
+    let Some(read_route) = &resource.read else {
+        return Ok(());
+    };
+    let path = fill_route_placeholders(&read_route.route, &values);
+    let gone = send_request(client, server_log, base_url, Method::GET, &path, None).await;
+    if gone.status != Some(404) {
+        return Err(failure(
+            CrudStep::ReadAfterDelete,
+            "deleted resource should answer 404",
+            gone,
+        ));
+    }
+    completed_steps.push(CrudStep::ReadAfterDelete);
+    Ok(())
+}
+
+// Run the CRUD sequence against every resource found in the routes
+pub async fn run_crud_scenarios(
+    client: &Client,
+    server_log: &ServerLog,
+    base_url: &str,
+    routes: &[RouteObject],
+) -> Vec<CrudReport> {
+    let mut reports: Vec<CrudReport> = vec![];
+    for resource in find_crud_resources(routes) {
+        let mut completed_steps: Vec<CrudStep> = vec![];
+        let failure = run_scenario(
+            client,
+            server_log,
+            base_url,
+            &resource,
+            &mut completed_steps,
+        )
+        .await
+        .err();
+        reports.push(CrudReport {
+            collection: resource.collection,
+            completed_steps,
+            failure,
+        });
+    }
+    reports
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::test_support::{spawn_fake_server, test_route as route, FakeResponse};
+
+    fn item_routes() -> Vec<RouteObject> {
+        vec![
+            route("post", "/item", json!({"name": "string", "done": "bool"})),
+            route("get", "/item/{id}", json!("None")),
+            route(
+                "put",
+                "/item/{id}",
+                json!({"name": "string", "done": "bool"}),
+            ),
+            route("delete", "/item/{id}", json!("None")),
+            route("get", "/crypto", json!("None")),
+        ]
+    }
+
+    // Fake item store; forget_updates simulates a broken update handler
+    async fn spawn_item_server(forget_updates: bool) -> String {
+        let store: Arc<Mutex<HashMap<String, Value>>> = Arc::new(Mutex::new(HashMap::new()));
+        spawn_fake_server(move |request| {
+            let mut store = store.lock().unwrap();
+            let id = request.path.trim_start_matches("/item/").to_string();
+            match request.method.as_str() {
+                "POST" => {
+                    let mut item: Value = serde_json::from_str(&request.body).unwrap();
+                    item["id"] = json!(42);
+                    store.insert(String::from("42"), item.clone());
+                    FakeResponse::json(201, item)
+                }
+                "GET" => match store.get(&id) {
+                    Some(item) => FakeResponse::json(200, item.clone()),
+                    None => FakeResponse::new(404, "not found"),
+                },
+                "PUT" => {
+                    if !forget_updates {
+                        let item: Value = serde_json::from_str(&request.body).unwrap();
+                        store.insert(id, item);
+                    }
+                    FakeResponse::new(200, "")
+                }
+                "DELETE" => {
+                    store.remove(&id);
+                    FakeResponse::new(204, "")
+                }
+                _ => FakeResponse::new(405, ""),
+            }
+        })
+        .await
+    }
+
+    #[test]
+    fn test_find_crud_resources() {
+        let resources = find_crud_resources(&item_routes());
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].collection, "/item");
+        assert_eq!(resources[0].id_placeholders, vec!["id"]);
+        assert!(resources[0].read.is_some());
+        assert!(resources[0].update.is_some());
+        assert!(resources[0].delete.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mixed_id_placeholders() {
+        let routes = vec![
+            route("post", "/item", json!({"name": "string", "done": "bool"})),
+            route("get", "/item/{id}", json!("None")),
+            route(
+                "put",
+                "/item/{item_id}",
+                json!({"name": "string", "done": "bool"}),
+            ),
+            route("delete", "/item/{itemId}", json!("None")),
+        ];
+        let resources = find_crud_resources(&routes);
+        assert_eq!(
+            resources[0].id_placeholders,
+            vec!["id", "item_id", "itemId"]
+        );
+
+        let base_url = spawn_item_server(false).await;
+        let reports =
+            run_crud_scenarios(&Client::new(), &ServerLog::default(), &base_url, &routes).await;
+        assert_eq!(reports[0].summary(), "PASS CRUD /item (6 steps)");
+    }
+
+    #[test]
+    fn test_persisted_mismatches() {
+        let sent = json!({"id": 1, "name": "sample", "done": true});
+        assert!(
+            persisted_mismatches(&sent, r#"{"id": 9, "name": "sample", "done": true}"#).is_empty()
+        );
+        assert_eq!(
+            persisted_mismatches(&sent, r#"{"name": "other"}"#),
+            vec![
+                "field 'done' is missing",
+                "field 'name' is \"other\" but \"sample\" was sent"
+            ]
+        );
+        assert_eq!(
+            updated_payload(&sent),
+            json!({"id": 1, "name": "sample-updated", "done": false})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_crud_scenarios() {
+        let base_url = spawn_item_server(false).await;
+        let reports = run_crud_scenarios(
+            &Client::new(),
+            &ServerLog::default(),
+            &base_url,
+            &item_routes(),
+        )
+        .await;
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].summary(), "PASS CRUD /item (6 steps)");
+
+        let base_url = spawn_item_server(true).await;
+        let reports = run_crud_scenarios(
+            &Client::new(),
+            &ServerLog::default(),
+            &base_url,
+            &item_routes(),
+        )
+        .await;
+        let failure = reports[0].failure.as_ref().expect("Expected a failure");
+        assert_eq!(failure.step, CrudStep::ReadAfterUpdate);
+        assert!(reports[0]
+            .bug_report()
+            .unwrap()
+            .contains("BROKE AT STEP 'read after update'"));
+    }
+}
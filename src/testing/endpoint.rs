@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use reqwest::{
+    header::{HeaderMap, CONTENT_TYPE},
+    Client, Method, RequestBuilder,
+};
+use serde_json::Value;
+use tokio::time::sleep;
+
+use crate::helpers::process::ServerLog;
+
+// Longest response body forwarded to the LLM
+const MAX_BODY_CHARS: usize = 2000;
+
+// Outcome of a single request against the generated server
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointResult {
+    pub method: Method,
+    pub route: String,
+    pub status: Option<u16>,
+    pub headers: HeaderMap,
+    pub body: String,
+    pub error: Option<String>,
+    pub server_logs: Vec<String>,
+}
+
+impl EndpointResult {
+    pub fn passed(&self) -> bool {
+        matches!(self.status, Some(status) if (200..300).contains(&status))
+    }
+
+    // Summary line shown to the user
+    pub fn describe(&self) -> String {
+        match (&self.status, &self.error) {
+            (Some(status), _) => format!("{} {} -> {}", self.method, self.route, status),
+            (None, Some(error)) => format!("{} {} -> {}", self.method, self.route, error),
+            (None, None) => format!("{} {} -> no response", self.method, self.route),
+        }
+    }
+
+    // Failure description sent back through the fix loop
+    pub fn bug_report(&self) -> String {
+        let status = self
+            .status
+            .map(|status| status.to_string())
+            .unwrap_or_else(|| String::from("NO RESPONSE"));
+        let mut body: String = self.body.chars().take(MAX_BODY_CHARS).collect();
+        if let Some(error) = &self.error {
+            body = format!("{} (request error: {})", body, error);
+        }
+        format!(
+            "ROUTE: {} {} \n STATUS: {} \n RESPONSE BODY: {} \n SERVER LOGS:\n{}",
+            self.method,
+            self.route,
+            status,
+            body,
+            self.server_logs.join("\n")
+        )
+    }
+}
+
+// Send one request and collect the server logs printed while it ran
+pub async fn send_request(
+    client: &Client,
+    server_log: &ServerLog,
+    base_url: &str,
+    method: Method,
+    route: &str,
+    body: Option<&Value>,
+) -> EndpointResult {
+    send_request_with_headers(
+        client,
+        server_log,
+        base_url,
+        method,
+        route,
+        body,
+        &HeaderMap::new(),
+    )
+    .await
+}
+
+// Same as send_request, with extra headers such as credentials
+pub async fn send_request_with_headers(
+    client: &Client,
+    server_log: &ServerLog,
+    base_url: &str,
+    method: Method,
+    route: &str,
+    body: Option<&Value>,
+    headers: &HeaderMap,
+) -> EndpointResult {
+    let url = format!("{}{}", base_url, route);
+    let mut request = client
+        .request(method.clone(), &url)
+        .headers(headers.clone());
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+    collect_result(request, server_log, method, route).await
+}
+
+// Send a body as is, even if it is not valid JSON
+pub async fn send_raw_request(
+    client: &Client,
+    server_log: &ServerLog,
+    base_url: &str,
+    method: Method,
+    route: &str,
+    raw_body: &str,
+) -> EndpointResult {
+    let url = format!("{}{}", base_url, route);
+    let request = client
+        .request(method.clone(), &url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(raw_body.to_string());
+    collect_result(request, server_log, method, route).await
+}
+
+async fn collect_result(
+    request: RequestBuilder,
+    server_log: &ServerLog,
+    method: Method,
+    route: &str,
+) -> EndpointResult {
+    let log_cursor = server_log.cursor();
+    let (status, response_headers, response_body, error) = match request.send().await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let response_headers = response.headers().clone();
+            let text = response.text().await.unwrap_or_default();
+            (Some(status), response_headers, text, None)
+        }
+        Err(e) => (None, HeaderMap::new(), String::new(), Some(e.to_string())),
+    };
+
+    let mut result = EndpointResult {
+        method,
+        route: route.to_string(),
+        status,
+        headers: response_headers,
+        body: response_body,
+        error,
+        server_logs: vec![],
+    };
+    if !matches!(result.status, Some(status) if status < 500) {
+        // Give the server a moment to flush what it logged
+        sleep(Duration::from_millis(200)).await;
+    }
+    result.server_logs = server_log.lines_since(log_cursor);
+    result
+}
+
+// Combined bug report for every failing check
+pub fn runtime_bug_report(reports: &[String]) -> String {
+    format!("RUNTIME ERRORS:\n{}", reports.join("\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::test_support::{spawn_fake_server, FakeResponse};
+
+    #[tokio::test]
+    async fn test_send_request() {
+        let base_url = spawn_fake_server(|request| match request.path.as_str() {
+            "/ok" => FakeResponse::new(200, "fine"),
+            "/echo" if request.method == "POST" => {
+                let echoed: Value = serde_json::from_str(&request.body).unwrap();
+                FakeResponse::json(201, echoed)
+            }
+            _ => FakeResponse::new(500, "database unavailable"),
+        })
+        .await;
+        let client = Client::new();
+        let server_log = ServerLog::default();
+
+        let ok = send_request(&client, &server_log, &base_url, Method::GET, "/ok", None).await;
+        assert!(ok.passed());
+        assert_eq!(ok.body, "fine");
+
+        let payload = serde_json::json!({"name": "benjamin"});
+        let echo = send_request(
+            &client,
+            &server_log,
+            &base_url,
+            Method::POST,
+            "/echo",
+            Some(&payload),
+        )
+        .await;
+        assert!(echo.passed());
+        assert_eq!(serde_json::from_str::<Value>(&echo.body).unwrap(), payload);
+
+        let broken = send_request(
+            &client,
+            &server_log,
+            &base_url,
+            Method::GET,
+            "/broken",
+            None,
+        )
+        .await;
+        assert!(!broken.passed());
+        assert_eq!(broken.describe(), "GET /broken -> 500");
+        assert!(broken
+            .bug_report()
+            .contains("RESPONSE BODY: database unavailable"));
+
+        let unreachable = send_request(
+            &client,
+            &server_log,
+            "http://127.0.0.1:9",
+            Method::GET,
+            "/",
+            None,
+        )
+        .await;
+        assert!(!unreachable.passed());
+        assert!(unreachable.bug_report().contains("STATUS: NO RESPONSE"));
+    }
+}
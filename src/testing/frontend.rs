@@ -0,0 +1,450 @@
+use std::collections::BTreeMap;
+
+use reqwest::{header::CONTENT_TYPE, Client, Method};
+
+use crate::{
+    helpers::{general::strip_code_fence, process::ServerLog},
+    models::{HttpMethod, RouteObject},
+    testing::endpoint::{send_request, EndpointResult},
+};
+
+// Only files a static page needs are written, so a reply cannot place anything else
+const FRONTEND_EXTENSIONS: [&str; 3] = ["html", "js", "css"];
+
+// Content type browsers expect for a frontend file
+pub fn content_type_for(file_name: &str) -> &'static str {
+    match file_name.rsplit('.').next().unwrap_or_default() {
+        "html" => "text/html",
+        "js" => "application/javascript",
+        "css" => "text/css",
+        _ => "application/octet-stream",
+    }
+}
+
+fn is_valid_file_name(name: &str) -> bool {
+    let plain_name = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    plain_name
+        && name
+            .rsplit_once('.')
+            .is_some_and(|(_, extension)| FRONTEND_EXTENSIONS.contains(&extension))
+}
+
+// Files of the model's reply, a JSON object from file name to contents
+pub fn parse_frontend_files(response: &str) -> Result<BTreeMap<String, String>, String> {
+    let files: BTreeMap<String, String> = serde_json::from_str(strip_code_fence(response))
+        .map_err(|e| format!("the reply is not a JSON object of file contents: {}", e))?;
+
+    if let Some(name) = files.keys().find(|name| !is_valid_file_name(name)) {
+        return Err(format!(
+            "{} is not a plain .html, .js or .css file name",
+            name
+        ));
+    }
+    if !files.contains_key("index.html") {
+        return Err(String::from("the frontend has no index.html"));
+    }
+    Ok(files)
+}
+
+// Path a frontend file is served at
+pub fn page_path(frontend_route: &str, file_name: &str) -> String {
+    format!("{}/{}", frontend_route.trim_end_matches('/'), file_name)
+}
+
+// Outcome of fetching one frontend file from the generated server
+#[derive(Debug, Clone)]
+pub struct PageCheck {
+    pub result: EndpointResult,
+    pub errors: Vec<String>,
+}
+
+impl PageCheck {
+    pub fn is_bug(&self) -> bool {
+        !self.result.passed() || !self.errors.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        let verdict = if self.is_bug() { "FAIL" } else { "PASS" };
+        let mut summary = format!("{} PAGE {}", verdict, self.result.describe());
+        if !self.errors.is_empty() {
+            summary = format!("{} ({})", summary, self.errors.join(", "));
+        }
+        summary
+    }
+
+    // Failure description sent back through the fix loop
+    pub fn bug_report(&self) -> String {
+        let mut report = self.result.bug_report();
+        if !self.errors.is_empty() {
+            report = format!("{} \n PAGE ERRORS: {}", report, self.errors.join(", "));
+        }
+        report
+    }
+}
+
+// Fetch every file, and index.html at the frontend route itself
+pub async fn check_pages(
+    client: &Client,
+    server_log: &ServerLog,
+    base_url: &str,
+    frontend_route: &str,
+    files: &BTreeMap<String, String>,
+) -> Vec<PageCheck> {
+    let mut pages: Vec<(String, &str, &String)> = files
+        .iter()
+        .map(|(name, contents)| (page_path(frontend_route, name), name.as_str(), contents))
+        .collect();
+    if let Some(index) = files.get("index.html") {
+        let route = match frontend_route.trim_end_matches('/') {
+            "" => String::from("/"),
+            route => route.to_string(),
+        };
+        pages.push((route, "index.html", index));
+    }
+
+    let mut checks: Vec<PageCheck> = vec![];
+    for (path, name, contents) in pages {
+        let result = send_request(client, server_log, base_url, Method::GET, &path, None).await;
+        let mut errors: Vec<String> = vec![];
+        if result.passed() {
+            if result.body.trim() != contents.trim() {
+                errors.push(format!("body differs from {}", name));
+            }
+            let expected_type = content_type_for(name);
+            let content_type = result
+                .headers
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            if !content_type.starts_with(expected_type) {
+                errors.push(format!(
+                    "content type is {:?}, expected {}",
+                    content_type, expected_type
+                ));
+            }
+        }
+        checks.push(PageCheck { result, errors });
+    }
+    checks
+}
+
+// Request a page sends with fetch, with template expressions written as {}
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiCall {
+    pub file: String,
+    pub method: HttpMethod,
+    pub path: String,
+}
+
+impl ApiCall {
+    pub fn summary(&self) -> String {
+        format!(
+            "FAIL API CALL {} {} from {} has no matching route",
+            self.method, self.path, self.file
+        )
+    }
+
+    // Failure description sent back through the fix loop
+    pub fn bug_report(&self) -> String {
+        format!(
+            "{} calls {} {}, which the backend does not serve. Only call the routes in API_ENDPOINTS",
+            self.file, self.method, self.path
+        )
+    }
+}
+
+// String literal starting at the quote at the start of source, with its length
+fn read_literal(source: &str) -> Option<(String, usize)> {
+    let quote = source.chars().next()?;
+    if !matches!(quote, '"' | '\'' | '`') {
+        return None;
+    }
+    let mut literal = String::new();
+    let mut chars = source.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    literal.push(escaped);
+                }
+            }
+            '$' if quote == '`' && source[index..].starts_with("${") => {
+                chars.next();
+                let mut depth = 1;
+                for (_, c) in chars.by_ref() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                literal.push_str("{}");
+            }
+            c if c == quote => return Some((literal, index + 1)),
+            c => literal.push(c),
+        }
+    }
+    None
+}
+
+// Arguments of a call up to its closing parenthesis, skipping parentheses in strings
+fn call_arguments(source: &str) -> &str {
+    let mut depth = 0;
+    let mut index = 0;
+    while index < source.len() {
+        let rest = &source[index..];
+        if let Some((_, length)) = read_literal(rest) {
+            index += length;
+            continue;
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        match c {
+            '(' | '{' | '[' => depth += 1,
+            ')' | '}' | ']' if depth == 0 => return &source[..index],
+            ')' | '}' | ']' => depth -= 1,
+            _ => {}
+        }
+        index += c.len_utf8();
+    }
+    source
+}
+
+// Method named by a `method: "POST"` option, if the call sets one
+fn option_method(arguments: &str) -> Option<HttpMethod> {
+    let (_, after) = arguments.split_once("method")?;
+    let after = after
+        .trim_start()
+        .trim_start_matches(['"', '\''])
+        .trim_start();
+    let after = after.strip_prefix(':')?.trim_start();
+    let (method, _) = read_literal(after)?;
+    method.parse().ok()
+}
+
+// Path of a fetched URL, or None for URLs that are not the API's
+fn api_path(url: &str) -> Option<String> {
+    let url = match url.split_once("://") {
+        Some((_, rest)) => {
+            let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+            let host = host.rsplit_once(':').map_or(host, |(host, _)| host);
+            // Other hosts are external APIs the page calls directly
+            if !matches!(host, "localhost" | "127.0.0.1" | "0.0.0.0") {
+                return None;
+            }
+            path
+        }
+        // A leading template expression is taken to be the API base URL
+        None => url.strip_prefix("{}").unwrap_or(url),
+    };
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    if !path.starts_with('/') {
+        return None;
+    }
+    match path.trim_end_matches('/') {
+        "" => Some(String::from("/")),
+        path => Some(path.to_string()),
+    }
+}
+
+// Every fetch call in the source of a page or script
+pub fn referenced_api_calls(file: &str, source: &str) -> Vec<ApiCall> {
+    let mut calls: Vec<ApiCall> = vec![];
+    for (index, _) in source.match_indices("fetch(") {
+        let arguments = source[index + "fetch(".len()..].trim_start();
+        let Some((url, length)) = read_literal(arguments) else {
+            continue;
+        };
+        let Some(path) = api_path(&url) else {
+            continue;
+        };
+        let method = option_method(call_arguments(&arguments[length..])).unwrap_or(HttpMethod::Get);
+        calls.push(ApiCall {
+            file: file.to_string(),
+            method,
+            path,
+        });
+    }
+    calls
+}
+
+// Placeholders match any segment, and {} from a template matches anything
+fn path_matches(path: &str, route: &str) -> bool {
+    let path_segments: Vec<&str> = path.split('/').collect();
+    let route_segments: Vec<&str> = route.trim_end_matches('/').split('/').collect();
+    path_segments.len() == route_segments.len()
+        && path_segments
+            .iter()
+            .zip(&route_segments)
+            .all(|(segment, route_segment)| {
+                *segment == "{}"
+                    || segment == route_segment
+                    || (route_segment.starts_with('{') && !segment.is_empty())
+            })
+}
+
+// Calls no extracted route serves; files of the frontend itself are skipped
+pub fn unknown_api_calls(
+    files: &BTreeMap<String, String>,
+    routes: &[RouteObject],
+    frontend_route: &str,
+) -> Vec<ApiCall> {
+    let frontend_route = frontend_route.trim_end_matches('/');
+    files
+        .iter()
+        .flat_map(|(name, source)| referenced_api_calls(name, source))
+        .filter(|call| {
+            let is_page = !frontend_route.is_empty()
+                && (call.path == frontend_route
+                    || call.path.starts_with(&format!("{}/", frontend_route)));
+            !is_page
+                && !routes.iter().any(|route| {
+                    route.method == call.method && path_matches(&call.path, &route.route)
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::test_support::{spawn_fake_server, test_route, FakeResponse};
+
+    const APP_JS: &str = r#"
+const API = window.location.origin;
+
+async function loadItems() {
+  const response = await fetch(`${API}/items?sort=name`);
+  return response.json();
+}
+
+async function addItem(title) {
+  await fetch("/items", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ title: title + ")" }),
+  });
+}
+
+async function removeItem(id) {
+  await fetch(`/items/${encodeURIComponent(id)}`, { 'method': 'DELETE' });
+}
+
+async function archive(id) {
+  await fetch(`${API}/items/${id}/archive`, { method: "PUT" });
+}
+
+fetch("https://cdn.example.com/theme.json");
+fetch("http://127.0.0.1:8080/stats");
+fetch("app/styles.css");
+"#;
+
+    fn files() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            (
+                String::from("index.html"),
+                String::from("<html><script src=\"/app/app.js\"></script></html>"),
+            ),
+            (String::from("app.js"), String::from(APP_JS)),
+        ])
+    }
+
+    #[test]
+    fn test_parse_frontend_files() {
+        let reply = json!({"index.html": "<html></html>", "styles.css": "body {}"}).to_string();
+        let files = parse_frontend_files(&format!("```json\n{}\n```", reply)).unwrap();
+        assert_eq!(files.len(), 2);
+
+        assert!(parse_frontend_files("<html></html>").is_err());
+        assert!(parse_frontend_files(&json!({"app.js": ""}).to_string()).is_err());
+        for name in ["../main.rs", "index.php", ".env.html", "js/app.js"] {
+            let reply = json!({"index.html": "", name: ""}).to_string();
+            assert!(parse_frontend_files(&reply).is_err(), "{} accepted", name);
+        }
+    }
+
+    #[test]
+    fn test_referenced_api_calls() {
+        let calls: Vec<String> = referenced_api_calls("app.js", APP_JS)
+            .iter()
+            .map(|call| format!("{} {}", call.method, call.path))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                "GET /items",
+                "POST /items",
+                "DELETE /items/{}",
+                "PUT /items/{}/archive",
+                "GET /stats",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_api_calls() {
+        let routes = vec![
+            test_route("get", "/items", json!("None")),
+            test_route("post", "/items", json!({"title": "string"})),
+            test_route("delete", "/items/{id}", json!("None")),
+        ];
+        let mut files = files();
+        files.insert(
+            String::from("extra.js"),
+            String::from("fetch('/app/index.html'); fetch('/items/7', {method: 'GET'});"),
+        );
+
+        let unknown: Vec<String> = unknown_api_calls(&files, &routes, "/app/")
+            .iter()
+            .map(|call| call.summary())
+            .collect();
+        assert_eq!(
+            unknown,
+            vec![
+                "FAIL API CALL PUT /items/{}/archive from app.js has no matching route",
+                "FAIL API CALL GET /stats from app.js has no matching route",
+                "FAIL API CALL GET /items/7 from extra.js has no matching route",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_pages() {
+        let base_url = spawn_fake_server(|request| match request.path.as_str() {
+            "/app" | "/app/index.html" => {
+                FakeResponse::new(200, "<html><script src=\"/app/app.js\"></script></html>")
+                    .with_header("content-type", "text/html; charset=utf-8")
+            }
+            "/app/app.js" => FakeResponse::new(200, "console.log('stale');")
+                .with_header("content-type", "text/plain"),
+            _ => FakeResponse::new(404, ""),
+        })
+        .await;
+
+        let checks = check_pages(
+            &Client::new(),
+            &ServerLog::default(),
+            &base_url,
+            "/app",
+            &files(),
+        )
+        .await;
+        let summaries: Vec<String> = checks.iter().map(|check| check.summary()).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                "FAIL PAGE GET /app/app.js -> 200 (body differs from app.js, content type is \"text/plain\", expected application/javascript)",
+                "PASS PAGE GET /app/index.html -> 200",
+                "PASS PAGE GET /app -> 200",
+            ]
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    helpers::process::ServerProcess,
+    models::RouteObject,
+    testing::{
+        endpoint::{send_raw_request, send_request, EndpointResult},
+        payload::{build_request_body, fill_route_placeholders, parse_method},
+    },
+};
+
+// Request that is sent as is, and kept as a regression case when it breaks the server
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FuzzCase {
+    pub method: String,
+    pub route: String,
+    pub path: String,
+    pub body: Option<String>,
+    pub description: String,
+}
+
+// Input that made the server answer 5xx, panic or crash
+#[derive(Debug, Clone)]
+pub struct FuzzFinding {
+    pub case: FuzzCase,
+    pub reason: String,
+    pub result: EndpointResult,
+}
+
+impl FuzzFinding {
+    pub fn summary(&self) -> String {
+        format!(
+            "FAIL FUZZ {} {} ({}): {}",
+            self.case.method, self.case.path, self.case.description, self.reason
+        )
+    }
+
+    pub fn bug_report(&self) -> String {
+        let body: String = self
+            .case
+            .body
+            .as_deref()
+            .unwrap_or("NONE")
+            .chars()
+            .take(300)
+            .collect();
+        format!(
+            "FUZZ INPUT BROKE THE SERVER ({}): {} \n REQUEST BODY: {} \n {}",
+            self.reason,
+            self.case.description,
+            body,
+            self.result.bug_report()
+        )
+    }
+}
+
+// Small xorshift generator, so a fuzz run can be replayed from its seed
+struct FuzzRng(u64);
+
+impl FuzzRng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound.max(1) as u64) as usize
+    }
+}
+
+// Edge cases kept ahead of the others, so a small budget still sends them
+const PRIORITY_CASES: [&str; 3] = ["huge string as", "huge segment as", "unicode as"];
+
+fn edge_values() -> Vec<(&'static str, Value)> {
+    vec![
+        ("null", Value::Null),
+        ("negative number", json!(-1)),
+        ("huge number", json!(1.0e300)),
+        ("empty string", json!("")),
+        ("huge string", Value::String("A".repeat(100_000))),
+        ("unicode", json!("ß∂ƒ 😀 \u{202e}تست \u{0}")),
+        ("bool", json!(true)),
+        ("array", json!([1, "two", null])),
+        ("nested object", json!({"a": {"b": {"c": {}}}})),
+    ]
+}
+
+fn edge_path_values() -> Vec<(&'static str, String)> {
+    vec![
+        ("negative id", String::from("-1")),
+        ("zero id", String::from("0")),
+        ("overflowing id", String::from("18446744073709551616")),
+        ("unicode", String::from("%F0%9F%98%80")),
+        ("null byte", String::from("%00")),
+        ("huge segment", "A".repeat(2000)),
+    ]
+}
+
+fn case(route: &RouteObject, path: &str, body: Option<String>, description: String) -> FuzzCase {
+    FuzzCase {
+        method: route.method.to_string(),
+        route: route.route.clone(),
+        path: path.to_string(),
+        body,
+        description,
+    }
+}
+
+// Edge cases first, then random mutations, up to max_cases
+pub fn generate_fuzz_cases(route: &RouteObject, max_cases: usize, seed: u64) -> Vec<FuzzCase> {
+    let mut rng = FuzzRng::new(seed);
+    let path = fill_route_placeholders(&route.route, &HashMap::new());
+    let placeholders = &route.path_params;
+    let mut cases: Vec<FuzzCase> = vec![];
+
+    // Hostile placeholder values
+    for (name, value) in edge_path_values() {
+        for placeholder in placeholders {
+            let values = HashMap::from([(placeholder.clone(), value.clone())]);
+            let fuzzed_path = fill_route_placeholders(&route.route, &values);
+            let description = format!("{} as {{{}}}", name, placeholder);
+            cases.push(case(route, &fuzzed_path, None, description));
+        }
+    }
+
+    // Broken bodies
+    let sample = build_request_body(&route.request_body);
+    let fields: Map<String, Value> = match &sample {
+        Some(Value::Object(fields)) => fields.clone(),
+        _ => Map::new(),
+    };
+    if let Some(sample) = &sample {
+        let sample_text = sample.to_string();
+        let truncated: String = sample_text.chars().take(sample_text.len() / 2).collect();
+        cases.push(case(
+            route,
+            &path,
+            Some(truncated),
+            String::from("malformed JSON"),
+        ));
+        cases.push(case(
+            route,
+            &path,
+            Some(String::new()),
+            String::from("empty body"),
+        ));
+        cases.push(case(
+            route,
+            &path,
+            Some(String::from("[]")),
+            String::from("array body"),
+        ));
+        cases.push(case(
+            route,
+            &path,
+            Some(String::from("{}")),
+            String::from("empty object"),
+        ));
+
+        for key in fields.keys() {
+            let mut missing = fields.clone();
+            missing.remove(key);
+            let body = Some(Value::Object(missing).to_string());
+            cases.push(case(route, &path, body, format!("missing field '{}'", key)));
+        }
+        for (name, value) in edge_values() {
+            for key in fields.keys() {
+                let mut mutated = fields.clone();
+                mutated.insert(key.clone(), value.clone());
+                let body = Some(Value::Object(mutated).to_string());
+                cases.push(case(route, &path, body, format!("{} as '{}'", name, key)));
+            }
+        }
+    }
+
+    // Random combinations of the above
+    let keys: Vec<&String> = fields.keys().collect();
+    let values = edge_values();
+    let path_values = edge_path_values();
+    let mut random_cases: Vec<FuzzCase> = vec![];
+    while random_cases.len() < max_cases && (!keys.is_empty() || !placeholders.is_empty()) {
+        let mut description: Vec<String> = vec![];
+        let mut fuzzed_path = path.clone();
+        if !placeholders.is_empty() {
+            let (name, value) = &path_values[rng.below(path_values.len())];
+            let placeholder = &placeholders[rng.below(placeholders.len())];
+            let values = HashMap::from([(placeholder.clone(), value.clone())]);
+            fuzzed_path = fill_route_placeholders(&route.route, &values);
+            description.push(format!("{} as {{{}}}", name, placeholder));
+        }
+        let mut body = sample.as_ref().map(|sample| sample.to_string());
+        if !keys.is_empty() {
+            let mut mutated = fields.clone();
+            for _ in 0..=rng.below(keys.len()) {
+                let key = keys[rng.below(keys.len())];
+                let (name, value) = &values[rng.below(values.len())];
+                mutated.insert(key.clone(), value.clone());
+                description.push(format!("{} as '{}'", name, key));
+            }
+            body = Some(Value::Object(mutated).to_string());
+        }
+        let description = format!("random: {}", description.join(", "));
+        random_cases.push(case(route, &fuzzed_path, body, description));
+    }
+
+    let (mut cases, other_cases): (Vec<FuzzCase>, Vec<FuzzCase>) =
+        cases.into_iter().partition(|case| {
+            PRIORITY_CASES
+                .iter()
+                .any(|prefix| case.description.starts_with(prefix))
+        });
+    cases.extend(other_cases);
+
+    // Keep room for random cases when the edge cases alone would fill the budget
+    let edge_budget = max_cases - max_cases / 4;
+    cases.truncate(edge_budget);
+    let random_budget = max_cases - cases.len();
+    cases.extend(random_cases.into_iter().take(random_budget));
+    cases
+}
+
+fn finding_reason(result: &EndpointResult, crashed: bool) -> Option<String> {
+    if crashed {
+        return Some(String::from("server process crashed"));
+    }
+    if result
+        .server_logs
+        .iter()
+        .any(|line| line.contains("panicked at"))
+    {
+        return Some(String::from("server panicked"));
+    }
+    match result.status {
+        Some(status) if status >= 500 => Some(format!("server error {}", status)),
+        _ => None,
+    }
+}
+
+// Send every case, replaying saved regressions first; stops once the server dies
+pub async fn run_fuzz_cases(
+    client: &Client,
+    server: &mut ServerProcess,
+    base_url: &str,
+    cases: &[FuzzCase],
+) -> io::Result<Vec<FuzzFinding>> {
+    let mut findings: Vec<FuzzFinding> = vec![];
+    for case in cases {
+        let Some(method) = parse_method(&case.method) else {
+            continue;
+        };
+        let result = match &case.body {
+            Some(body) => {
+                send_raw_request(client, server.log(), base_url, method, &case.path, body).await
+            }
+            None => send_request(client, server.log(), base_url, method, &case.path, None).await,
+        };
+
+        let crashed = result.status.is_none() && server.has_exited()?.is_some();
+        if let Some(reason) = finding_reason(&result, crashed) {
+            findings.push(FuzzFinding {
+                case: case.clone(),
+                reason,
+                result,
+            });
+        }
+        if crashed {
+            break;
+        }
+    }
+    Ok(findings)
+}
+
+// Saved cases whose route still exists, followed by fresh cases for every route
+pub fn plan_fuzz_cases(
+    routes: &[RouteObject],
+    regressions: &[FuzzCase],
+    cases_per_route: usize,
+    seed: u64,
+) -> Vec<FuzzCase> {
+    let mut cases: Vec<FuzzCase> = regressions
+        .iter()
+        .filter(|case| {
+            routes.iter().any(|route| {
+                route.route == case.route
+                    && route.method.as_str().eq_ignore_ascii_case(&case.method)
+            })
+        })
+        .cloned()
+        .collect();
+    for (index, route) in routes.iter().enumerate() {
+        let route_seed = seed.wrapping_add(index as u64);
+        cases.extend(generate_fuzz_cases(route, cases_per_route, route_seed));
+    }
+    cases
+}
+
+pub fn load_regression_cases(path: &Path) -> Vec<FuzzCase> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+// Add the inputs behind new findings to the saved regression cases
+pub fn save_regression_cases(path: &Path, findings: &[FuzzFinding]) -> io::Result<()> {
+    let mut cases = load_regression_cases(path);
+    for finding in findings {
+        if !cases.contains(&finding.case) {
+            cases.push(finding.case.clone());
+        }
+    }
+    let contents = serde_json::to_string_pretty(&cases)?;
+    fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::testing::test_support::{spawn_fake_server, test_route as route, FakeResponse};
+
+    #[test]
+    fn test_generate_fuzz_cases() {
+        let item = route(
+            "put",
+            "/item/{id}",
+            json!({"name": "string", "count": "number"}),
+        );
+        let cases = generate_fuzz_cases(&item, 40, 7);
+        assert_eq!(cases.len(), 40);
+        assert!(cases.iter().any(|c| c.path == "/item/-1"));
+        assert!(cases.iter().any(|c| c.description == "malformed JSON"));
+        assert!(cases
+            .iter()
+            .any(|c| c.description == "missing field 'count'"
+                && c.body.as_deref() == Some(r#"{"name":"sample"}"#)));
+        assert!(cases.iter().any(|c| c.description.starts_with("random: ")));
+
+        // Same seed, same cases
+        assert_eq!(cases, generate_fuzz_cases(&item, 40, 7));
+
+        let list = route("get", "/items", json!("None"));
+        assert!(generate_fuzz_cases(&list, 40, 7).is_empty());
+
+        // The default budget still sends the hostile strings on every field
+        let note = route(
+            "put",
+            "/note/{id}",
+            json!({"title": "string", "body": "string", "tag": "string", "rank": "number"}),
+        );
+        let cases = generate_fuzz_cases(&note, 20, 7);
+        assert_eq!(cases.len(), 20);
+        for field in ["title", "body", "tag", "rank"] {
+            for name in ["huge string", "unicode"] {
+                let description = format!("{} as '{}'", name, field);
+                assert!(cases.iter().any(|c| c.description == description));
+            }
+        }
+        assert!(cases
+            .iter()
+            .any(|c| c.description == "huge segment as {id}"));
+        assert!(cases.iter().any(|c| c.description == "unicode as {id}"));
+    }
+
+    #[tokio::test]
+    async fn test_run_fuzz_cases() {
+        let base_url = spawn_fake_server(|request| {
+            if request.body.contains("-1") {
+                FakeResponse::new(500, "index out of range")
+            } else {
+                FakeResponse::new(400, "bad request")
+            }
+        })
+        .await;
+        let mut server = ServerProcess::spawn(
+            "sh",
+            &[String::from("-c"), String::from("sleep 30")],
+            ".",
+            &[],
+            &std::env::temp_dir().join("benjamin-fuzz-test.log"),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+
+        let item = route("post", "/item", json!({"count": "number"}));
+        let cases = plan_fuzz_cases(&[item], &[], 30, 3);
+        let findings = run_fuzz_cases(&Client::new(), &mut server, &base_url, &cases)
+            .await
+            .unwrap();
+        server.shutdown().await.unwrap();
+
+        assert!(!findings.is_empty());
+        assert!(findings.iter().all(|f| f.reason == "server error 500"));
+        assert!(findings
+            .iter()
+            .any(|f| f.case.description == "negative number as 'count'"));
+
+        let regression_path = std::env::temp_dir().join("benjamin-fuzz-regressions-test.json");
+        let _ = fs::remove_file(&regression_path);
+        save_regression_cases(&regression_path, &findings).unwrap();
+        save_regression_cases(&regression_path, &findings).unwrap();
+        let saved = load_regression_cases(&regression_path);
+        assert!(findings.iter().all(|f| saved.contains(&f.case)));
+        assert!(saved
+            .iter()
+            .enumerate()
+            .all(|(index, case)| !saved[index + 1..].contains(case)));
+
+        let other_route = route("get", "/other", json!("None"));
+        assert!(plan_fuzz_cases(&[other_route], &saved, 0, 3).is_empty());
+    }
+}
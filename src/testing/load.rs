@@ -0,0 +1,200 @@
+use std::time::{Duration, Instant};
+
+use reqwest::{Client, Method};
+use serde_json::Value;
+
+use crate::{
+    models::RouteLoadResult,
+    testing::{payload::build_request_body, routes::RouteCheck},
+};
+
+// Route that passed the functional tests and is safe to repeat
+#[derive(Debug, Clone)]
+pub struct LoadTarget {
+    pub method: Method,
+    pub path: String,
+    pub body: Option<Value>,
+}
+
+// Passing routes, reusing the paths that worked; deletes are skipped
+pub fn load_targets(route_checks: &[RouteCheck]) -> Vec<LoadTarget> {
+    route_checks
+        .iter()
+        .filter(|check| check.result.passed() && check.result.method != Method::DELETE)
+        .map(|check| LoadTarget {
+            method: check.result.method.clone(),
+            path: check.result.route.clone(),
+            body: build_request_body(&check.route.request_body),
+        })
+        .collect()
+}
+
+// Nearest rank percentile of sorted latencies, in milliseconds
+fn percentile(sorted: &[Duration], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    let index = rank.clamp(1, sorted.len()) - 1;
+    sorted[index].as_secs_f64() * 1000.0
+}
+
+fn summarise(
+    target: &LoadTarget,
+    samples: Vec<(Duration, bool)>,
+    elapsed: Duration,
+) -> RouteLoadResult {
+    let requests = samples.len() as u64;
+    let errors = samples.iter().filter(|(_, ok)| !ok).count() as u64;
+    let mut latencies: Vec<Duration> = samples.into_iter().map(|(latency, _)| latency).collect();
+    latencies.sort();
+
+    RouteLoadResult {
+        method: target.method.to_string(),
+        route: target.path.clone(),
+        requests,
+        errors,
+        error_rate: if requests == 0 {
+            0.0
+        } else {
+            errors as f64 / requests as f64
+        },
+        requests_per_sec: requests as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        p50_ms: percentile(&latencies, 50.0),
+        p95_ms: percentile(&latencies, 95.0),
+        p99_ms: percentile(&latencies, 99.0),
+    }
+}
+
+// Keep sending requests to one route until the deadline
+async fn load_worker(
+    client: Client,
+    url: String,
+    target: LoadTarget,
+    deadline: Instant,
+) -> Vec<(Duration, bool)> {
+    let mut samples: Vec<(Duration, bool)> = vec![];
+    while Instant::now() < deadline {
+        let mut request = client.request(target.method.clone(), &url);
+        if let Some(body) = &target.body {
+            request = request.json(body);
+        }
+        let started_at = Instant::now();
+        let ok = match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+                // Read the body so the latency covers the whole response
+                response.bytes().await.is_ok() && status.is_success()
+            }
+            Err(_) => false,
+        };
+        samples.push((started_at.elapsed(), ok));
+    }
+    samples
+}
+
+// Hit each route with concurrent workers for the given duration, one route at a time
+pub async fn run_load_test(
+    client: &Client,
+    base_url: &str,
+    targets: &[LoadTarget],
+    concurrency: usize,
+    duration_per_route: Duration,
+) -> Vec<RouteLoadResult> {
+    let mut results: Vec<RouteLoadResult> = vec![];
+    for target in targets {
+        let url = format!("{}{}", base_url, target.path);
+        let started_at = Instant::now();
+        let deadline = started_at + duration_per_route;
+
+        let workers: Vec<_> = (0..concurrency.max(1))
+            .map(|_| {
+                tokio::spawn(load_worker(
+                    client.clone(),
+                    url.clone(),
+                    target.clone(),
+                    deadline,
+                ))
+            })
+            .collect();
+        let mut samples: Vec<(Duration, bool)> = vec![];
+        for worker in workers {
+            samples.extend(worker.await.unwrap_or_default());
+        }
+
+        results.push(summarise(target, samples, started_at.elapsed()));
+    }
+    results
+}
+
+// Line shown to the user for one route
+pub fn load_summary(result: &RouteLoadResult) -> String {
+    format!(
+        "LOAD {} {}: {} requests, {:.1} req/s, {:.1}% errors, p50 {:.1}ms, p95 {:.1}ms, p99 {:.1}ms",
+        result.method,
+        result.route,
+        result.requests,
+        result.requests_per_sec,
+        result.error_rate * 100.0,
+        result.p50_ms,
+        result.p95_ms,
+        result.p99_ms
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::test_support::{spawn_fake_server, FakeResponse};
+
+    #[test]
+    fn test_percentile() {
+        let latencies: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&latencies, 50.0), 50.0);
+        assert_eq!(percentile(&latencies, 95.0), 95.0);
+        assert_eq!(percentile(&latencies, 99.0), 99.0);
+        assert_eq!(percentile(&latencies[..1], 99.0), 1.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_run_load_test() {
+        let base_url = spawn_fake_server(|request| match request.path.as_str() {
+            "/items" => FakeResponse::json(200, json!([])),
+            _ => FakeResponse::new(503, "busy"),
+        })
+        .await;
+        let targets = vec![
+            LoadTarget {
+                method: Method::GET,
+                path: String::from("/items"),
+                body: None,
+            },
+            LoadTarget {
+                method: Method::POST,
+                path: String::from("/item"),
+                body: Some(json!({"name": "sample"})),
+            },
+        ];
+
+        let results = run_load_test(
+            &Client::new(),
+            &base_url,
+            &targets,
+            4,
+            Duration::from_millis(300),
+        )
+        .await;
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].requests > 0);
+        assert_eq!(results[0].errors, 0);
+        assert!(results[0].requests_per_sec > 0.0);
+        assert!(results[0].p50_ms <= results[0].p99_ms);
+        assert_eq!(results[1].route, "/item");
+        assert_eq!(results[1].error_rate, 1.0);
+        assert!(load_summary(&results[1]).starts_with("LOAD POST /item: "));
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::PathBuf,
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{config::DbConfig, params_from_iter, types::Value as SqlValue, Connection};
+
+use crate::{
+    codegen::migrations::{quoted, tables, Column},
+    models::{ColumnType, DataModel},
+};
+
+// Database file removed again when the check is done
+struct TemporaryDatabase {
+    path: PathBuf,
+}
+
+impl TemporaryDatabase {
+    fn create() -> rusqlite::Result<(Self, Connection)> {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let path = env::temp_dir().join(format!(
+            "benjamin-migration-{}-{}.db",
+            process::id(),
+            started_at
+        ));
+        let connection = Connection::open(&path)?;
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+        // Otherwise a misspelled "column" silently reads as a string
+        connection.set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DML, false)?;
+        connection.set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DDL, false)?;
+        Ok((Self { path }, connection))
+    }
+}
+
+impl Drop for TemporaryDatabase {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// Value stored in a column of sample row N, distinct per row so unique columns never clash.
+// References point at row N of the referenced table, which is filled first
+fn sample_value(column: &Column, table: &str, row: i64, filled: &HashSet<String>) -> SqlValue {
+    if let Some(referenced) = &column.references {
+        // Only a reference cycle gets here before its parent rows exist
+        if referenced != table && !filled.contains(referenced) && column.is_optional {
+            return SqlValue::Null;
+        }
+        return SqlValue::Integer(row);
+    }
+    match column.column_type {
+        ColumnType::Integer => SqlValue::Integer(row),
+        ColumnType::Real => SqlValue::Real(row as f64 + 0.5),
+        ColumnType::Boolean => SqlValue::Integer(row % 2),
+        ColumnType::Text => SqlValue::Text(format!("{}-{}", column.name, row)),
+        ColumnType::Timestamp => SqlValue::Text(format!("2024-01-{:02}T00:00:00Z", row)),
+    }
+}
+
+// Apply the migration to a temporary SQLite database, twice as on every server start,
+// then write sample rows and prepare every data-access statement against it
+pub fn validate_sqlite_migration(
+    model: &DataModel,
+    migration: &str,
+    statements: &[String],
+) -> rusqlite::Result<Vec<String>> {
+    let (_database, connection) = TemporaryDatabase::create()?;
+    let mut problems: Vec<String> = vec![];
+    for run in ["first", "second"] {
+        if let Err(e) = connection.execute_batch(migration) {
+            problems.push(format!("migration failed on its {} run: {}", run, e));
+            return Ok(problems);
+        }
+    }
+
+    let mut filled: HashSet<String> = HashSet::new();
+    for table in tables(model) {
+        let mut statement =
+            connection.prepare(&format!("PRAGMA table_info({})", quoted(&table.name)))?;
+        let created: Vec<String> = statement
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        if created.is_empty() {
+            problems.push(format!("table {} was not created", table.name));
+            continue;
+        }
+        for name in table.column_names() {
+            if !created.iter().any(|column| column == name) {
+                problems.push(format!("table {} has no column {}", table.name, name));
+            }
+        }
+
+        // Two rows, so unique columns and references are exercised. A plain INSERT,
+        // so every constraint failure is reported; foreign keys are enforced
+        let names: Vec<String> = table.column_names().into_iter().map(quoted).collect();
+        for row in 1..=2 {
+            let values: Vec<SqlValue> = table
+                .columns
+                .iter()
+                .map(|column| sample_value(column, &table.name, row, &filled))
+                .collect();
+            let insert = if names.is_empty() {
+                format!("INSERT INTO {} DEFAULT VALUES", quoted(&table.name))
+            } else {
+                let placeholders: Vec<&str> = names.iter().map(|_| "?").collect();
+                format!(
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    quoted(&table.name),
+                    names.join(", "),
+                    placeholders.join(", ")
+                )
+            };
+            if let Err(e) = connection.execute(&insert, params_from_iter(values)) {
+                problems.push(format!("inserting into {} failed: {}", table.name, e));
+                break;
+            }
+        }
+        filled.insert(table.name.clone());
+    }
+
+    for sql in statements {
+        if let Err(e) = connection.prepare(sql) {
+            problems.push(format!(
+                "statement {:?} does not fit the schema: {}",
+                sql, e
+            ));
+        }
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        codegen::{
+            data_access::data_access_statements,
+            migrations::{migration, SqlDialect},
+        },
+        testing::test_support::blog_model,
+    };
+
+    #[test]
+    fn test_validate_sqlite_migration() {
+        let model = blog_model();
+        let problems = validate_sqlite_migration(
+            &model,
+            &migration(&model, SqlDialect::Sqlite),
+            &data_access_statements(&model),
+        )
+        .unwrap();
+        assert!(problems.is_empty(), "{:?}", problems);
+    }
+
+    #[test]
+    fn test_validate_sqlite_migration_problems() {
+        let model = blog_model();
+        let without_tags = migration(&model, SqlDialect::Sqlite).replace(
+            "CREATE TABLE IF NOT EXISTS \"tag\"",
+            "CREATE TABLE IF NOT EXISTS \"label\"",
+        );
+        let mut statements = data_access_statements(&model);
+        statements.push(String::from("SELECT \"missing\" FROM \"post\""));
+
+        let problems = validate_sqlite_migration(&model, &without_tags, &statements).unwrap();
+        assert!(problems.contains(&String::from("table tag was not created")));
+        assert!(problems
+            .iter()
+            .any(|problem| problem.starts_with("inserting into post_tag failed")));
+        assert!(problems.iter().any(|problem| problem
+            .starts_with("statement \"SELECT \\\"missing\\\" FROM \\\"post\\\"\" does not fit")));
+
+        let checked_title = migration(&model, SqlDialect::Sqlite).replace(
+            "\"title\" TEXT NOT NULL,",
+            "\"title\" TEXT NOT NULL CHECK (length(\"title\") > 20),",
+        );
+        let problems = validate_sqlite_migration(&model, &checked_title, &[]).unwrap();
+        assert_eq!(
+            problems[0],
+            "inserting into post failed: CHECK constraint failed: length(\"title\") > 20"
+        );
+
+        let broken = validate_sqlite_migration(&model, "CREATE TABLE \"post\" (", &[]).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert!(broken[0].starts_with("migration failed on its first run"));
+    }
+}
@@ -0,0 +1,306 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use reqwest::Url;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+use crate::models::ExternalApiSample;
+
+// Origin such as "https://api.example.com" and the mock path prefix standing in for it
+fn origin_and_prefix(url: &str) -> Option<(String, String)> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    let authority = match parsed.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    Some((
+        format!("{}://{}", parsed.scheme(), authority),
+        format!("/{}", authority),
+    ))
+}
+
+// Path on the mock server that replays the sample of url
+pub fn mock_path(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let (_, prefix) = origin_and_prefix(url)?;
+    let mut path = format!("{}{}", prefix, parsed.path());
+    if let Some(query) = parsed.query() {
+        path = format!("{}?{}", path, query);
+    }
+    Some(path)
+}
+
+// Point every recorded external origin in the code at the mock server
+pub fn point_code_at_mock(
+    code: &str,
+    samples: &[ExternalApiSample],
+    mock_base_url: &str,
+) -> String {
+    let mut origins: Vec<(String, String)> = samples
+        .iter()
+        .filter_map(|sample| origin_and_prefix(&sample.url))
+        .collect();
+    // Longer origins first, so one origin never rewrites part of another
+    origins.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then(a.cmp(b)));
+    origins.dedup();
+
+    origins
+        .iter()
+        .fold(code.to_string(), |code, (origin, prefix)| {
+            code.replace(origin.as_str(), &format!("{}{}", mock_base_url, prefix))
+        })
+}
+
+// Exact path first, then the same path with another query, then any sample of the same host
+fn find_sample<'a>(
+    samples: &'a [ExternalApiSample],
+    request_path: &str,
+) -> Option<&'a ExternalApiSample> {
+    let keyed: Vec<(String, &ExternalApiSample)> = samples
+        .iter()
+        .filter_map(|sample| Some((mock_path(&sample.url)?, sample)))
+        .collect();
+    let without_query = |path: &str| path.split('?').next().unwrap_or_default().to_string();
+    let host_of = |path: &str| path.split('/').nth(1).unwrap_or_default().to_string();
+
+    keyed
+        .iter()
+        .find(|(path, _)| path == request_path)
+        .or_else(|| {
+            keyed
+                .iter()
+                .find(|(path, _)| without_query(path) == without_query(request_path))
+        })
+        .or_else(|| {
+            keyed
+                .iter()
+                .find(|(path, _)| host_of(path) == host_of(request_path))
+        })
+        .map(|(_, sample)| *sample)
+}
+
+fn render_response(sample: Option<&ExternalApiSample>) -> String {
+    let (status, content_type, body) = match sample {
+        Some(sample) => (
+            sample.status,
+            sample
+                .content_type
+                .clone()
+                .unwrap_or(String::from("application/json")),
+            sample.body.clone(),
+        ),
+        None => (
+            404,
+            String::from("application/json"),
+            String::from(r#"{"error":"no recorded sample for this url"}"#),
+        ),
+    };
+    format!(
+        "HTTP/1.1 {} Mock\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+// Request target of the request line, once the headers have arrived
+async fn read_request_path(stream: &mut TcpStream) -> Option<String> {
+    let mut raw: Vec<u8> = vec![];
+    let mut buffer = [0u8; 4096];
+    while !raw.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        raw.extend_from_slice(&buffer[..read]);
+    }
+    let head = String::from_utf8_lossy(&raw).to_string();
+    head.lines()
+        .next()?
+        .split_whitespace()
+        .nth(1)
+        .map(String::from)
+}
+
+// Local server replaying the sample responses recorded by the architect
+pub struct MockServer {
+    base_url: String,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start(samples: Vec<ExternalApiSample>) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let base_url = format!("http://{}", listener.local_addr()?);
+        let samples = Arc::new(samples);
+
+        let task = tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let samples = samples.clone();
+                tokio::spawn(async move {
+                    let Some(path) = read_request_path(&mut stream).await else {
+                        return;
+                    };
+                    let response = render_response(find_sample(&samples, &path));
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        Ok(Self { base_url, task })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+// Copy of the web server project whose main.rs calls the mock server,
+// so the real sources are never touched, even if Benjamin is killed
+pub struct MockProject {
+    dir: PathBuf,
+}
+
+fn copy_project(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == "target" || name == ".git" {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            copy_project(&entry.path(), &to.join(&name))?;
+        } else {
+            fs::copy(entry.path(), to.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+impl MockProject {
+    pub fn create(project_dir: &Path, main_path: &Path, main_code: &str) -> io::Result<Self> {
+        let dir =
+            std::env::temp_dir().join(format!("benjamin-mock-project-{}", std::process::id()));
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        copy_project(project_dir, &dir)?;
+        let relative_main = main_path
+            .strip_prefix(project_dir)
+            .unwrap_or(Path::new("src/main.rs"));
+        fs::write(dir.join(relative_main), main_code)?;
+        Ok(Self { dir })
+    }
+
+    pub fn manifest_path(&self) -> PathBuf {
+        self.dir.join("Cargo.toml")
+    }
+}
+
+impl Drop for MockProject {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample(url: &str, body: &str) -> ExternalApiSample {
+        ExternalApiSample {
+            url: url.to_string(),
+            status: 200,
+            content_type: Some(String::from("application/json")),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_point_code_at_mock() {
+        let samples = vec![
+            sample("https://api.rates.io/v1/latest?base=USD", "{}"),
+            sample("http://localhost:9000/prices", "{}"),
+        ];
+        let code = r#"let url = format!("https://api.rates.io/v1/latest?base={}", base);
+let prices = reqwest::get("http://localhost:9000/prices").await?;"#;
+
+        let mocked = point_code_at_mock(code, &samples, "http://127.0.0.1:4000");
+        assert_eq!(
+            mocked,
+            r#"let url = format!("http://127.0.0.1:4000/api.rates.io/v1/latest?base={}", base);
+let prices = reqwest::get("http://127.0.0.1:4000/localhost:9000/prices").await?;"#
+        );
+        assert_eq!(
+            mock_path(&samples[0].url).as_deref(),
+            Some("/api.rates.io/v1/latest?base=USD")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_server() {
+        let samples = vec![
+            sample(
+                "https://api.rates.io/v1/latest?base=USD",
+                r#"{"rate": 1.1}"#,
+            ),
+            sample("https://api.rates.io/v1/history", r#"{"history": []}"#),
+        ];
+        let mock = MockServer::start(samples).await.unwrap();
+        let client = reqwest::Client::new();
+        let get = |path: &str| client.get(format!("{}{}", mock.base_url(), path)).send();
+
+        let exact = get("/api.rates.io/v1/latest?base=USD").await.unwrap();
+        assert_eq!(exact.text().await.unwrap(), r#"{"rate": 1.1}"#);
+
+        let other_query = get("/api.rates.io/v1/latest?base=EUR").await.unwrap();
+        assert_eq!(other_query.text().await.unwrap(), r#"{"rate": 1.1}"#);
+
+        let same_host = get("/api.rates.io/v2/other").await.unwrap();
+        assert_eq!(same_host.status(), 200);
+
+        let unknown = get("/elsewhere.com/").await.unwrap();
+        assert_eq!(unknown.status(), 404);
+    }
+
+    #[test]
+    fn test_mock_project() {
+        let project_dir = std::env::temp_dir().join("benjamin-mock-project-test");
+        let _ = fs::remove_dir_all(&project_dir);
+        fs::create_dir_all(project_dir.join("src")).unwrap();
+        fs::create_dir_all(project_dir.join("target")).unwrap();
+        fs::write(project_dir.join("Cargo.toml"), "[package]").unwrap();
+        let main_path = project_dir.join("src").join("main.rs");
+        fs::write(&main_path, "original").unwrap();
+
+        let mock_project = MockProject::create(&project_dir, &main_path, "mocked").unwrap();
+        let copy_dir = mock_project.manifest_path().parent().unwrap().to_path_buf();
+        assert_eq!(fs::read_to_string(&main_path).unwrap(), "original");
+        assert_eq!(
+            fs::read_to_string(copy_dir.join("src").join("main.rs")).unwrap(),
+            "mocked"
+        );
+        assert!(copy_dir.join("Cargo.toml").exists());
+        assert!(!copy_dir.join("target").exists());
+
+        drop(mock_project);
+        assert!(!copy_dir.exists());
+        fs::remove_dir_all(&project_dir).unwrap();
+    }
+}
@@ -0,0 +1,16 @@
+pub mod auth;
+pub mod contract;
+pub mod crud;
+pub mod endpoint;
+pub mod frontend;
+pub mod fuzz;
+pub mod load;
+pub mod migrations;
+pub mod mock;
+pub mod payload;
+pub mod regression;
+pub mod routes;
+pub mod shape;
+
+#[cfg(test)]
+pub mod test_support;
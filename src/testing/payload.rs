@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use reqwest::Method;
+use serde_json::{Map, Value};
+
+use crate::models::FieldSchema;
+
+// Build an example value that matches a RouteObject schema
+pub fn sample_value(schema: &FieldSchema) -> Value {
+    match schema {
+        FieldSchema::Empty => Value::Null,
+        FieldSchema::Number => Value::from(1),
+        FieldSchema::Bool => Value::Bool(true),
+        FieldSchema::Optional(inner) => sample_value(inner),
+        FieldSchema::Object(fields) => {
+            let sample: Map<String, Value> = fields
+                .iter()
+                .map(|(key, field)| (key.clone(), sample_value(field)))
+                .collect();
+            Value::Object(sample)
+        }
+        FieldSchema::Array(item) => match item.as_ref() {
+            FieldSchema::Unknown => Value::Array(vec![]),
+            item => Value::Array(vec![sample_value(item)]),
+        },
+        FieldSchema::String | FieldSchema::Unknown | FieldSchema::Named(_) => {
+            Value::String(String::from("sample"))
+        }
+    }
+}
+
+// Request payload for a route, if its schema describes one
+pub fn build_request_body(schema: &FieldSchema) -> Option<Value> {
+    match schema {
+        FieldSchema::Empty | FieldSchema::Unknown => None,
+        schema => Some(sample_value(schema)),
+    }
+}
+
+// Example value for a placeholder no earlier request provided
+pub fn default_placeholder_value(name: &str) -> String {
+    if name.to_lowercase().contains("id") {
+        String::from("1")
+    } else {
+        String::from("sample")
+    }
+}
+
+// Replace {placeholders} with known values, falling back to examples
+pub fn fill_route_placeholders(route: &str, values: &HashMap<String, String>) -> String {
+    route
+        .split('/')
+        .map(|segment| {
+            match segment
+                .strip_prefix('{')
+                .and_then(|name| name.strip_suffix('}'))
+            {
+                Some(name) => values
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| default_placeholder_value(name)),
+                None => segment.to_string(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+// Parse a method name regardless of case
+pub fn parse_method(method: &str) -> Option<Method> {
+    Method::from_bytes(method.trim().to_uppercase().as_bytes()).ok()
+}
+
+// Order in which methods are exercised, so data exists before it is read or deleted
+pub fn method_rank(method: &Method) -> u8 {
+    match *method {
+        Method::POST => 0,
+        Method::GET => 1,
+        Method::PUT | Method::PATCH => 2,
+        Method::DELETE => 4,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_request_body() {
+        let schema = FieldSchema::from(json!({
+            "id": "number",
+            "name": "string",
+            "completed": "bool",
+            "tags": ["string"],
+            "owner": {"id": "u64"}
+        }));
+        let body = build_request_body(&schema).expect("Expected a body");
+        assert_eq!(
+            body,
+            json!({
+                "id": 1,
+                "name": "sample",
+                "completed": true,
+                "tags": ["sample"],
+                "owner": {"id": 1}
+            })
+        );
+
+        assert_eq!(build_request_body(&FieldSchema::from(json!("None"))), None);
+        assert_eq!(
+            build_request_body(&FieldSchema::from(json!("not_provided"))),
+            None
+        );
+        assert_eq!(build_request_body(&FieldSchema::from(Value::Null)), None);
+    }
+
+    #[test]
+    fn test_fill_route_placeholders() {
+        let mut values = HashMap::new();
+        values.insert(String::from("tag"), String::from("urgent"));
+        assert_eq!(
+            fill_route_placeholders("/item/{id}/tags/{tag}", &values),
+            "/item/1/tags/urgent"
+        );
+        assert_eq!(fill_route_placeholders("/items", &values), "/items");
+    }
+
+    #[test]
+    fn test_parse_method() {
+        assert_eq!(parse_method("get"), Some(Method::GET));
+        assert_eq!(parse_method(" DELETE "), Some(Method::DELETE));
+        assert_eq!(parse_method("not a method"), None);
+        assert!(method_rank(&Method::POST) < method_rank(&Method::DELETE));
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use crate::{
+    models::{FieldSchema, RouteObject},
+    testing::{
+        routes::RouteCheck,
+        shape::{infer_shape, validate_shape},
+    },
+};
+
+// Status and response shape of a route the last time it passed
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteSnapshot {
+    pub status: u16,
+    pub shape: FieldSchema,
+}
+
+// A route that passed in an earlier version and is broken now
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub route: String,
+    pub previous: RouteSnapshot,
+    pub status: Option<u16>,
+    // Response shape changes, or why the route got no response at all
+    pub changes: Vec<String>,
+}
+
+impl Regression {
+    pub fn summary(&self) -> String {
+        let now = self
+            .status
+            .map(|status| status.to_string())
+            .unwrap_or(String::from("no response"));
+        let mut summary = format!(
+            "REGRESSION {}: was {}, now {}",
+            self.route, self.previous.status, now
+        );
+        if !self.changes.is_empty() {
+            summary = format!("{} ({})", summary, self.changes.join(", "));
+        }
+        summary
+    }
+
+    // Failure description sent back through the fix loop
+    pub fn bug_report(&self) -> String {
+        format!(
+            "{} \n PREVIOUS RESPONSE SHAPE: {} \n This route worked in the previous version, keep its old behaviour",
+            self.summary(),
+            self.previous.shape
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapshotComparison {
+    pub regressions: Vec<Regression>,
+    pub fixes: Vec<String>,
+}
+
+impl SnapshotComparison {
+    // Roll back when a new version breaks more routes than it repairs
+    pub fn should_roll_back(&self) -> bool {
+        self.regressions.len() > self.fixes.len()
+    }
+}
+
+// Route behaviour recorded across code versions
+#[derive(Debug, Clone, Default)]
+pub struct EndpointSnapshot {
+    routes: HashMap<String, RouteSnapshot>,
+    failing: HashSet<String>,
+    code: Option<String>,
+    api_endpoints: Option<Vec<RouteObject>>,
+}
+
+fn check_key(check: &RouteCheck) -> String {
+    format!("{} {}", check.route.method, check.route.route)
+}
+
+fn body_shape(body: &str) -> FieldSchema {
+    serde_json::from_str::<Value>(body)
+        .map(|value| FieldSchema::from(infer_shape(&value)))
+        .unwrap_or(FieldSchema::String)
+}
+
+impl EndpointSnapshot {
+    // Code of the version the snapshot was last recorded from
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    // Routes extracted from that version
+    pub fn api_endpoints(&self) -> Option<&[RouteObject]> {
+        self.api_endpoints.as_deref()
+    }
+
+    pub fn compare(&self, route_checks: &[RouteCheck]) -> SnapshotComparison {
+        let mut comparison = SnapshotComparison::default();
+        for check in route_checks {
+            let key = check_key(check);
+            let Some(previous) = self.routes.get(&key) else {
+                if !check.is_bug() && self.failing.contains(&key) {
+                    comparison.fixes.push(key);
+                }
+                continue;
+            };
+
+            let changes = if check.result.passed() {
+                validate_shape(&previous.shape, &check.result.body)
+            } else {
+                vec![]
+            };
+            if !check.result.passed() || !changes.is_empty() {
+                comparison.regressions.push(Regression {
+                    route: key,
+                    previous: previous.clone(),
+                    status: check.result.status,
+                    changes,
+                });
+            } else if self.failing.contains(&key) {
+                comparison.fixes.push(key);
+            }
+        }
+
+        // Routes a rewrite dropped never show up in the checks
+        let checked: HashSet<String> = route_checks.iter().map(check_key).collect();
+        let mut dropped: Vec<(&String, &RouteSnapshot)> = self
+            .routes
+            .iter()
+            .filter(|(key, _)| !checked.contains(*key))
+            .collect();
+        dropped.sort_by_key(|(key, _)| *key);
+        for (key, previous) in dropped {
+            comparison.regressions.push(Regression {
+                route: key.clone(),
+                previous: previous.clone(),
+                status: None,
+                changes: vec![String::from("no longer served")],
+            });
+        }
+        comparison
+    }
+
+    // Keep the latest passing behaviour of every route
+    pub fn record(
+        &mut self,
+        route_checks: &[RouteCheck],
+        code: &str,
+        api_endpoints: &[RouteObject],
+    ) {
+        self.failing.clear();
+        for check in route_checks {
+            let key = check_key(check);
+            match check.result.status {
+                Some(status) if check.result.passed() && !check.is_bug() => {
+                    let shape = body_shape(&check.result.body);
+                    self.routes.insert(key, RouteSnapshot { status, shape });
+                }
+                _ => {
+                    self.failing.insert(key);
+                }
+            }
+        }
+        self.code = Some(code.to_string());
+        self.api_endpoints = Some(api_endpoints.to_vec());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::{header::HeaderMap, Method};
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::{endpoint::EndpointResult, test_support::test_route};
+
+    fn check(method: Method, route: &str, status: u16, body: &str) -> RouteCheck {
+        RouteCheck {
+            route: test_route(method.as_str(), route, json!("None")),
+            result: EndpointResult {
+                method,
+                route: route.to_string(),
+                status: Some(status),
+                headers: HeaderMap::new(),
+                body: body.to_string(),
+                error: None,
+                server_logs: vec![],
+            },
+            shape_errors: vec![],
+            guessed_placeholders: false,
+            protected: false,
+        }
+    }
+
+    #[test]
+    fn test_compare_snapshots() {
+        let mut snapshot = EndpointSnapshot::default();
+        snapshot.record(
+            &[
+                check(Method::GET, "/items", 200, r#"[{"id": 1}]"#),
+                check(Method::POST, "/item", 201, r#"{"id": 1}"#),
+                check(Method::DELETE, "/item/{id}", 500, "boom"),
+            ],
+            "fn main() {}",
+            &[test_route("get", "/items", json!("None"))],
+        );
+        assert_eq!(snapshot.code(), Some("fn main() {}"));
+        assert_eq!(snapshot.api_endpoints().map(|routes| routes.len()), Some(1));
+
+        let comparison = snapshot.compare(&[
+            check(Method::GET, "/items", 200, r#"[{"id": "1"}]"#),
+            check(Method::POST, "/item", 500, "boom"),
+            check(Method::DELETE, "/item/{id}", 200, ""),
+        ]);
+        assert_eq!(comparison.fixes, vec!["DELETE /item/{id}"]);
+        let summaries: Vec<String> = comparison.regressions.iter().map(|r| r.summary()).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                "REGRESSION GET /items: was 200, now 200 (response[0].id should be number but is string)",
+                "REGRESSION POST /item: was 201, now 500",
+            ]
+        );
+        assert!(comparison.should_roll_back());
+        assert!(comparison.regressions[1]
+            .bug_report()
+            .contains("PREVIOUS RESPONSE SHAPE: {\"id\":\"number\"}"));
+
+        let unchanged = snapshot.compare(&[
+            check(Method::GET, "/items", 200, r#"[{"id": 2}]"#),
+            check(Method::POST, "/item", 201, r#"{"id": 2}"#),
+        ]);
+        assert_eq!(unchanged, SnapshotComparison::default());
+
+        let dropped = snapshot.compare(&[check(Method::GET, "/items", 200, r#"[{"id": 2}]"#)]);
+        let summaries: Vec<String> = dropped.regressions.iter().map(|r| r.summary()).collect();
+        assert_eq!(
+            summaries,
+            vec!["REGRESSION POST /item: was 201, now no response (no longer served)"]
+        );
+        assert!(dropped.should_roll_back());
+    }
+}
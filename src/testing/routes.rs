@@ -0,0 +1,278 @@
+use std::{collections::HashMap, io};
+
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::{
+    analysis::actix_routes::path_key,
+    helpers::process::ServerProcess,
+    models::{RouteObject, RouteTestResult},
+    testing::{
+        endpoint::{send_request, EndpointResult},
+        payload::{build_request_body, fill_route_placeholders, method_rank},
+        shape::validate_shape,
+    },
+};
+
+// Outcome of exercising one route of the extracted schema
+#[derive(Debug, Clone)]
+pub struct RouteCheck {
+    pub route: RouteObject,
+    pub result: EndpointResult,
+    pub shape_errors: Vec<String>,
+    // Some placeholder got an example value rather than an id the server returned
+    pub guessed_placeholders: bool,
+    // Declared as protected, so it may refuse a request without a session
+    pub protected: bool,
+}
+
+impl RouteCheck {
+    fn refused_login(&self) -> bool {
+        self.protected && matches!(self.result.status, Some(401 | 403))
+    }
+
+    // Guessed ids may not exist, so a 404 for one is not a bug,
+    // and neither is a protected route asking for a login
+    pub fn is_bug(&self) -> bool {
+        let missing_resource = self.guessed_placeholders && self.result.status == Some(404);
+        let expected_refusal = missing_resource || self.refused_login();
+        (!self.result.passed() && !expected_refusal) || !self.shape_errors.is_empty()
+    }
+
+    // Per route line shown to the user
+    pub fn summary(&self) -> String {
+        let verdict = if self.is_bug() {
+            "FAIL"
+        } else if self.result.passed() {
+            "PASS"
+        } else {
+            "WARN"
+        };
+        let mut summary = format!("{} {}", verdict, self.result.describe());
+        if !self.shape_errors.is_empty() {
+            summary = format!("{} ({})", summary, self.shape_errors.join(", "));
+        }
+        summary
+    }
+
+    // Failure description sent back through the fix loop
+    pub fn bug_report(&self) -> String {
+        let mut report = self.result.bug_report();
+        if !self.shape_errors.is_empty() {
+            report = format!(
+                "{} \n EXPECTED RESPONSE SHAPE: {} \n RESPONSE SHAPE MISMATCHES: {}",
+                report,
+                self.route.response,
+                self.shape_errors.join(", ")
+            );
+        }
+        report
+    }
+
+    // Record kept in the FactSheet
+    pub fn to_test_result(&self) -> RouteTestResult {
+        let mut errors = self.shape_errors.clone();
+        if let Some(error) = &self.result.error {
+            errors.push(error.clone());
+        }
+        RouteTestResult {
+            method: self.result.method.to_string(),
+            route: self.result.route.clone(),
+            status: self.result.status,
+            passed: !self.is_bug(),
+            errors,
+        }
+    }
+}
+
+// Reuse the id of a created resource for later {id} placeholders
+fn remember_created_id(body: &str, placeholder_values: &mut HashMap<String, String>) {
+    let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(body) else {
+        return;
+    };
+    match fields.get("id") {
+        Some(Value::Number(id)) => {
+            placeholder_values.insert(String::from("id"), id.to_string());
+        }
+        Some(Value::String(id)) => {
+            placeholder_values.insert(String::from("id"), id.clone());
+        }
+        _ => {}
+    }
+}
+
+// Send a request with an example body to every route, stopping if the server dies
+pub async fn check_routes(
+    client: &Client,
+    server: &mut ServerProcess,
+    base_url: &str,
+    routes: &[RouteObject],
+    protected_routes: &[String],
+) -> io::Result<Vec<RouteCheck>> {
+    let protected_keys: Vec<String> = protected_routes.iter().map(|path| path_key(path)).collect();
+    let mut ordered: Vec<(reqwest::Method, &RouteObject)> = routes
+        .iter()
+        .map(|route| (route.method.to_method(), route))
+        .collect();
+    ordered.sort_by_key(|(method, _)| method_rank(method));
+
+    let mut placeholder_values: HashMap<String, String> = HashMap::new();
+    let mut checks: Vec<RouteCheck> = vec![];
+    for (method, route) in ordered {
+        let path = fill_route_placeholders(&route.route, &placeholder_values);
+        let guessed_placeholders = route
+            .path_params
+            .iter()
+            .any(|name| !placeholder_values.contains_key(name));
+        let body = build_request_body(&route.request_body);
+        let result =
+            send_request(client, server.log(), base_url, method, &path, body.as_ref()).await;
+
+        let passed = result.passed();
+        let shape_errors = if passed {
+            remember_created_id(&result.body, &mut placeholder_values);
+            validate_shape(&route.response, &result.body)
+        } else {
+            vec![]
+        };
+        checks.push(RouteCheck {
+            route: route.clone(),
+            result,
+            shape_errors,
+            guessed_placeholders,
+            protected: protected_keys.contains(&path_key(&route.route)),
+        });
+        if !passed && server.has_exited()?.is_some() {
+            break;
+        }
+    }
+    Ok(checks)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        models::FieldSchema,
+        testing::test_support::{spawn_fake_server, test_route as route, FakeResponse},
+    };
+
+    #[tokio::test]
+    async fn test_check_routes() {
+        let base_url =
+            spawn_fake_server(
+                |request| match (request.method.as_str(), request.path.as_str()) {
+                    ("POST", "/item") if request.body.contains("\"name\":\"sample\"") => {
+                        FakeResponse::json(201, json!({"id": 7, "name": "sample"}))
+                    }
+                    ("GET", "/item/7") => FakeResponse::new(404, "not found"),
+                    ("DELETE", "/item/7") => FakeResponse::new(500, "boom"),
+                    ("GET", "/items") => FakeResponse::json(200, json!([{"id": "7"}])),
+                    ("GET", path) if path.starts_with("/order/") => {
+                        FakeResponse::new(404, "not found")
+                    }
+                    _ => FakeResponse::new(400, "bad request"),
+                },
+            )
+            .await;
+        let mut server = ServerProcess::spawn(
+            "sh",
+            &[String::from("-c"), String::from("sleep 30")],
+            ".",
+            &[],
+            &std::env::temp_dir().join("benjamin-routes-test.log"),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+
+        let mut list_route = route("get", "/items", json!("None"));
+        list_route.response = FieldSchema::from(json!([{"id": "number"}]));
+        let routes = vec![
+            route("delete", "/item/{id}", json!("None")),
+            route("get", "/item/{id}", json!("None")),
+            route("post", "/item", json!({"name": "string"})),
+            route("get", "/order/{order_id}", json!("None")),
+            list_route,
+        ];
+        let checks = check_routes(&Client::new(), &mut server, &base_url, &routes, &[])
+            .await
+            .unwrap();
+        server.shutdown().await.unwrap();
+
+        let summaries: Vec<String> = checks.iter().map(|check| check.summary()).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                "PASS POST /item -> 201",
+                "FAIL GET /item/7 -> 404",
+                "WARN GET /order/1 -> 404",
+                "FAIL GET /items -> 200 (response[0].id should be number but is string)",
+                "FAIL DELETE /item/7 -> 500",
+            ]
+        );
+        assert!(checks[1].bug_report().contains("GET /item/7"));
+        assert!(checks[3]
+            .bug_report()
+            .contains("EXPECTED RESPONSE SHAPE: [{\"id\":\"number\"}]"));
+
+        let test_result = checks[3].to_test_result();
+        assert_eq!(test_result.method, "GET");
+        assert_eq!(test_result.status, Some(200));
+        assert!(!test_result.passed);
+        assert!(checks[2].to_test_result().passed);
+    }
+
+    #[tokio::test]
+    async fn test_protected_route_checks() {
+        let base_url = spawn_fake_server(|request| match request.path.as_str() {
+            "/health" => FakeResponse::new(200, "ok"),
+            "/admin" => FakeResponse::new(403, "forbidden"),
+            _ => FakeResponse::new(401, "login required"),
+        })
+        .await;
+        let mut server = ServerProcess::spawn(
+            "sh",
+            &[String::from("-c"), String::from("sleep 30")],
+            ".",
+            &[],
+            &std::env::temp_dir().join("benjamin-protected-routes-test.log"),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+
+        let routes = vec![
+            route("get", "/health", json!("None")),
+            route("get", "/todos", json!("None")),
+            route("get", "/todos/{id}", json!("None")),
+            route("get", "/admin", json!("None")),
+        ];
+        let protected_routes = vec![String::from("/todos/{todo_id}"), String::from("/admin")];
+        let checks = check_routes(
+            &Client::new(),
+            &mut server,
+            &base_url,
+            &routes,
+            &protected_routes,
+        )
+        .await
+        .unwrap();
+        server.shutdown().await.unwrap();
+
+        let summaries: Vec<String> = checks.iter().map(|check| check.summary()).collect();
+        assert_eq!(
+            summaries,
+            vec![
+                "PASS GET /health -> 200",
+                "FAIL GET /todos -> 401",
+                "WARN GET /todos/1 -> 401",
+                "WARN GET /admin -> 403",
+            ]
+        );
+        assert!(checks[2].protected);
+        assert!(checks[3].to_test_result().passed);
+    }
+}
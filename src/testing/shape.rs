@@ -0,0 +1,165 @@
+use serde_json::{Map, Value};
+
+use crate::models::FieldSchema;
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn collect_mismatches(
+    path: &str,
+    schema: &FieldSchema,
+    value: &Value,
+    mismatches: &mut Vec<String>,
+) {
+    let mut mismatch = |expected: &str| {
+        mismatches.push(format!(
+            "{} should be {} but is {}",
+            path,
+            expected,
+            type_name(value)
+        ))
+    };
+    match schema {
+        // Unknown kinds, such as struct names, are not checked
+        schema if schema.is_unchecked() => {}
+        FieldSchema::Optional(_) if value.is_null() => {}
+        FieldSchema::Optional(inner) => collect_mismatches(path, inner, value, mismatches),
+        FieldSchema::Number if !value.is_number() => mismatch("number"),
+        FieldSchema::String if !value.is_string() => mismatch("string"),
+        FieldSchema::Bool if !value.is_boolean() => mismatch("bool"),
+        FieldSchema::Object(fields) => {
+            let Value::Object(actual) = value else {
+                mismatch("object");
+                return;
+            };
+            for (key, field_schema) in fields {
+                let field_path = format!("{}.{}", path, key);
+                match actual.get(key) {
+                    None if matches!(field_schema, FieldSchema::Optional(_)) => {}
+                    None => mismatches.push(format!("{} is missing", field_path)),
+                    Some(field) => collect_mismatches(&field_path, field_schema, field, mismatches),
+                }
+            }
+        }
+        FieldSchema::Array(item_schema) => {
+            let Value::Array(actual) = value else {
+                mismatch("array");
+                return;
+            };
+            for (index, item) in actual.iter().enumerate() {
+                let before = mismatches.len();
+                collect_mismatches(
+                    &format!("{}[{}]", path, index),
+                    item_schema,
+                    item,
+                    mismatches,
+                );
+                // One broken item is enough to describe the problem
+                if mismatches.len() > before {
+                    break;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// Type sketch of a response body, in the form the extraction step uses
+pub fn infer_shape(value: &Value) -> Value {
+    match value {
+        Value::Null => Value::from("null"),
+        Value::Bool(_) => Value::from("bool"),
+        Value::Number(_) => Value::from("number"),
+        Value::String(_) => Value::from("string"),
+        Value::Array(items) => Value::Array(items.first().map(infer_shape).into_iter().collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, field)| (key.clone(), infer_shape(field)))
+                .collect::<Map<String, Value>>(),
+        ),
+    }
+}
+
+// Differences between a response body and the route's response schema
+pub fn validate_shape(schema: &FieldSchema, body: &str) -> Vec<String> {
+    if schema.is_unchecked() {
+        return vec![];
+    }
+
+    match serde_json::from_str::<Value>(body) {
+        Ok(value) => {
+            let mut mismatches: Vec<String> = vec![];
+            collect_mismatches("response", schema, &value, &mut mismatches);
+            mismatches
+        }
+        // Plain text is fine when the schema only promises a string
+        Err(_) if *schema == FieldSchema::String => vec![],
+        Err(_) => vec![String::from("response is not JSON")],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_shape() {
+        let sketch = FieldSchema::from(
+            json!({"id": "number", "name": "string", "tags": ["string"], "note": "Option<string>"}),
+        );
+
+        let valid = r#"{"id": 1, "name": "a", "tags": ["x", "y"], "extra": true}"#;
+        assert!(validate_shape(&sketch, valid).is_empty());
+
+        let invalid = r#"{"id": "1", "tags": ["x", 2], "note": null}"#;
+        assert_eq!(
+            validate_shape(&sketch, invalid),
+            vec![
+                "response.id should be number but is string",
+                "response.name is missing",
+                "response.tags[1] should be string but is number",
+            ]
+        );
+
+        assert_eq!(
+            validate_shape(&sketch, "<html>oops</html>"),
+            vec!["response is not JSON"]
+        );
+        assert_eq!(
+            validate_shape(
+                &FieldSchema::from(json!([{"id": "number"}])),
+                r#"{"id": 1}"#
+            ),
+            vec!["response should be array but is object"]
+        );
+    }
+
+    #[test]
+    fn test_validate_shape_unchecked() {
+        let check = |sketch: Value, body: &str| validate_shape(&FieldSchema::from(sketch), body);
+        assert!(check(json!("None"), "anything").is_empty());
+        assert!(check(json!("not_provided"), "").is_empty());
+        assert!(check(json!("string"), "plain text").is_empty());
+        assert!(check(json!({"price": "PriceData"}), r#"{"price": {"usd": 1}}"#).is_empty());
+    }
+
+    #[test]
+    fn test_infer_shape() {
+        let body = json!({"id": 1, "tags": ["a"], "empty": [], "note": null, "done": false});
+        assert_eq!(
+            infer_shape(&body),
+            json!({"id": "number", "tags": ["string"], "empty": [], "note": "null", "done": "bool"})
+        );
+    }
+}
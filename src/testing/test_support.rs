@@ -0,0 +1,169 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::models::{DataModel, FieldSchema, RouteObject};
+
+// Request as seen by the fake server
+#[derive(Debug, Clone)]
+pub struct FakeRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+// Response returned by the fake server
+#[derive(Debug, Clone)]
+pub struct FakeResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl FakeResponse {
+    pub fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: body.to_string(),
+        }
+    }
+
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![(
+                String::from("content-type"),
+                String::from("application/json"),
+            )],
+            body: body.to_string(),
+        }
+    }
+
+    pub fn with_header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+}
+
+// Data model with every kind of relationship
+pub fn blog_model() -> DataModel {
+    serde_json::from_value(serde_json::json!({
+        "entities": [
+            {"name": "Post", "fields": [
+                {"name": "title", "type": "string"},
+                {"name": "published", "type": "bool"},
+                {"name": "published_at", "type": "timestamp", "is_optional": true}
+            ]},
+            {"name": "User", "fields": [
+                {"name": "id", "type": "integer"},
+                {"name": "email", "type": "string", "is_unique": true}
+            ]},
+            {"name": "Tag", "fields": [{"name": "label", "type": "string"}]},
+            {"name": "Profile", "fields": [{"name": "bio", "type": "text"}]}
+        ],
+        "relationships": [
+            {"from": "User", "to": "Post", "kind": "one_to_many"},
+            {"from": "User", "to": "Profile", "kind": "one_to_one"},
+            {"from": "Post", "to": "Tag", "kind": "many_to_many"}
+        ]
+    }))
+    .unwrap()
+}
+
+// Route as the extraction step would describe it
+pub fn test_route(method: &str, path: &str, request_body: serde_json::Value) -> RouteObject {
+    RouteObject::new(
+        method.parse().expect("Unknown method in test route"),
+        path,
+        FieldSchema::from(request_body),
+        FieldSchema::Empty,
+    )
+}
+
+type Handler = dyn Fn(&FakeRequest) -> FakeResponse + Send + Sync;
+
+async fn read_request(stream: &mut TcpStream) -> Option<FakeRequest> {
+    let mut raw: Vec<u8> = vec![];
+    let mut buffer = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        raw.extend_from_slice(&buffer[..read]);
+        if let Some(pos) = raw.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&raw[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let content_length: usize = headers
+        .get("content-length")
+        .and_then(|len| len.parse().ok())
+        .unwrap_or(0);
+    while raw.len() < header_end + content_length {
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        raw.extend_from_slice(&buffer[..read]);
+    }
+    let body_end = raw.len().min(header_end + content_length);
+    let body = String::from_utf8_lossy(&raw[header_end..body_end]).to_string();
+
+    Some(FakeRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+// Minimal HTTP server for tests, answering every request with handler
+pub async fn spawn_fake_server<F>(handler: F) -> String
+where
+    F: Fn(&FakeRequest) -> FakeResponse + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let handler: Arc<Handler> = Arc::new(handler);
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let Some(request) = read_request(&mut stream).await else {
+                    return;
+                };
+                let response = handler(&request);
+                let mut raw = format!(
+                    "HTTP/1.1 {} Fake\r\ncontent-length: {}\r\nconnection: close\r\n",
+                    response.status,
+                    response.body.len()
+                );
+                for (key, value) in &response.headers {
+                    raw.push_str(&format!("{}: {}\r\n", key, value));
+                }
+                raw.push_str("\r\n");
+                raw.push_str(&response.body);
+                let _ = stream.write_all(raw.as_bytes()).await;
+            });
+        }
+    });
+
+    base_url
+}